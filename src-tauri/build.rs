@@ -1,3 +1,6 @@
 fn main() {
-    tauri_build::build()
+    // The CLI is built without the webview, so there is no Tauri context to generate
+    if std::env::var_os("CARGO_FEATURE_GUI").is_some() {
+        tauri_build::build()
+    }
 }
@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+#[cfg(any(feature = "gui", test))]
+use std::fs;
+#[cfg(any(feature = "gui", test))]
+use std::path::Path;
+
+#[cfg(any(feature = "gui", test))]
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Downloaded,
+    /// An identical file was already on disk and was kept instead.
+    Deduplicated,
+    NotFound,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchReportRow {
+    pub row_number: usize,
+    pub query: String,
+    pub video_id: Option<String>,
+    pub video_title: Option<String>,
+    pub match_score: Option<i32>,
+    pub status: BatchItemStatus,
+    pub error: Option<String>,
+    pub output_path: Option<String>,
+}
+
+/// An input that never became a download, such as a streaming link that couldn't be resolved
+/// or a track list row without a title.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputError {
+    /// The line or track list row the input came from.
+    pub row_number: usize,
+    /// What was entered, empty when nothing usable could be read from the row.
+    pub input: String,
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(row_number: usize, input: &str, message: impl Into<String>) -> Self {
+        InputError {
+            row_number,
+            input: input.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.input.is_empty() {
+            write!(f, "Row {}: {}", self.row_number, self.message)
+        } else {
+            write!(f, "{}: {}", self.input, self.message)
+        }
+    }
+}
+
+/// A failed row for an input that never became a download.
+#[cfg(any(feature = "gui", test))]
+pub fn input_error_row(error: &InputError) -> BatchReportRow {
+    BatchReportRow {
+        row_number: error.row_number,
+        query: error.input.clone(),
+        video_id: None,
+        video_title: None,
+        match_score: None,
+        status: BatchItemStatus::Failed,
+        error: Some(error.message.clone()),
+        output_path: None,
+    }
+}
+
+/// A batch's rows with a failed row for each input error, in row order.
+#[cfg(any(feature = "gui", test))]
+pub fn batch_report(
+    mut rows: Vec<BatchReportRow>,
+    input_errors: &[InputError],
+) -> Vec<BatchReportRow> {
+    rows.extend(input_errors.iter().map(input_error_row));
+    rows.sort_by_key(|row| row.row_number);
+    rows
+}
+
+#[cfg(any(feature = "gui", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+#[cfg(any(feature = "gui", test))]
+impl ReportFormat {
+    pub fn from_path(path: &Path) -> ReportFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ReportFormat::Json,
+            _ => ReportFormat::Csv,
+        }
+    }
+}
+
+#[cfg(any(feature = "gui", test))]
+fn create_parent(path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| AppError::io("Failed to create directory", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(any(feature = "gui", test))]
+pub fn write_report(rows: &[BatchReportRow], path: &Path, format: ReportFormat) -> AppResult<()> {
+    create_parent(path)?;
+
+    let serialize_error = |e: &dyn std::fmt::Display| {
+        AppError::internal(format!("Failed to serialize report: {}", e))
+    };
+    let content = match format {
+        ReportFormat::Json => {
+            serde_json::to_string_pretty(rows).map_err(|e| serialize_error(&e))?
+        }
+        ReportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row).map_err(|e| serialize_error(&e))?;
+            }
+            let bytes = writer.into_inner().map_err(|e| serialize_error(&e))?;
+            String::from_utf8(bytes).map_err(|e| serialize_error(&e))?
+        }
+    };
+
+    fs::write(path, content).map_err(|e| AppError::io("Failed to write report", e))
+}
+
+#[cfg(any(feature = "gui", test))]
+// Files inside the playlist's folder are written relative to it so the folder can be moved as a whole
+fn playlist_location(file: &Path, playlist_dir: &Path) -> String {
+    file.strip_prefix(playlist_dir)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(any(feature = "gui", test))]
+/// Writes an M3U8 playlist of every row that has a file on disk, in original row order.
+/// Returns the number of entries written.
+pub fn write_playlist(rows: &[BatchReportRow], path: &Path) -> AppResult<usize> {
+    create_parent(path)?;
+    let playlist_dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut playable: Vec<&BatchReportRow> = rows
+        .iter()
+        .filter(|row| {
+            matches!(
+                row.status,
+                BatchItemStatus::Downloaded | BatchItemStatus::Deduplicated
+            )
+        })
+        .filter(|row| row.output_path.is_some())
+        .collect();
+    playable.sort_by_key(|row| row.row_number);
+
+    let mut content = String::from("#EXTM3U\n");
+    for row in &playable {
+        let file = Path::new(row.output_path.as_deref().unwrap_or_default());
+        let title = row
+            .video_title
+            .as_deref()
+            .or_else(|| file.file_stem().and_then(|s| s.to_str()))
+            .unwrap_or(&row.query);
+        content.push_str(&format!(
+            "#EXTINF:-1,{}\n",
+            title.replace(['\r', '\n'], " ")
+        ));
+        content.push_str(&playlist_location(file, playlist_dir));
+        content.push('\n');
+    }
+
+    fs::write(path, content).map_err(|e| AppError::io("Failed to write playlist", e))?;
+    Ok(playable.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    fn row(row_number: usize, status: BatchItemStatus, output: Option<&str>) -> BatchReportRow {
+        BatchReportRow {
+            row_number,
+            query: format!("Query {}", row_number),
+            video_id: Some(format!("id{}", row_number)),
+            video_title: Some(format!("Video {}", row_number)),
+            match_score: None,
+            status,
+            error: None,
+            output_path: output.map(|o| o.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_batch_report_adds_failed_rows_for_input_errors_in_place() {
+        let rows = batch_report(
+            vec![
+                row(1, BatchItemStatus::Downloaded, Some("/music/a.mp3")),
+                row(3, BatchItemStatus::Downloaded, Some("/music/b.mp3")),
+            ],
+            &[
+                InputError::new(
+                    2,
+                    "https://open.spotify.com/album/x",
+                    "Failed to fetch https://open.spotify.com/album/x: HTTP 404",
+                ),
+                InputError::new(
+                    5,
+                    "",
+                    "Missing both artist and track name - cannot create search query",
+                ),
+            ],
+        );
+
+        let numbers: Vec<usize> = rows.iter().map(|row| row.row_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 5]);
+        assert_eq!(rows[1].status, BatchItemStatus::Failed);
+        assert_eq!(rows[1].query, "https://open.spotify.com/album/x");
+        assert_eq!(
+            rows[1].error.as_deref(),
+            Some("Failed to fetch https://open.spotify.com/album/x: HTTP 404")
+        );
+        assert_eq!(rows[3].query, "");
+        assert_eq!(
+            rows[3].error.as_deref(),
+            Some("Missing both artist and track name - cannot create search query")
+        );
+    }
+
+    #[test]
+    fn test_input_error_display() {
+        let link = InputError::new(2, "https://open.spotify.com/album/x", "HTTP 404");
+        assert_eq!(
+            link.to_string(),
+            "https://open.spotify.com/album/x: HTTP 404"
+        );
+        let row = InputError::new(5, "", "Missing both artist and track name");
+        assert_eq!(row.to_string(), "Row 5: Missing both artist and track name");
+    }
+
+    #[test]
+    fn test_report_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.JSON")),
+            ReportFormat::Json
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.csv")),
+            ReportFormat::Csv
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report")),
+            ReportFormat::Csv
+        );
+    }
+
+    #[test]
+    fn test_write_csv_report() {
+        let dir = test_dir("batch_report_csv_test");
+        let path = dir.join("report.csv");
+        let mut failed = row(2, BatchItemStatus::Failed, None);
+        failed.error = Some("yt-dlp exited, code 1".to_string());
+        failed.match_score = Some(45);
+
+        write_report(
+            &[
+                row(1, BatchItemStatus::Downloaded, Some("/music/a.mp3")),
+                failed,
+            ],
+            &path,
+            ReportFormat::Csv,
+        )
+        .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "row_number,query,video_id,video_title,match_score,status,error,output_path"
+        );
+        assert_eq!(lines[1], "1,Query 1,id1,Video 1,,downloaded,,/music/a.mp3");
+        assert_eq!(
+            lines[2],
+            "2,Query 2,id2,Video 2,45,failed,\"yt-dlp exited, code 1\","
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_write_json_report_round_trip() {
+        let dir = test_dir("batch_report_json_test");
+        let path = dir.join("report.json");
+        let rows = vec![row(1, BatchItemStatus::NotFound, None)];
+
+        write_report(&rows, &path, ReportFormat::Json).unwrap();
+
+        let parsed: Vec<BatchReportRow> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed, rows);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_write_playlist_keeps_row_order_and_skips_failures() {
+        let dir = test_dir("batch_report_playlist_test");
+        let path = dir.join("run.m3u8");
+        let inside = dir.join("Artist").join("b.mp3");
+        let rows = vec![
+            row(
+                3,
+                BatchItemStatus::Downloaded,
+                Some(inside.to_str().unwrap()),
+            ),
+            row(1, BatchItemStatus::Deduplicated, Some("/elsewhere/a.mp3")),
+            row(2, BatchItemStatus::Failed, None),
+            row(4, BatchItemStatus::NotFound, None),
+        ];
+
+        let written = write_playlist(&rows, &path).unwrap();
+
+        assert_eq!(written, 2);
+        let expected_inside = Path::new("Artist").join("b.mp3");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "#EXTM3U\n#EXTINF:-1,Video 1\n/elsewhere/a.mp3\n#EXTINF:-1,Video 3\n{}\n",
+                expected_inside.display()
+            )
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    lyricut_yt_downloader_lib::run_cli()
+}
@@ -0,0 +1,490 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+const REMOVE_PHRASES: &[&str] = &[
+    "[Audio HD]",
+    "(Official Video)",
+    "(lyrics)",
+    "[High Quality]",
+    "(Official Music Video)",
+    "(Audio)",
+    "[visualizer]",
+    "[Official]",
+    "[Lyric Video]",
+    "[Lyrics]",
+    "(Lyric Video)",
+    "(Studio)",
+    "[Studio]",
+    "[Version]",
+    "(Version)",
+];
+
+const VERSION_QUALIFIERS: &[&str] = &[
+    "(Radio Mix)",
+    "(Radio Edit)",
+    "[Clean version]",
+    "(Explicit)",
+    "[Explicit]",
+    "(Clean)",
+    "[Live]",
+    "(Live)",
+    "[Remastered]",
+    "(Remastered)",
+    "[Remix]",
+    "(Remix)",
+    "[DJ Mix]",
+    "(DJ Mix)",
+    "[Acoustic]",
+    "(Acoustic)",
+    "[Instrumental]",
+    "(Instrumental)",
+    "[Extended]",
+    "(Extended)",
+    "[Edit]",
+    "(Edit)",
+    "[Mixed]",
+    "(Mixed)",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    #[default]
+    Literal,
+    Regex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupAction {
+    Remove,
+    Replace,
+    VersionTag,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub kind: MatchKind,
+    pub action: CleanupAction,
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct CleanupOutcome {
+    pub title: String,
+    pub version_tags: Vec<String>,
+}
+
+impl CleanupOutcome {
+    pub fn display_name(&self) -> String {
+        self.version_tags
+            .iter()
+            .fold(self.title.clone(), |name, tag| format!("{} ({})", name, tag))
+    }
+
+    pub fn version(&self) -> Option<String> {
+        if self.version_tags.is_empty() {
+            None
+        } else {
+            Some(self.version_tags.join(", "))
+        }
+    }
+}
+
+#[cfg(any(feature = "gui", test))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupPreview {
+    pub original: String,
+    pub cleaned: String,
+    pub title: String,
+    pub version: Option<String>,
+}
+
+pub fn default_cleanup_rules() -> Vec<CleanupRule> {
+    let removals = REMOVE_PHRASES.iter().map(|phrase| CleanupRule {
+        pattern: phrase.to_string(),
+        kind: MatchKind::Literal,
+        action: CleanupAction::Remove,
+        replacement: None,
+    });
+    let versions = VERSION_QUALIFIERS.iter().map(|phrase| CleanupRule {
+        pattern: phrase.to_string(),
+        kind: MatchKind::Literal,
+        action: CleanupAction::VersionTag,
+        replacement: None,
+    });
+
+    removals.chain(versions).collect()
+}
+
+fn compile_rule(rule: &CleanupRule) -> Result<Regex, String> {
+    let pattern = match rule.kind {
+        MatchKind::Literal => format!("(?i)\\s*{}", regex::escape(&rule.pattern)),
+        MatchKind::Regex => rule.pattern.clone(),
+    };
+    Regex::new(&pattern).map_err(|e| format!("Invalid cleanup rule '{}': {}", rule.pattern, e))
+}
+
+pub fn validate_cleanup_rules(rules: &[CleanupRule]) -> Result<(), String> {
+    for rule in rules {
+        if rule.pattern.trim().is_empty() {
+            return Err("Cleanup rule pattern must not be empty".to_string());
+        }
+        compile_rule(rule)?;
+        if rule.action == CleanupAction::Replace && rule.replacement.is_none() {
+            return Err(format!(
+                "Cleanup rule '{}' replaces text but has no replacement",
+                rule.pattern
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn version_label(captures: &regex::Captures) -> String {
+    let text = captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str())
+        .unwrap_or("");
+    text.trim()
+        .trim_start_matches(['(', '['])
+        .trim_end_matches([')', ']'])
+        .trim()
+        .to_string()
+}
+
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn apply_cleanup_rules(original: &str, rules: &[CleanupRule]) -> CleanupOutcome {
+    let mut title = original.to_string();
+    let mut version_tags: Vec<String> = Vec::new();
+
+    for rule in rules {
+        // Invalid rules are rejected when settings are saved; skip any that slipped through
+        let Ok(regex) = compile_rule(rule) else {
+            continue;
+        };
+
+        match rule.action {
+            CleanupAction::Remove => {
+                title = regex.replace_all(&title, "").to_string();
+            }
+            CleanupAction::Replace => {
+                let replacement = rule.replacement.as_deref().unwrap_or("");
+                title = regex.replace_all(&title, replacement).to_string();
+            }
+            CleanupAction::VersionTag => {
+                for captures in regex.captures_iter(&title) {
+                    let label = version_label(&captures);
+                    if !label.is_empty()
+                        && !version_tags.iter().any(|t| t.eq_ignore_ascii_case(&label))
+                    {
+                        version_tags.push(label);
+                    }
+                }
+                title = regex.replace_all(&title, "").to_string();
+            }
+        }
+    }
+
+    CleanupOutcome {
+        title: collapse_whitespace(&title),
+        version_tags,
+    }
+}
+
+#[cfg(any(feature = "gui", test))]
+pub fn build_cleanup_previews(titles: &[String], rules: &[CleanupRule]) -> Vec<CleanupPreview> {
+    titles
+        .iter()
+        .map(|original| {
+            let outcome = apply_cleanup_rules(original, rules);
+            CleanupPreview {
+                original: original.clone(),
+                cleaned: outcome.display_name(),
+                version: outcome.version(),
+                title: outcome.title,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, kind: MatchKind, action: CleanupAction) -> CleanupRule {
+        CleanupRule {
+            pattern: pattern.to_string(),
+            kind,
+            action,
+            replacement: None,
+        }
+    }
+
+    fn clean(name: &str) -> String {
+        apply_cleanup_rules(name, &default_cleanup_rules()).display_name()
+    }
+
+    #[test]
+    fn test_default_rules_remove_marketing_phrases() {
+        let outcome = apply_cleanup_rules("Song (Official Video) [Audio HD]", &default_cleanup_rules());
+        assert_eq!(outcome.title, "Song");
+        assert!(outcome.version_tags.is_empty());
+    }
+
+    #[test]
+    fn test_default_rules_keep_version_qualifiers() {
+        let outcome = apply_cleanup_rules("Song (Remix) [Official]", &default_cleanup_rules());
+        assert_eq!(outcome.title, "Song");
+        assert_eq!(outcome.version_tags, vec!["Remix".to_string()]);
+        assert_eq!(outcome.display_name(), "Song (Remix)");
+        assert_eq!(outcome.version(), Some("Remix".to_string()));
+    }
+
+    #[test]
+    fn test_version_tags_are_deduplicated() {
+        let outcome = apply_cleanup_rules("Song [Live] (live)", &default_cleanup_rules());
+        assert_eq!(outcome.version_tags, vec!["Live".to_string()]);
+    }
+
+    #[test]
+    fn test_multiple_version_tags() {
+        let outcome = apply_cleanup_rules("Song (Acoustic) [Live]", &default_cleanup_rules());
+        assert_eq!(outcome.display_name(), "Song (Live) (Acoustic)");
+        assert_eq!(outcome.version(), Some("Live, Acoustic".to_string()));
+    }
+
+    #[test]
+    fn test_regex_version_tag_uses_capture_group() {
+        let rules = vec![rule(
+            r"(?i)\s*[\(\[]([^\)\]]*\bremix)[\)\]]",
+            MatchKind::Regex,
+            CleanupAction::VersionTag,
+        )];
+        let outcome = apply_cleanup_rules("Song (Skrillex Remix)", &rules);
+        assert_eq!(outcome.title, "Song");
+        assert_eq!(outcome.version_tags, vec!["Skrillex Remix".to_string()]);
+    }
+
+    #[test]
+    fn test_replace_action() {
+        let rules = vec![CleanupRule {
+            pattern: " ft. ".to_string(),
+            kind: MatchKind::Literal,
+            action: CleanupAction::Replace,
+            replacement: Some(" feat. ".to_string()),
+        }];
+        let outcome = apply_cleanup_rules("Artist ft. Guest - Song", &rules);
+        assert_eq!(outcome.title, "Artist feat. Guest - Song");
+    }
+
+    #[test]
+    fn test_regex_remove_action() {
+        let rules = vec![rule(r"\s*\d{4}\s*$", MatchKind::Regex, CleanupAction::Remove)];
+        let outcome = apply_cleanup_rules("Song 2019", &rules);
+        assert_eq!(outcome.title, "Song");
+    }
+
+    #[test]
+    fn test_literal_rules_are_case_insensitive() {
+        let rules = vec![rule("[hq]", MatchKind::Literal, CleanupAction::Remove)];
+        let outcome = apply_cleanup_rules("Song [HQ]", &rules);
+        assert_eq!(outcome.title, "Song");
+    }
+
+    #[test]
+    fn test_invalid_regex_rule_is_skipped() {
+        let rules = vec![rule("(unclosed", MatchKind::Regex, CleanupAction::Remove)];
+        let outcome = apply_cleanup_rules("Song (unclosed", &rules);
+        assert_eq!(outcome.title, "Song (unclosed");
+    }
+
+    #[test]
+    fn test_validate_cleanup_rules() {
+        assert!(validate_cleanup_rules(&default_cleanup_rules()).is_ok());
+        assert!(validate_cleanup_rules(&[rule("(", MatchKind::Regex, CleanupAction::Remove)]).is_err());
+        assert!(validate_cleanup_rules(&[rule(" ", MatchKind::Literal, CleanupAction::Remove)]).is_err());
+        assert!(validate_cleanup_rules(&[rule("x", MatchKind::Literal, CleanupAction::Replace)]).is_err());
+    }
+
+    #[test]
+    fn test_build_cleanup_previews() {
+        let titles = vec![
+            "Artist - Song (Official Video)".to_string(),
+            "Artist - Song (Acoustic)".to_string(),
+        ];
+        let previews = build_cleanup_previews(&titles, &default_cleanup_rules());
+
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].cleaned, "Artist - Song");
+        assert_eq!(previews[0].version, None);
+        assert_eq!(previews[1].cleaned, "Artist - Song (Acoustic)");
+        assert_eq!(previews[1].title, "Artist - Song");
+        assert_eq!(previews[1].version, Some("Acoustic".to_string()));
+    }
+
+    #[test]
+    fn test_cleanup_rule_deserializes_with_defaults() {
+        let rule: CleanupRule =
+            serde_json::from_str(r#"{"pattern": "[HQ]", "action": "remove"}"#).unwrap();
+        assert_eq!(rule.kind, MatchKind::Literal);
+        assert_eq!(rule.action, CleanupAction::Remove);
+    }
+
+    #[test]
+    fn test_default_rules_removes_audio_hd() {
+        let result = clean("Song Name [Audio HD]");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_radio_mix_as_version() {
+        let result = clean("Song Name (Radio Mix)");
+        assert_eq!(result, "Song Name (Radio Mix)");
+    }
+
+    #[test]
+    fn test_default_rules_removes_official_video() {
+        let result = clean("Song Name (Official Video)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_removes_lyrics() {
+        let result = clean("Song Name (lyrics)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_radio_edit_as_version() {
+        let result = clean("Song Name (Radio Edit)");
+        assert_eq!(result, "Song Name (Radio Edit)");
+    }
+
+    #[test]
+    fn test_default_rules_removes_high_quality() {
+        let result = clean("Song Name [High Quality]");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_removes_official_music_video() {
+        let result = clean("Song Name (Official Music Video)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_removes_audio() {
+        let result = clean("Song Name (Audio)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_clean_version_as_version() {
+        let result = clean("Song Name [Clean version]");
+        assert_eq!(result, "Song Name (Clean version)");
+    }
+
+    #[test]
+    fn test_default_rules_removes_visualizer() {
+        let result = clean("Song Name [visualizer]");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_removes_multiple_patterns() {
+        let result = clean("Song Name [Audio HD] (Official Video)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_handles_case_insensitive() {
+        let result = clean("Song Name (LYRICS)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_handles_lowercase() {
+        let result = clean("Song Name (lyrics)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_trims_whitespace() {
+        let result = clean("   Song Name   ");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_preserves_actual_text() {
+        let result = clean("Song Name with Meaningful Text");
+        assert_eq!(result, "Song Name with Meaningful Text");
+    }
+
+    #[test]
+    fn test_default_rules_handles_empty_string() {
+        let result = clean("");
+        assert_eq!(result, "");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_live_as_version() {
+        let result = clean("Song Name [Live]");
+        assert_eq!(result, "Song Name (Live)");
+    }
+
+    #[test]
+    fn test_default_rules_removes_studio() {
+        let result = clean("Song Name (Studio)");
+        assert_eq!(result, "Song Name");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_remastered_as_version() {
+        let result = clean("Song Name [Remastered]");
+        assert_eq!(result, "Song Name (Remastered)");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_remix_as_version() {
+        let result = clean("Song Name (Remix)");
+        assert_eq!(result, "Song Name (Remix)");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_acoustic_as_version() {
+        let result = clean("Song Name [Acoustic]");
+        assert_eq!(result, "Song Name (Acoustic)");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_extended_as_version() {
+        let result = clean("Song Name (Extended)");
+        assert_eq!(result, "Song Name (Extended)");
+    }
+
+    #[test]
+    fn test_default_rules_complex_example() {
+        let result = clean("Artist Name - Song Title (Official Video) [Audio HD]");
+        assert_eq!(result, "Artist Name - Song Title");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_explicit_as_version() {
+        let result = clean("Song Name (Explicit)");
+        assert_eq!(result, "Song Name (Explicit)");
+    }
+
+    #[test]
+    fn test_default_rules_keeps_instrumental_as_version() {
+        let result = clean("Song Name [Instrumental]");
+        assert_eq!(result, "Song Name (Instrumental)");
+    }
+}
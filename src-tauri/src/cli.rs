@@ -0,0 +1,775 @@
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::batch_report::{BatchItemStatus, BatchReportRow, InputError};
+use crate::conflict::ConflictPolicy;
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::ffmpeg_setup;
+use crate::link_resolver::OpenGraphResolver;
+use crate::metadata::{tag_mp3, TrackMetadata};
+use crate::organizer::OrganizeMode;
+use crate::pipeline::{retry_event, EventSink, Pipeline, QueueItem, QueueOutcome};
+use crate::rate_limit;
+use crate::search::{FallbackSearch, SearchBackend};
+use crate::search_scoring::SearchHints;
+use crate::settings::{default_data_dir, load_settings, settings_path, AppSettings};
+use crate::subtitles::SubtitleOptions;
+use crate::ytdlp::YtDlp;
+use crate::ytdlp_setup;
+use crate::{build_input_items, construct_search_query, import_csv_tracks, AudioMode};
+
+const USAGE: &str = "\
+Usage: lyricut-cli [--json] [--data-dir DIR] <command>
+
+Commands:
+  download <url|query>... [--output DIR] [--mode official|raw|clean] [download options]
+  import-csv <file> [--output DIR] [--mode official|raw|clean] [--profile NAME] [download options]
+  search <query> [--mode official|raw|clean]
+  tag <file.mp3> [--title T] [--artist A] [--album A] [--year Y] [--genre G] [--track N]
+  setup
+
+Options:
+  --json           Print results as JSON
+  --data-dir DIR   Use the settings and tools in DIR instead of the app's data directory
+
+Download options, overriding the app's settings:
+  --template T     Name files like \"{artist} - {title}\"
+  --on-conflict P  When a file is already there: auto-suffix, skip, overwrite or keep-better
+  --organize M     Sort into folders: flat, artist-album, genre-artist or a template like \"{genre}/{artist}\"
+  --lyrics LANG    Turn subtitles in LANG into lyrics embedded in the file
+  --lrc            Also write the lyrics next to the file as .lrc";
+
+const EXIT_OK: u8 = 0;
+const EXIT_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_TOOL_MISSING: u8 = 3;
+const EXIT_NETWORK: u8 = 4;
+const EXIT_UNAVAILABLE: u8 = 5;
+const EXIT_FILESYSTEM: u8 = 6;
+const EXIT_TOOL_FAILED: u8 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Download {
+        inputs: Vec<String>,
+        output: PathBuf,
+        mode: AudioMode,
+    },
+    ImportCsv {
+        path: PathBuf,
+        output: PathBuf,
+        mode: AudioMode,
+        profile: Option<String>,
+    },
+    Search {
+        query: String,
+        mode: AudioMode,
+    },
+    Tag {
+        path: String,
+        metadata: Box<TrackMetadata>,
+    },
+    Setup,
+    Help,
+}
+
+/// Download settings given on the command line, which win over the settings file.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SettingsOverrides {
+    filename_template: Option<String>,
+    conflict_policy: Option<ConflictPolicy>,
+    organize_mode: Option<OrganizeMode>,
+    subtitles: Option<SubtitleOptions>,
+}
+
+impl SettingsOverrides {
+    fn apply(&self, settings: &mut AppSettings) {
+        if let Some(template) = &self.filename_template {
+            settings.filename_template = Some(template.clone());
+        }
+        if let Some(policy) = self.conflict_policy {
+            settings.conflict_policy = policy;
+        }
+        if let Some(mode) = &self.organize_mode {
+            settings.organize_mode = mode.clone();
+        }
+        if let Some(subtitles) = &self.subtitles {
+            settings.subtitles = Some(subtitles.clone());
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CliArgs {
+    json: bool,
+    data_dir: Option<PathBuf>,
+    overrides: SettingsOverrides,
+    command: Command,
+}
+
+/// Scripts tell failures apart by exit code, so every error kind belongs to a class.
+fn exit_code(kind: ErrorKind) -> u8 {
+    match kind {
+        ErrorKind::InvalidInput => EXIT_USAGE,
+        ErrorKind::YtdlpMissing | ErrorKind::FfmpegMissing => EXIT_TOOL_MISSING,
+        ErrorKind::Network | ErrorKind::RateLimited => EXIT_NETWORK,
+        ErrorKind::VideoUnavailable | ErrorKind::AgeRestricted | ErrorKind::GeoBlocked => {
+            EXIT_UNAVAILABLE
+        }
+        ErrorKind::DiskFull | ErrorKind::PermissionDenied | ErrorKind::FileNotFound => {
+            EXIT_FILESYSTEM
+        }
+        ErrorKind::ToolFailed | ErrorKind::TimedOut => EXIT_TOOL_FAILED,
+        ErrorKind::Internal => EXIT_FAILED,
+    }
+}
+
+fn parse_mode(value: &str) -> Result<AudioMode, String> {
+    match value.to_lowercase().as_str() {
+        "official" => Ok(AudioMode::Official),
+        "raw" => Ok(AudioMode::Raw),
+        "clean" => Ok(AudioMode::Clean),
+        _ => Err(format!("Unknown audio mode: {}", value)),
+    }
+}
+
+fn parse_conflict_policy(value: &str) -> Result<ConflictPolicy, String> {
+    match value.to_lowercase().as_str() {
+        "auto-suffix" => Ok(ConflictPolicy::AutoSuffix),
+        "skip" => Ok(ConflictPolicy::Skip),
+        "overwrite" => Ok(ConflictPolicy::Overwrite),
+        "keep-better" => Ok(ConflictPolicy::KeepBetter),
+        _ => Err(format!("Unknown conflict policy: {}", value)),
+    }
+}
+
+fn parse_organize_mode(value: &str) -> OrganizeMode {
+    match value.to_lowercase().as_str() {
+        "flat" => OrganizeMode::Flat,
+        "artist-album" => OrganizeMode::ArtistAlbum,
+        "genre-artist" => OrganizeMode::GenreArtist,
+        _ => OrganizeMode::Custom(value.to_string()),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<CliArgs, String> {
+    let mut json = false;
+    let mut data_dir = None;
+    let mut output = None;
+    let mut mode = None;
+    let mut profile = None;
+    let mut overrides = SettingsOverrides::default();
+    let mut metadata = TrackMetadata::default();
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => positional.insert(0, "help".to_string()),
+            "--data-dir" => data_dir = Some(PathBuf::from(value()?)),
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "--mode" => mode = Some(parse_mode(&value()?)?),
+            "--profile" => profile = Some(value()?),
+            "--template" => overrides.filename_template = Some(value()?),
+            "--on-conflict" => overrides.conflict_policy = Some(parse_conflict_policy(&value()?)?),
+            "--organize" => overrides.organize_mode = Some(parse_organize_mode(&value()?)),
+            "--lyrics" => {
+                let language = value()?;
+                overrides
+                    .subtitles
+                    .get_or_insert_with(SubtitleOptions::default)
+                    .language = language;
+            }
+            "--lrc" => {
+                overrides
+                    .subtitles
+                    .get_or_insert_with(SubtitleOptions::default)
+                    .write_sidecar = true
+            }
+            "--title" => metadata.title = Some(value()?),
+            "--artist" => metadata.artist = Some(value()?),
+            "--album" => metadata.album = Some(value()?),
+            "--year" => metadata.year = Some(value()?),
+            "--genre" => metadata.genre = Some(value()?),
+            "--track" => metadata.track_number = Some(value()?),
+            option if option.starts_with('-') && option.len() > 1 => {
+                return Err(format!("Unknown option: {}", option))
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let output = output.unwrap_or_else(|| PathBuf::from("."));
+    let mode = mode.unwrap_or(AudioMode::Official);
+    let mut positional = positional.into_iter();
+    let name = positional.next();
+    let rest: Vec<String> = positional.collect();
+
+    let command = match name.as_deref() {
+        None | Some("help") => Command::Help,
+        Some("download") if !rest.is_empty() => Command::Download {
+            inputs: rest,
+            output,
+            mode,
+        },
+        Some("import-csv") if rest.len() == 1 => Command::ImportCsv {
+            path: PathBuf::from(&rest[0]),
+            output,
+            mode,
+            profile,
+        },
+        Some("search") if !rest.is_empty() => Command::Search {
+            query: rest.join(" "),
+            mode,
+        },
+        Some("tag") if rest.len() == 1 => Command::Tag {
+            path: rest[0].clone(),
+            metadata: Box::new(metadata),
+        },
+        Some("setup") if rest.is_empty() => Command::Setup,
+        Some(name @ ("download" | "import-csv" | "search" | "tag" | "setup")) => {
+            return Err(format!("Wrong arguments for {}", name))
+        }
+        Some(name) => return Err(format!("Unknown command: {}", name)),
+    };
+
+    Ok(CliArgs {
+        json,
+        data_dir,
+        overrides,
+        command,
+    })
+}
+
+/// Prints retries and fallbacks as they happen. With --json each event is a line of JSON on
+/// stderr, leaving stdout to the result.
+struct CliEvents {
+    json: bool,
+}
+
+impl EventSink for CliEvents {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        if self.json {
+            if event != "download-progress" {
+                eprintln!(
+                    "{}",
+                    serde_json::json!({ "event": event, "payload": payload })
+                );
+            }
+            return;
+        }
+        match event {
+            "item-retry" => eprintln!(
+                "Retrying {} of {} ({}/{}): {}",
+                payload["stage"].as_str().unwrap_or_default(),
+                payload["target"].as_str().unwrap_or_default(),
+                payload["attempt"],
+                payload["max_attempts"],
+                payload["message"].as_str().unwrap_or_default()
+            ),
+            "item-fallback" => eprintln!(
+                "{} failed, trying {}: {}",
+                payload["from"].as_str().unwrap_or_default(),
+                payload["to"].as_str().unwrap_or_default(),
+                payload["message"].as_str().unwrap_or_default()
+            ),
+            _ => {}
+        }
+    }
+}
+
+struct Context {
+    json: bool,
+    data_dir: PathBuf,
+    settings: AppSettings,
+}
+
+impl Context {
+    fn load(args: &CliArgs) -> AppResult<Context> {
+        let data_dir = match &args.data_dir {
+            Some(dir) => dir.clone(),
+            None => default_data_dir()
+                .ok_or_else(|| AppError::internal("Could not find the app data directory"))?,
+        };
+        let mut settings = load_settings(&settings_path(&data_dir))?;
+        args.overrides.apply(&mut settings);
+        // The app checks settings when saving them, but the file may have been edited by hand
+        settings.validate().map_err(AppError::invalid_input)?;
+        rate_limit::global().configure(&settings.rate_limits);
+        Ok(Context {
+            json: args.json,
+            data_dir,
+            settings,
+        })
+    }
+
+    fn ytdlp(&self) -> AppResult<YtDlp> {
+        let path = ytdlp_setup::get_ytdlp_command(&self.data_dir)?;
+        Ok(YtDlp::new(&path, &self.settings.ytdlp))
+    }
+
+    fn events(&self) -> CliEvents {
+        CliEvents { json: self.json }
+    }
+}
+
+#[derive(Serialize)]
+struct BatchSummary {
+    items: Vec<BatchReportRow>,
+    /// Inputs that never became an item, such as streaming links that could not be resolved.
+    errors: Vec<InputError>,
+}
+
+fn print_json(out: &mut impl Write, value: &impl Serialize) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::internal(format!("Failed to serialize output: {}", e)))?;
+    writeln!(out, "{}", json).map_err(|e| AppError::io("Failed to write output", e))
+}
+
+fn print_line(out: &mut impl Write, line: &str) -> AppResult<()> {
+    writeln!(out, "{}", line).map_err(|e| AppError::io("Failed to write output", e))
+}
+
+// The first failure decides the exit code, so a script sees why a batch went wrong
+fn batch_exit_code(outcomes: &[QueueOutcome], errors: &[InputError]) -> u8 {
+    let failure = outcomes.iter().find_map(|outcome| match &outcome.error {
+        Some(error) => Some(exit_code(error.kind)),
+        None if outcome.row.status == BatchItemStatus::NotFound => Some(EXIT_UNAVAILABLE),
+        None => None,
+    });
+    match failure {
+        Some(code) => code,
+        None if !errors.is_empty() => EXIT_FAILED,
+        None => EXIT_OK,
+    }
+}
+
+async fn run_batch(
+    context: &Context,
+    items: Vec<QueueItem>,
+    errors: Vec<InputError>,
+    output: &Path,
+    mode: &AudioMode,
+    out: &mut impl Write,
+) -> AppResult<u8> {
+    let ytdlp = context.ytdlp()?;
+    let ffmpeg_path = ffmpeg_setup::check_ffmpeg(&context.data_dir)?;
+    let pipeline = Pipeline::new(&ytdlp, &ffmpeg_path, &context.settings);
+    let output = output.to_string_lossy();
+    let events = context.events();
+    let total = items.len();
+
+    let mut outcomes = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        if !context.json {
+            print_line(out, &format!("[{}/{}] {}", index + 1, total, item.input))?;
+        }
+        let outcome = pipeline
+            .run_queue_item(item, &output, Some(mode), &events)
+            .await;
+        if !context.json {
+            let row = &outcome.row;
+            let line = match row.status {
+                BatchItemStatus::Downloaded => {
+                    format!(
+                        "  downloaded {}",
+                        row.output_path.as_deref().unwrap_or_default()
+                    )
+                }
+                BatchItemStatus::Deduplicated => format!(
+                    "  already there {}",
+                    row.output_path.as_deref().unwrap_or_default()
+                ),
+                BatchItemStatus::NotFound => "  not found".to_string(),
+                BatchItemStatus::Failed => {
+                    format!("  failed: {}", row.error.as_deref().unwrap_or_default())
+                }
+            };
+            print_line(out, &line)?;
+        }
+        outcomes.push(outcome);
+    }
+
+    let code = batch_exit_code(&outcomes, &errors);
+    if context.json {
+        print_json(
+            out,
+            &BatchSummary {
+                items: outcomes.into_iter().map(|outcome| outcome.row).collect(),
+                errors,
+            },
+        )?;
+    } else {
+        for error in &errors {
+            print_line(out, &format!("error: {}", error))?;
+        }
+        let done = outcomes
+            .iter()
+            .filter(|outcome| outcome.row.status != BatchItemStatus::Failed)
+            .filter(|outcome| outcome.row.status != BatchItemStatus::NotFound)
+            .count();
+        print_line(out, &format!("{} of {} downloaded", done, total))?;
+    }
+    Ok(code)
+}
+
+async fn execute(args: CliArgs, out: &mut impl Write) -> AppResult<u8> {
+    let context = Context::load(&args)?;
+
+    match args.command {
+        Command::Download {
+            inputs,
+            output,
+            mode,
+        } => {
+            let resolver = OpenGraphResolver::new(&context.settings.resolver)?;
+            let input = build_input_items(&inputs.join("\n"), &mode, &resolver).await;
+            let items = input
+                .items
+                .into_iter()
+                .map(QueueItem::from_processed)
+                .collect();
+            run_batch(&context, items, input.errors, &output, &mode, out).await
+        }
+        Command::ImportCsv {
+            path,
+            output,
+            mode,
+            profile,
+        } => {
+            let content =
+                fs::read(&path).map_err(|e| AppError::io("Failed to read CSV file", e))?;
+            let import = import_csv_tracks(&content, &context.settings, profile.as_deref(), &mode)?;
+            let items = import.tracks.into_iter().map(QueueItem::from_csv).collect();
+            run_batch(&context, items, import.errors, &output, &mode, out).await
+        }
+        Command::Search { query, mode } => {
+            let ytdlp = context.ytdlp()?;
+            let search =
+                FallbackSearch::from_settings(&ytdlp, &context.settings.search, Some(&mode));
+            let events = context.events();
+            let query = construct_search_query(&query, &mode);
+            let hints = SearchHints::default();
+            let found = context
+                .settings
+                .retry
+                .run(
+                    |attempt| {
+                        events.emit_event("item-retry", retry_event("search", &query, attempt));
+                    },
+                    |_| search.search(&query, &hints),
+                )
+                .await?;
+
+            match (&found, context.json) {
+                (_, true) => print_json(out, &found)?,
+                (Some(video), false) => print_line(
+                    out,
+                    &format!(
+                        "{}\nhttps://www.youtube.com/watch?v={}",
+                        video.title, video.id
+                    ),
+                )?,
+                (None, false) => print_line(out, &format!("No results for {}", query))?,
+            }
+            Ok(if found.is_some() {
+                EXIT_OK
+            } else {
+                EXIT_UNAVAILABLE
+            })
+        }
+        Command::Tag { path, metadata } => {
+            tag_mp3(&path, *metadata)?;
+            if context.json {
+                print_json(out, &serde_json::json!({ "path": path }))?;
+            } else {
+                print_line(out, &format!("Tagged {}", path))?;
+            }
+            Ok(EXIT_OK)
+        }
+        Command::Setup => {
+            let progress = |_status: &str, message: &str| {
+                if !context.json {
+                    eprintln!("yt-dlp: {}", message);
+                }
+            };
+            let ytdlp = ytdlp_setup::download_ytdlp(&context.data_dir, progress).await?;
+            if !context.json {
+                eprintln!("ffmpeg: checking...");
+            }
+            let ffmpeg = ffmpeg_setup::download_ffmpeg(&context.data_dir).await?;
+            if context.json {
+                print_json(
+                    out,
+                    &serde_json::json!({ "ytdlp": ytdlp, "ffmpeg": ffmpeg }),
+                )?;
+            } else {
+                print_line(out, &format!("yt-dlp: {}\nffmpeg: {}", ytdlp, ffmpeg))?;
+            }
+            Ok(EXIT_OK)
+        }
+        Command::Help => {
+            print_line(out, USAGE)?;
+            Ok(EXIT_OK)
+        }
+    }
+}
+
+fn run_with(args: &[String], out: &mut impl Write, err: &mut impl Write) -> u8 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => {
+            let _ = writeln!(err, "error: {}\n\n{}", message, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    let json = args.json;
+
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| AppError::internal(format!("Failed to start the runtime: {}", e)))
+        .and_then(|runtime| runtime.block_on(execute(args, out)));
+
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            if json {
+                let _ = print_json(out, &serde_json::json!({ "error": error }));
+            } else {
+                let _ = writeln!(err, "error: {}", error);
+                let hint = match error.kind {
+                    ErrorKind::YtdlpMissing | ErrorKind::FfmpegMissing => {
+                        Some("Run `lyricut-cli setup` to install it.")
+                    }
+                    kind => kind.hint(),
+                };
+                if let Some(hint) = hint {
+                    let _ = writeln!(err, "{}", hint);
+                }
+            }
+            exit_code(error.kind)
+        }
+    }
+}
+
+/// Entry point of the lyricut-cli binary.
+pub fn run_cli() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = run_with(&args, &mut io::stdout().lock(), &mut io::stderr().lock());
+    ExitCode::from(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_download_with_global_options() {
+        let parsed = parse_args(&args(&[
+            "--json",
+            "download",
+            "https://youtu.be/dQw4w9WgXcQ",
+            "Queen - Bohemian Rhapsody",
+            "--output",
+            "/music",
+            "--mode",
+            "clean",
+            "--data-dir",
+            "/tmp/lyricut",
+        ]))
+        .unwrap();
+
+        assert!(parsed.json);
+        assert_eq!(parsed.data_dir, Some(PathBuf::from("/tmp/lyricut")));
+        assert_eq!(parsed.overrides, SettingsOverrides::default());
+        assert_eq!(
+            parsed.command,
+            Command::Download {
+                inputs: args(&["https://youtu.be/dQw4w9WgXcQ", "Queen - Bohemian Rhapsody"]),
+                output: PathBuf::from("/music"),
+                mode: AudioMode::Clean,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_tag_and_search() {
+        let parsed = parse_args(&args(&[
+            "tag", "song.mp3", "--title", "Hey Jude", "--track", "7",
+        ]))
+        .unwrap();
+        match parsed.command {
+            Command::Tag { path, metadata } => {
+                assert_eq!(path, "song.mp3");
+                assert_eq!(metadata.title.as_deref(), Some("Hey Jude"));
+                assert_eq!(metadata.track_number.as_deref(), Some("7"));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        let parsed = parse_args(&args(&["search", "daft", "punk"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Command::Search {
+                query: "daft punk".to_string(),
+                mode: AudioMode::Official,
+            }
+        );
+        assert_eq!(parse_args(&[]).unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_arguments() {
+        assert!(parse_args(&args(&["fetch", "x"])).is_err());
+        assert!(parse_args(&args(&["download"])).is_err());
+        assert!(parse_args(&args(&["import-csv", "a.csv", "b.csv"])).is_err());
+        assert!(parse_args(&args(&["search", "x", "--mode", "loud"])).is_err());
+        assert!(parse_args(&args(&["search", "x", "--output"])).is_err());
+        assert!(parse_args(&args(&["setup", "--force"])).is_err());
+
+        let mut err = Vec::new();
+        assert_eq!(
+            run_with(&args(&["fetch"]), &mut Vec::new(), &mut err),
+            EXIT_USAGE
+        );
+        assert!(String::from_utf8(err)
+            .unwrap()
+            .contains("Unknown command: fetch"));
+    }
+
+    #[test]
+    fn test_parse_download_options_override_settings() {
+        let parsed = parse_args(&args(&[
+            "download",
+            "Queen - Bohemian Rhapsody",
+            "--template",
+            "{artist}/{title}",
+            "--organize",
+            "artist-album",
+            "--on-conflict",
+            "keep-better",
+            "--lrc",
+            "--lyrics",
+            "de",
+        ]))
+        .unwrap();
+        let mut settings = AppSettings::default();
+        parsed.overrides.apply(&mut settings);
+
+        assert_eq!(
+            settings.filename_template.as_deref(),
+            Some("{artist}/{title}")
+        );
+        assert_eq!(settings.conflict_policy, ConflictPolicy::KeepBetter);
+        assert_eq!(settings.organize_mode, OrganizeMode::ArtistAlbum);
+        let subtitles = settings.subtitles.unwrap();
+        assert_eq!(subtitles.language, "de");
+        assert!(subtitles.embed);
+        assert!(subtitles.write_sidecar);
+        assert_eq!(
+            parse_organize_mode("{genre}/{artist}"),
+            OrganizeMode::Custom("{genre}/{artist}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_conflict_policy() {
+        assert!(parse_args(&args(&["download", "x", "--on-conflict", "merge"])).is_err());
+    }
+
+    #[test]
+    fn test_invalid_template_is_a_usage_error() {
+        let data_dir = std::env::temp_dir().join("lyricut_cli_bad_template");
+        let code = run_with(
+            &args(&[
+                "--data-dir",
+                data_dir.to_str().unwrap(),
+                "--template",
+                "{nope}",
+                "search",
+                "x",
+            ]),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        );
+        assert_eq!(code, EXIT_USAGE);
+    }
+
+    #[test]
+    fn test_exit_codes_group_error_kinds() {
+        assert_eq!(exit_code(ErrorKind::InvalidInput), EXIT_USAGE);
+        assert_eq!(exit_code(ErrorKind::FfmpegMissing), EXIT_TOOL_MISSING);
+        assert_eq!(exit_code(ErrorKind::RateLimited), EXIT_NETWORK);
+        assert_eq!(exit_code(ErrorKind::GeoBlocked), EXIT_UNAVAILABLE);
+        assert_eq!(exit_code(ErrorKind::DiskFull), EXIT_FILESYSTEM);
+        assert_eq!(exit_code(ErrorKind::ToolFailed), EXIT_TOOL_FAILED);
+        assert_eq!(exit_code(ErrorKind::TimedOut), EXIT_TOOL_FAILED);
+        assert_eq!(exit_code(ErrorKind::Internal), EXIT_FAILED);
+    }
+
+    #[test]
+    fn test_tag_missing_file_fails_with_filesystem_code() {
+        let data_dir = std::env::temp_dir().join("lyricut_cli_tag_missing");
+        let mut out = Vec::new();
+        let code = run_with(
+            &args(&[
+                "--json",
+                "--data-dir",
+                data_dir.to_str().unwrap(),
+                "tag",
+                "/nonexistent/song.mp3",
+            ]),
+            &mut out,
+            &mut Vec::new(),
+        );
+
+        assert_eq!(code, EXIT_FILESYSTEM);
+        let output: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(output["error"]["kind"], "file_not_found");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_with_fake_ytdlp() {
+        let tools = crate::test_support::FakeTools::new("cli_search");
+        tools.add_search_result(serde_json::json!({
+            "id": "fJ9rUzIMcZQ",
+            "title": "Queen - Bohemian Rhapsody (Official Video)",
+            "uploader": "Queen Official",
+            "duration": 355
+        }));
+        let data_dir = PathBuf::from(tools.ytdlp_path());
+        let data_dir = data_dir.parent().unwrap().to_str().unwrap();
+
+        let mut out = Vec::new();
+        let code = run_with(
+            &args(&[
+                "--json",
+                "--data-dir",
+                data_dir,
+                "search",
+                "Bohemian Rhapsody",
+            ]),
+            &mut out,
+            &mut Vec::new(),
+        );
+        assert_eq!(code, EXIT_OK);
+        let found: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(found["id"], "fJ9rUzIMcZQ");
+        assert!(tools.ytdlp_calls()[0].contains("official"));
+    }
+}
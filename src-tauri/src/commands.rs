@@ -0,0 +1,522 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::batch_report::{batch_report, write_playlist, write_report, BatchReportRow, InputError, ReportFormat};
+use crate::cleanup_rules::{build_cleanup_previews, CleanupPreview, CleanupRule};
+use crate::conflict::ConflictPolicy;
+use crate::csv_parser::{read_csv_headers, validate_csv_headers, CsvImportResult};
+use crate::csv_profiles::{detect_profile, CsvProfileDetection};
+use crate::error::{AppError, AppResult};
+use crate::ffmpeg_setup;
+use crate::file_processor::{clean_filename_with_rules, convert_to_mp3_with_ffmpeg};
+use crate::filename_template::FilenameTemplate;
+use crate::link_resolver::OpenGraphResolver;
+use crate::metadata::{tag_mp3, TrackMetadata};
+use crate::organizer::{organize_folder, OrganizeMode, OrganizeReport};
+use crate::http_server::HttpServer;
+use crate::pipeline::{retry_event, EventSink, ItemRequest, Pipeline, QueueItem};
+use crate::queue::{self, QueueEntry, QueueSnapshot, QueueSource};
+use crate::rate_limit;
+use crate::search::{FallbackSearch, SearchBackend};
+use crate::search_scoring::SearchHints;
+use crate::settings::{load_settings, save_settings, settings_path, AppSettings};
+use crate::subtitles::SubtitleOptions;
+use crate::watch_folder;
+use crate::youtube_client::VideoInfo;
+use crate::ytdlp::YtDlp;
+use crate::ytdlp_setup;
+use crate::{build_input_items, import_csv_tracks, AudioMode, ProcessInputResult, ProcessItemResult};
+
+impl EventSink for tauri::Window {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+}
+
+impl EventSink for tauri::AppHandle {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+}
+
+fn app_data_dir(app_handle: &tauri::AppHandle) -> AppResult<PathBuf> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::internal(format!("Failed to get app data dir: {}", e)))
+}
+
+fn app_settings_path(app_handle: &tauri::AppHandle) -> AppResult<PathBuf> {
+    Ok(settings_path(&app_data_dir(app_handle)?))
+}
+
+fn get_ytdlp_command(app_handle: &tauri::AppHandle) -> AppResult<String> {
+    ytdlp_setup::get_ytdlp_command(&app_data_dir(app_handle)?)
+}
+
+#[tauri::command]
+fn check_ytdlp(app_handle: tauri::AppHandle) -> AppResult<String> {
+    ytdlp_setup::check_ytdlp(&app_data_dir(&app_handle)?)
+}
+
+#[tauri::command]
+async fn download_ytdlp(app_handle: tauri::AppHandle) -> AppResult<String> {
+    ytdlp_setup::download_ytdlp(&app_data_dir(&app_handle)?, |status, message| {
+        let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
+            "status": status,
+            "message": message
+        }));
+    })
+    .await
+}
+
+#[tauri::command]
+fn check_ffmpeg(app_handle: tauri::AppHandle) -> AppResult<String> {
+    ffmpeg_setup::check_ffmpeg(&app_data_dir(&app_handle)?)
+}
+
+#[tauri::command]
+async fn download_ffmpeg(app_handle: tauri::AppHandle) -> AppResult<String> {
+    ffmpeg_setup::download_ffmpeg(&app_data_dir(&app_handle)?).await
+}
+
+#[tauri::command]
+fn get_settings(app_handle: tauri::AppHandle) -> AppResult<AppSettings> {
+    load_settings(&app_settings_path(&app_handle)?)
+}
+
+#[tauri::command]
+fn update_settings(settings: AppSettings, app_handle: tauri::AppHandle) -> AppResult<AppSettings> {
+    settings.validate().map_err(AppError::invalid_input)?;
+    save_settings(&app_settings_path(&app_handle)?, &settings)?;
+    Ok(settings)
+}
+
+#[tauri::command]
+fn preview_cleanup(
+    titles: Vec<String>,
+    rules: Option<Vec<CleanupRule>>,
+    app_handle: tauri::AppHandle,
+) -> AppResult<Vec<CleanupPreview>> {
+    let rules = match rules {
+        Some(rules) => rules,
+        None => load_settings(&app_settings_path(&app_handle)?)?.cleanup_rules,
+    };
+    Ok(build_cleanup_previews(&titles, &rules))
+}
+
+#[tauri::command]
+async fn set_download_path(window: tauri::Window) -> AppResult<Option<String>> {
+    let path = window
+        .dialog()
+        .file()
+        .set_title("Select Download Path")
+        .blocking_pick_folder();
+
+    Ok(path.map(|fp| fp.to_string()))
+}
+
+#[tauri::command]
+fn open_folder(path: String) -> AppResult<()> {
+    tauri_plugin_opener::reveal_item_in_dir(&path).map_err(|e| AppError::internal(e.to_string()))
+}
+
+#[tauri::command]
+async fn search_video_command(
+    query: String,
+    audio_mode: Option<AudioMode>,
+    hints: Option<SearchHints>,
+    app_handle: tauri::AppHandle,
+) -> AppResult<Option<VideoInfo>> {
+    let ytdlp_path = get_ytdlp_command(&app_handle)?;
+    let settings = load_settings(&app_settings_path(&app_handle)?)?;
+    rate_limit::global().configure(&settings.rate_limits);
+    let ytdlp = YtDlp::new(&ytdlp_path, &settings.ytdlp);
+    let search = FallbackSearch::from_settings(&ytdlp, &settings.search, audio_mode.as_ref());
+    let hints = hints.unwrap_or_default();
+    settings.retry.run(
+        |attempt| {
+            let _ = app_handle.emit("item-retry", retry_event("search", &query, attempt));
+        },
+        |_| search.search(&query, &hints),
+    ).await
+}
+
+#[tauri::command]
+async fn download_video_command(
+    video_id: String,
+    output_path: String,
+    window: tauri::Window,
+) -> AppResult<String> {
+    let ytdlp_path = get_ytdlp_command(&window.app_handle())?;
+    let ffmpeg_path = match check_ffmpeg(window.app_handle().clone()) {
+        Ok(p) => p,
+        Err(_) => download_ffmpeg(window.app_handle().clone()).await.map_err(|e| AppError {
+            message: format!(
+                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
+                e
+            ),
+            ..e
+        })?,
+    };
+    let settings = load_settings(&app_settings_path(window.app_handle())?)?;
+    rate_limit::global().configure(&settings.rate_limits);
+    let permit = rate_limit::global().start_download().await;
+    YtDlp::new(&ytdlp_path, &settings.ytdlp).download(
+        &video_id,
+        &output_path,
+        Some(&ffmpeg_path),
+        None,
+        permit.limit_rate(),
+        |progress, message| {
+        let _ = window.emit(
+            "download-progress",
+            serde_json::json!({
+                "progress": progress,
+                "message": message
+            }),
+        );
+    },
+    ).await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn process_item(
+    target: String,
+    output_path: String,
+    metadata_override: Option<TrackMetadata>,
+    subtitle_options: Option<SubtitleOptions>,
+    filename_template: Option<String>,
+    video_info: Option<VideoInfo>,
+    organize_mode: Option<OrganizeMode>,
+    conflict_policy: Option<ConflictPolicy>,
+    search_query: Option<String>,
+    search_hints: Option<SearchHints>,
+    audio_mode: Option<AudioMode>,
+    window: tauri::Window,
+) -> AppResult<ProcessItemResult> {
+    let ytdlp_path = get_ytdlp_command(&window.app_handle())?;
+    let ffmpeg_path = match check_ffmpeg(window.app_handle().clone()) {
+        Ok(p) => p,
+        Err(_) => download_ffmpeg(window.app_handle().clone()).await.map_err(|e| AppError {
+            message: format!(
+                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
+                e
+            ),
+            ..e
+        })?,
+    };
+    let settings = load_settings(&app_settings_path(window.app_handle())?)?;
+    rate_limit::global().configure(&settings.rate_limits);
+    let ytdlp = YtDlp::new(&ytdlp_path, &settings.ytdlp);
+    let request = ItemRequest {
+        target,
+        output_path,
+        metadata_override,
+        subtitle_options,
+        filename_template,
+        video_info,
+        organize_mode,
+        conflict_policy,
+        search_query,
+        search_hints,
+        audio_mode,
+    };
+    Pipeline::new(&ytdlp, &ffmpeg_path, &settings)
+        .process_item(request, &window)
+        .await
+}
+
+#[tauri::command]
+fn enqueue_items(items: Vec<QueueItem>, output_path: String, audio_mode: AudioMode) -> Vec<u64> {
+    queue::global().enqueue(items, &output_path, &audio_mode, QueueSource::App)
+}
+
+#[tauri::command]
+fn get_queue() -> QueueSnapshot {
+    queue::global().snapshot()
+}
+
+#[tauri::command]
+fn cancel_queue_item(id: u64) -> bool {
+    queue::global().cancel(id)
+}
+
+/// The report of a finished batch, with a failed row for each input that never became an item.
+#[tauri::command]
+fn build_batch_report(ids: Vec<u64>, input_errors: Vec<InputError>) -> Vec<BatchReportRow> {
+    let rows = queue::global()
+        .finished(&ids)
+        .iter()
+        .map(QueueEntry::report_row)
+        .collect();
+    batch_report(rows, &input_errors)
+}
+
+#[tauri::command]
+fn get_queue_history(limit: Option<usize>) -> Vec<QueueEntry> {
+    queue::global().history(limit.unwrap_or(usize::MAX))
+}
+
+// The server starts before the window can listen for events, so a failure waits to be asked about
+static LOCAL_SERVER_ERROR: OnceLock<AppError> = OnceLock::new();
+
+#[tauri::command]
+fn check_local_server() -> AppResult<()> {
+    match LOCAL_SERVER_ERROR.get() {
+        Some(error) => Err(error.clone()),
+        None => Ok(()),
+    }
+}
+
+// Runs the queue and the watch folders for the lifetime of the app, forwards queue events to
+// the window and, when enabled, starts the local HTTP server on top of it
+fn start_queue(app_handle: &tauri::AppHandle) -> AppResult<()> {
+    let data_dir = app_data_dir(app_handle)?;
+    let settings = load_settings(&settings_path(&data_dir))?;
+
+    let mut events = queue::global().subscribe();
+    let forward_to = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => forward_to.emit_event(&event.event, event.payload),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    if settings.http_server.enabled {
+        let server = HttpServer::new(&settings.http_server, &data_dir, queue::global());
+        match tauri::async_runtime::block_on(HttpServer::bind(settings.http_server.port)) {
+            Ok(listener) => {
+                tauri::async_runtime::spawn(server.serve(listener));
+            }
+            Err(e) => {
+                let _ = LOCAL_SERVER_ERROR.set(e);
+            }
+        }
+    }
+
+    let watch_dir = data_dir.clone();
+    tauri::async_runtime::spawn(async move {
+        watch_folder::run_watcher(queue::global(), &watch_dir).await
+    });
+
+    tauri::async_runtime::spawn(async move {
+        queue::run_worker(queue::global(), &data_dir).await
+    });
+    Ok(())
+}
+
+#[tauri::command]
+fn clean_filename_command(original_name: String, app_handle: tauri::AppHandle) -> AppResult<String> {
+    let settings = load_settings(&app_settings_path(&app_handle)?)?;
+    Ok(clean_filename_with_rules(&original_name, &settings.cleanup_rules))
+}
+
+#[tauri::command]
+async fn convert_to_mp3_command(
+    input_path: String,
+    output_path: String,
+    window: tauri::Window,
+) -> AppResult<String> {
+    let window_clone = window.clone();
+    let ffmpeg_path = match check_ffmpeg(window.app_handle().clone()) {
+        Ok(p) => p,
+        Err(_) => download_ffmpeg(window.app_handle().clone()).await.map_err(|e| AppError {
+            message: format!(
+                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
+                e
+            ),
+            ..e
+        })?,
+    };
+    convert_to_mp3_with_ffmpeg(&ffmpeg_path, &input_path, &output_path).inspect_err(|e| {
+        let _ = window_clone.emit(
+            "conversion-error",
+            serde_json::json!({
+                "error": e.message
+            }),
+        );
+    })
+}
+
+#[tauri::command]
+fn validate_filename_template(template: String) -> AppResult<()> {
+    FilenameTemplate::parse(&template)
+        .map(|_| ())
+        .map_err(AppError::invalid_input)
+}
+
+#[tauri::command]
+async fn organize_library(
+    library_path: String,
+    mode: OrganizeMode,
+) -> AppResult<OrganizeReport> {
+    organize_folder(&library_path, &mode)
+}
+
+#[tauri::command]
+fn export_batch_report(
+    rows: Vec<BatchReportRow>,
+    report_path: String,
+    format: Option<ReportFormat>,
+    playlist_path: Option<String>,
+) -> AppResult<()> {
+    let report_path = PathBuf::from(report_path);
+    let format = format.unwrap_or_else(|| ReportFormat::from_path(&report_path));
+    write_report(&rows, &report_path, format)?;
+
+    if let Some(playlist_path) = playlist_path {
+        write_playlist(&rows, Path::new(&playlist_path))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn read_file_command(path: String) -> AppResult<String> {
+    fs::read_to_string(&path).map_err(|e| AppError::io(&format!("Failed to read {}", path), e))
+}
+
+// CSV files are read as bytes so exports in UTF-16 or legacy code pages can be decoded
+fn read_csv_file(path: &str) -> AppResult<Vec<u8>> {
+    fs::read(path).map_err(|e| AppError::io("Failed to read CSV file", e))
+}
+
+#[tauri::command]
+fn parse_csv_command(
+    csv_path: String,
+    audio_mode: AudioMode,
+    profile: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> AppResult<CsvImportResult> {
+    let settings = load_settings(&app_settings_path(&app_handle)?)?;
+    import_csv_tracks(&read_csv_file(&csv_path)?, &settings, profile.as_deref(), &audio_mode)
+}
+
+#[tauri::command]
+fn validate_csv_command(csv_path: String, app_handle: tauri::AppHandle) -> AppResult<Vec<String>> {
+    let settings = load_settings(&app_settings_path(&app_handle)?)?;
+    validate_csv_headers(&read_csv_file(&csv_path)?, &settings.all_csv_profiles()).map_err(AppError::invalid_input)
+}
+
+#[tauri::command]
+fn detect_csv_profile(
+    csv_path: String,
+    app_handle: tauri::AppHandle,
+) -> AppResult<Option<CsvProfileDetection>> {
+    let settings = load_settings(&app_settings_path(&app_handle)?)?;
+    let headers = read_csv_headers(&read_csv_file(&csv_path)?).map_err(AppError::invalid_input)?;
+    Ok(detect_profile(&settings.all_csv_profiles(), &headers))
+}
+
+#[tauri::command]
+fn tag_mp3_command(
+    file_path: String,
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    year: Option<String>,
+    genre: Option<String>,
+    track_number: Option<String>,
+) -> AppResult<()> {
+    let metadata = TrackMetadata {
+        title,
+        subtitle: None,
+        artist,
+        album,
+        year,
+        genre,
+        track_number,
+        album_artist: None,
+        comment: Some("Downloaded from YouTube".to_string()),
+        lyrics: None,
+        label: None,
+        copyright: None,
+        release_date: None,
+        disc_number: None,
+        isrc: None,
+    };
+
+    tag_mp3(&file_path, metadata)
+}
+
+#[tauri::command]
+async fn process_input(
+    input_text: String,
+    audio_mode: AudioMode,
+    app_handle: tauri::AppHandle,
+) -> AppResult<ProcessInputResult> {
+    let settings = load_settings(&app_settings_path(&app_handle)?)?;
+    let resolver = OpenGraphResolver::new(&settings.resolver)?;
+    Ok(build_input_items(&input_text, &audio_mode, &resolver).await)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .setup(|app| Ok(start_queue(app.handle())?))
+        .invoke_handler(tauri::generate_handler![
+            check_ytdlp,
+            download_ytdlp,
+            check_ffmpeg,
+            download_ffmpeg,
+            get_settings,
+            update_settings,
+            preview_cleanup,
+            set_download_path,
+            open_folder,
+            process_input,
+            search_video_command,
+            download_video_command,
+            process_item,
+            enqueue_items,
+            get_queue,
+            cancel_queue_item,
+            get_queue_history,
+            build_batch_report,
+            check_local_server,
+            clean_filename_command,
+            validate_filename_template,
+            organize_library,
+            convert_to_mp3_command,
+            read_file_command,
+            parse_csv_command,
+            validate_csv_command,
+            detect_csv_profile,
+            export_batch_report,
+            tag_mp3_command
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_folder_with_valid_path() {
+        let result = open_folder("/tmp".to_string());
+        assert!(result.is_ok() || result.is_err());
+    }
+
+    #[test]
+    fn test_open_folder_with_nonexistent_path() {
+        let result = open_folder("/nonexistent/path/that/does/not/exist".to_string());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_open_folder_does_not_panic() {
+        let _ = open_folder("".to_string());
+    }
+}
@@ -0,0 +1,478 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+
+const MAX_SUFFIX_ATTEMPTS: u32 = 1000;
+const PROBE_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    #[default]
+    AutoSuffix,
+    KeepBetter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    NoConflict,
+    Skipped,
+    Overwritten,
+    Suffixed,
+    KeptExisting,
+    ReplacedExisting,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlacementResult {
+    pub path: String,
+    pub resolution: ConflictResolution,
+    pub conflicting_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioQuality {
+    pub bitrate_kbps: u32,
+    pub duration_seconds: f64,
+}
+
+pub fn suffixed_path(desired: &Path, index: u32) -> PathBuf {
+    let stem = desired
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = match desired.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, index, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, index),
+    };
+    desired.with_file_name(file_name)
+}
+
+fn temporary_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{}.partial", name))
+}
+
+// Links instead of renaming so an existing file is never clobbered, even if one appears mid-batch
+fn place_without_clobber(source: &Path, target: &Path) -> AppResult<bool> {
+    match fs::hard_link(source, target) {
+        Ok(()) => {
+            fs::remove_file(source).map_err(|e| AppError::io("Failed to remove temporary file", e))?;
+            Ok(true)
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(_) => {
+            // Some filesystems (FAT, network shares) don't support hard links
+            if target.exists() {
+                return Ok(false);
+            }
+            fs::rename(source, target).map_err(|e| AppError::io("Failed to rename file", e))?;
+            Ok(true)
+        }
+    }
+}
+
+fn replace_atomically(source: &Path, target: &Path) -> AppResult<()> {
+    let staging = temporary_path(target);
+    fs::rename(source, &staging).map_err(|e| AppError::io("Failed to stage file", e))?;
+
+    // On Windows rename() is MoveFileExW with MOVEFILE_REPLACE_EXISTING, so the old file
+    // stays in place until the new one replaces it
+    fs::rename(&staging, target).map_err(|e| {
+        let _ = fs::rename(&staging, source);
+        AppError::io("Failed to replace existing file", e)
+    })
+}
+
+fn read_probe(path: &Path) -> Option<Vec<u8>> {
+    let mut buffer = Vec::new();
+    fs::File::open(path)
+        .ok()?
+        .take(PROBE_BYTES)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    Some(buffer)
+}
+
+fn id3v2_length(bytes: &[u8]) -> usize {
+    if bytes.len() < 10 || &bytes[..3] != b"ID3" {
+        return 0;
+    }
+    let size = bytes[6..10]
+        .iter()
+        .fold(0usize, |acc, b| (acc << 7) | (*b as usize & 0x7f));
+    let footer = if bytes[5] & 0x10 != 0 { 10 } else { 0 };
+    10 + size + footer
+}
+
+pub fn probe_mp3_quality(path: &Path) -> Option<AudioQuality> {
+    const MPEG1_BITRATES: [u32; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+    const MPEG2_BITRATES: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+    const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+    let file_size = fs::metadata(path).ok()?.len();
+    let bytes = read_probe(path)?;
+    let audio_start = id3v2_length(&bytes);
+
+    let header_offset = (audio_start..bytes.len().saturating_sub(4)).find(|&i| {
+        bytes[i] == 0xff
+            && bytes[i + 1] & 0xe0 == 0xe0
+            && (bytes[i + 1] >> 1) & 0x03 == 0x01
+            && bytes[i + 2] >> 4 != 0x0f
+            && bytes[i + 2] >> 4 != 0
+            && (bytes[i + 2] >> 2) & 0x03 != 0x03
+    })?;
+
+    let header = &bytes[header_offset..header_offset + 4];
+    let version = (header[1] >> 3) & 0x03;
+    let is_mpeg1 = version == 0x03;
+    let bitrate_index = (header[2] >> 4) as usize;
+    let sample_rate_divisor = match version {
+        0x03 => 1,
+        0x02 => 2,
+        _ => 4,
+    };
+    let sample_rate = SAMPLE_RATES[((header[2] >> 2) & 0x03) as usize] / sample_rate_divisor;
+    let samples_per_frame: u32 = if is_mpeg1 { 1152 } else { 576 };
+    let audio_bytes = file_size.saturating_sub(header_offset as u64);
+
+    // VBR files written by LAME carry the real frame count in a Xing/Info header
+    let frame_window = &bytes[header_offset..(header_offset + 200).min(bytes.len())];
+    let vbr_frames = frame_window
+        .windows(4)
+        .position(|w| w == b"Xing" || w == b"Info")
+        .and_then(|pos| {
+            let flags = frame_window.get(pos + 4..pos + 8)?;
+            if flags[3] & 0x01 == 0 {
+                return None;
+            }
+            let frames = frame_window.get(pos + 8..pos + 12)?;
+            Some(u32::from_be_bytes([frames[0], frames[1], frames[2], frames[3]]))
+        })
+        .filter(|frames| *frames > 0);
+
+    match vbr_frames {
+        Some(frames) => {
+            let duration_seconds = frames as f64 * samples_per_frame as f64 / sample_rate as f64;
+            let bitrate_kbps = (audio_bytes as f64 * 8.0 / duration_seconds / 1000.0).round() as u32;
+            Some(AudioQuality {
+                bitrate_kbps,
+                duration_seconds,
+            })
+        }
+        None => {
+            let table = if is_mpeg1 { &MPEG1_BITRATES } else { &MPEG2_BITRATES };
+            let bitrate_kbps = table[bitrate_index];
+            let duration_seconds = audio_bytes as f64 * 8.0 / (bitrate_kbps as f64 * 1000.0);
+            Some(AudioQuality {
+                bitrate_kbps,
+                duration_seconds,
+            })
+        }
+    }
+}
+
+fn new_file_is_better(new_file: &Path, existing: &Path) -> bool {
+    match (probe_mp3_quality(new_file), probe_mp3_quality(existing)) {
+        (Some(new_quality), Some(existing_quality)) => {
+            if new_quality.bitrate_kbps != existing_quality.bitrate_kbps {
+                new_quality.bitrate_kbps > existing_quality.bitrate_kbps
+            } else {
+                new_quality.duration_seconds > existing_quality.duration_seconds + 1.0
+            }
+        }
+        _ => {
+            let size = |p: &Path| fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+            size(new_file) > size(existing)
+        }
+    }
+}
+
+pub fn place_file(
+    source: &Path,
+    desired: &Path,
+    policy: ConflictPolicy,
+) -> AppResult<PlacementResult> {
+    if let Some(parent) = desired.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create output directory", e))?;
+    }
+
+    let desired_str = desired.to_string_lossy().to_string();
+
+    if source == desired {
+        return Ok(PlacementResult {
+            path: desired_str,
+            resolution: ConflictResolution::NoConflict,
+            conflicting_path: None,
+        });
+    }
+
+    if place_without_clobber(source, desired)? {
+        return Ok(PlacementResult {
+            path: desired_str,
+            resolution: ConflictResolution::NoConflict,
+            conflicting_path: None,
+        });
+    }
+
+    let discard_source = |resolution| {
+        fs::remove_file(source).map_err(|e| AppError::io("Failed to remove duplicate download", e))?;
+        Ok(PlacementResult {
+            path: desired_str.clone(),
+            resolution,
+            conflicting_path: Some(desired_str.clone()),
+        })
+    };
+
+    match policy {
+        ConflictPolicy::Skip => discard_source(ConflictResolution::Skipped),
+        ConflictPolicy::KeepBetter if !new_file_is_better(source, desired) => {
+            discard_source(ConflictResolution::KeptExisting)
+        }
+        ConflictPolicy::KeepBetter | ConflictPolicy::Overwrite => {
+            replace_atomically(source, desired)?;
+            let resolution = if policy == ConflictPolicy::Overwrite {
+                ConflictResolution::Overwritten
+            } else {
+                ConflictResolution::ReplacedExisting
+            };
+            Ok(PlacementResult {
+                path: desired_str.clone(),
+                resolution,
+                conflicting_path: Some(desired_str),
+            })
+        }
+        ConflictPolicy::AutoSuffix => {
+            for index in 2..MAX_SUFFIX_ATTEMPTS {
+                let candidate = suffixed_path(desired, index);
+                if place_without_clobber(source, &candidate)? {
+                    return Ok(PlacementResult {
+                        path: candidate.to_string_lossy().to_string(),
+                        resolution: ConflictResolution::Suffixed,
+                        conflicting_path: Some(desired_str),
+                    });
+                }
+            }
+            Err(AppError::internal(format!("Too many files named like {}", desired_str)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Minimal MPEG-1 Layer III frame header: 44.1 kHz, given bitrate index
+    fn cbr_mp3(bitrate_index: u8, frames: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for _ in 0..frames {
+            bytes.extend_from_slice(&[0xff, 0xfb, bitrate_index << 4, 0x00]);
+            bytes.extend_from_slice(&[0u8; 413]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_suffixed_path() {
+        let path = suffixed_path(Path::new("/music/Song.mp3"), 2);
+        assert_eq!(path, PathBuf::from("/music/Song (2).mp3"));
+    }
+
+    #[test]
+    fn test_place_file_without_conflict() {
+        let dir = test_dir("conflict_no_conflict_test");
+        let source = dir.join("download.mp3");
+        fs::write(&source, b"new").unwrap();
+
+        let result = place_file(&source, &dir.join("Song.mp3"), ConflictPolicy::Skip).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::NoConflict);
+        assert_eq!(fs::read(dir.join("Song.mp3")).unwrap(), b"new");
+        assert!(!source.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_place_file_creates_directories() {
+        let dir = test_dir("conflict_creates_dirs_test");
+        let source = dir.join("download.mp3");
+        fs::write(&source, b"new").unwrap();
+        let desired = dir.join("Artist").join("Album").join("Song.mp3");
+
+        let result = place_file(&source, &desired, ConflictPolicy::AutoSuffix).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::NoConflict);
+        assert!(desired.is_file());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_place_file_skip_keeps_existing() {
+        let dir = test_dir("conflict_skip_test");
+        let source = dir.join("download.mp3");
+        let desired = dir.join("Song.mp3");
+        fs::write(&source, b"new").unwrap();
+        fs::write(&desired, b"old").unwrap();
+
+        let result = place_file(&source, &desired, ConflictPolicy::Skip).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::Skipped);
+        assert_eq!(fs::read(&desired).unwrap(), b"old");
+        assert!(!source.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_place_file_overwrite_replaces_existing() {
+        let dir = test_dir("conflict_overwrite_test");
+        let source = dir.join("download.mp3");
+        let desired = dir.join("Song.mp3");
+        fs::write(&source, b"new").unwrap();
+        fs::write(&desired, b"old").unwrap();
+
+        let result = place_file(&source, &desired, ConflictPolicy::Overwrite).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::Overwritten);
+        assert_eq!(fs::read(&desired).unwrap(), b"new");
+        assert!(!source.exists());
+        assert!(!temporary_path(&desired).exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_replace_atomically_failure_keeps_existing_and_source() {
+        let dir = test_dir("conflict_replace_failure_test");
+        let source = dir.join("download.mp3");
+        let desired = dir.join("Song.mp3");
+        fs::write(&source, b"new").unwrap();
+        fs::create_dir_all(desired.join("occupied")).unwrap();
+
+        assert!(replace_atomically(&source, &desired).is_err());
+
+        assert!(desired.join("occupied").is_dir());
+        assert_eq!(fs::read(&source).unwrap(), b"new");
+        assert!(!temporary_path(&desired).exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_place_file_auto_suffix() {
+        let dir = test_dir("conflict_suffix_test");
+        let desired = dir.join("Song.mp3");
+        fs::write(&desired, b"first").unwrap();
+        fs::write(dir.join("Song (2).mp3"), b"second").unwrap();
+        let source = dir.join("download.mp3");
+        fs::write(&source, b"third").unwrap();
+
+        let result = place_file(&source, &desired, ConflictPolicy::AutoSuffix).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::Suffixed);
+        assert_eq!(PathBuf::from(&result.path), dir.join("Song (3).mp3"));
+        assert_eq!(result.conflicting_path, Some(desired.to_string_lossy().to_string()));
+        assert_eq!(fs::read(&desired).unwrap(), b"first");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_place_file_keep_better_prefers_higher_bitrate() {
+        let dir = test_dir("conflict_keep_better_test");
+        let desired = dir.join("Song.mp3");
+        let source = dir.join("download.mp3");
+        fs::write(&desired, cbr_mp3(0x09, 20)).unwrap();
+        fs::write(&source, cbr_mp3(0x0e, 20)).unwrap();
+
+        let result = place_file(&source, &desired, ConflictPolicy::KeepBetter).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::ReplacedExisting);
+        assert_eq!(probe_mp3_quality(&desired).unwrap().bitrate_kbps, 320);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_place_file_keep_better_keeps_existing() {
+        let dir = test_dir("conflict_keep_existing_test");
+        let desired = dir.join("Song.mp3");
+        let source = dir.join("download.mp3");
+        fs::write(&desired, cbr_mp3(0x0e, 20)).unwrap();
+        fs::write(&source, cbr_mp3(0x09, 20)).unwrap();
+
+        let result = place_file(&source, &desired, ConflictPolicy::KeepBetter).unwrap();
+
+        assert_eq!(result.resolution, ConflictResolution::KeptExisting);
+        assert_eq!(probe_mp3_quality(&desired).unwrap().bitrate_kbps, 320);
+        assert!(!source.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_probe_mp3_quality_cbr() {
+        let dir = test_dir("conflict_probe_cbr_test");
+        let file = dir.join("cbr.mp3");
+        fs::write(&file, cbr_mp3(0x09, 10)).unwrap();
+
+        let quality = probe_mp3_quality(&file).unwrap();
+
+        assert_eq!(quality.bitrate_kbps, 128);
+        assert!(quality.duration_seconds > 0.0);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_probe_mp3_quality_skips_id3_tag() {
+        let dir = test_dir("conflict_probe_id3_test");
+        let file = dir.join("tagged.mp3");
+        let mut bytes = b"ID3\x04\x00\x00\x00\x00\x00\x05".to_vec();
+        bytes.extend_from_slice(&[0xff, 0xfb, 0x00, 0x00, 0x00]);
+        bytes.extend(cbr_mp3(0x0b, 5));
+        fs::write(&file, bytes).unwrap();
+
+        assert_eq!(probe_mp3_quality(&file).unwrap().bitrate_kbps, 192);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_probe_mp3_quality_vbr_header() {
+        let dir = test_dir("conflict_probe_vbr_test");
+        let file = dir.join("vbr.mp3");
+        let mut first_frame = vec![0xff, 0xfb, 0x90, 0x00];
+        first_frame.extend_from_slice(&[0u8; 32]);
+        first_frame.extend_from_slice(b"Xing");
+        first_frame.extend_from_slice(&[0, 0, 0, 0x01]);
+        first_frame.extend_from_slice(&1000u32.to_be_bytes());
+        first_frame.resize(417, 0);
+        fs::write(&file, first_frame).unwrap();
+
+        let quality = probe_mp3_quality(&file).unwrap();
+
+        let expected_duration = 1000.0 * 1152.0 / 44100.0;
+        assert!((quality.duration_seconds - expected_duration).abs() < 0.01);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_probe_mp3_quality_not_mp3() {
+        let dir = test_dir("conflict_probe_invalid_test");
+        let file = dir.join("text.mp3");
+        fs::write(&file, b"definitely not audio").unwrap();
+
+        assert!(probe_mp3_quality(&file).is_none());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -2,14 +2,11 @@ use csv::ReaderBuilder;
 use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 
-pub const EXPECTED_HEADERS: &[&str] = &[
-    "Artist Name(s)",
-    "Track Name",
-    "Album Name",
-    "Artist Genres",
-    "Album Release Date",
-    "BPM/Tempo",
-];
+use crate::batch_report::InputError;
+use crate::csv_profiles::{
+    detect_profile, generic_csv_profile, match_columns, CsvField, CsvProfile, DurationUnit,
+};
+use crate::csv_sniffer::{decode_and_sniff, CsvFormat};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct CsvTrackMetadata {
@@ -19,6 +16,37 @@ pub struct CsvTrackMetadata {
     pub artist_genres: Option<String>,
     pub album_release_date: Option<String>,
     pub bpm_tempo: Option<String>,
+    pub isrc: Option<String>,
+    pub duration: Option<String>,
+    pub track_number: Option<String>,
+    pub disc_number: Option<String>,
+    pub album_artist_names: Option<String>,
+    pub label: Option<String>,
+    pub explicit: Option<String>,
+    pub popularity: Option<String>,
+    pub spotify_uri: Option<String>,
+}
+
+impl CsvTrackMetadata {
+    /// Reads the duration in its column's unit. Without one, "m:ss"/"h:mm:ss" clock times and
+    /// plain seconds are both accepted.
+    pub fn duration_seconds(&self, unit: Option<DurationUnit>) -> Option<u64> {
+        let raw = self.duration.as_deref()?.trim();
+        let clock = || {
+            raw.split(':').try_fold(0u64, |total, part| {
+                part.trim().parse::<u64>().ok().map(|n| total * 60 + n)
+            })
+        };
+        let number = || raw.parse::<f64>().ok().filter(|v| *v >= 0.0);
+
+        match unit {
+            Some(DurationUnit::Clock) => clock(),
+            Some(DurationUnit::Milliseconds) => number().map(|ms| (ms / 1000.0).round() as u64),
+            Some(DurationUnit::Seconds) => number().map(|s| s.round() as u64),
+            None if raw.contains(':') => clock(),
+            None => number().map(|s| s.round() as u64),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -27,7 +55,9 @@ pub struct CsvImportResult {
     pub total_count: usize,
     pub success_count: usize,
     pub error_count: usize,
-    pub errors: Vec<String>,
+    pub errors: Vec<InputError>,
+    pub profile: Option<String>,
+    pub format: Option<CsvFormat>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -35,45 +65,86 @@ pub struct CsvTrackEntry {
     pub row_number: usize,
     pub metadata: CsvTrackMetadata,
     pub search_query: String,
+    pub duration_seconds: Option<u64>,
 }
 
-fn normalize_header(header: &str) -> String {
+pub fn normalize_header(header: &str) -> String {
     header
+        .trim()
         .to_lowercase()
         .replace([' ', '-', '/'], "_")
         .replace(['(', ')', '[', ']', '"'], "")
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
 }
 
-fn find_column_index(headers: &[String], target: &str) -> Option<usize> {
-    let normalized_target = normalize_header(target);
-
-    for (index, header) in headers.iter().enumerate() {
-        let normalized_header = normalize_header(header);
-        if normalized_header == normalized_target
-            || normalized_header.contains(&normalized_target)
-            || normalized_target.contains(&normalized_header)
-        {
-            return Some(index);
-        }
-    }
-    None
-}
-
-pub fn parse_csv_content(content: &str) -> Result<CsvImportResult, String> {
-    let mut reader = ReaderBuilder::new()
+fn csv_reader<'a>(text: &'a str, format: &CsvFormat) -> csv::Reader<Cursor<&'a str>> {
+    ReaderBuilder::new()
         .has_headers(true)
         .flexible(true)
-        .from_reader(Cursor::new(content));
+        .delimiter(format.delimiter as u8)
+        .quote(format.quote as u8)
+        .from_reader(Cursor::new(text))
+}
 
-    let headers: Vec<String> = reader
+fn headers_of(reader: &mut csv::Reader<Cursor<&str>>) -> Result<Vec<String>, String> {
+    Ok(reader
         .headers()
         .map_err(|e| format!("Failed to read CSV headers: {}", e))?
         .iter()
-        .map(|s| s.to_string())
-        .collect();
+        .map(|s| s.trim().to_string())
+        .collect())
+}
+
+pub fn read_csv_headers(content: &[u8]) -> Result<Vec<String>, String> {
+    let (text, format) = decode_and_sniff(content);
+    headers_of(&mut csv_reader(&text, &format))
+}
+
+// Files no profile recognises still import when the generic profile can find a track column
+fn select_profile(
+    profiles: &[CsvProfile],
+    headers: &[String],
+    profile_name: Option<&str>,
+) -> Result<CsvProfile, String> {
+    let name = match profile_name {
+        Some(name) => name.to_string(),
+        None => match detect_profile(profiles, headers) {
+            Some(detection) => detection.profile,
+            None => {
+                let generic = generic_csv_profile();
+                detect_profile(std::slice::from_ref(&generic), headers)
+                    .ok_or("Could not recognise the CSV columns - choose a mapping profile")?;
+                return Ok(generic);
+            }
+        },
+    };
+
+    profiles
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(&name))
+        .cloned()
+        .ok_or_else(|| format!("Unknown CSV profile: {}", name))
+}
+
+/// Parses raw file bytes in any common encoding and dialect, using the named profile or the best
+/// matching one when `profile_name` is `None`.
+pub fn parse_csv_content(
+    content: &[u8],
+    profiles: &[CsvProfile],
+    profile_name: Option<&str>,
+) -> Result<CsvImportResult, String> {
+    let (text, format) = decode_and_sniff(content);
+    let mut reader = csv_reader(&text, &format);
+    let headers = headers_of(&mut reader)?;
+
+    let profile = select_profile(profiles, &headers, profile_name)?;
+    let columns = match_columns(&profile, &headers);
 
     let mut tracks: Vec<CsvTrackEntry> = Vec::new();
-    let mut errors: Vec<String> = Vec::new();
+    let mut errors: Vec<InputError> = Vec::new();
     let mut success_count = 0;
 
     for (row_number, result) in reader.records().enumerate() {
@@ -81,61 +152,63 @@ pub fn parse_csv_content(content: &str) -> Result<CsvImportResult, String> {
 
         match result {
             Ok(record) => {
-                let artist_names = find_column_index(&headers, "Artist Name(s)")
-                    .and_then(|idx| record.get(idx).map(|s| s.to_string()))
-                    .filter(|s| !s.is_empty());
-
-                let track_name = find_column_index(&headers, "Track Name")
-                    .and_then(|idx| record.get(idx).map(|s| s.to_string()))
-                    .filter(|s| !s.is_empty());
-
-                let album_name = find_column_index(&headers, "Album Name")
-                    .and_then(|idx| record.get(idx).map(|s| s.to_string()))
-                    .filter(|s| !s.is_empty());
-
-                let artist_genres = find_column_index(&headers, "Artist Genres")
-                    .and_then(|idx| record.get(idx).map(|s| s.to_string()))
-                    .filter(|s| !s.is_empty());
-
-                let album_release_date = find_column_index(&headers, "Album Release Date")
-                    .and_then(|idx| record.get(idx).map(|s| s.to_string()))
-                    .filter(|s| !s.is_empty());
+                let value = |field: CsvField| {
+                    columns
+                        .iter()
+                        .find(|c| c.field == field)
+                        .and_then(|c| record.get(c.index))
+                        .map(|s| s.to_string())
+                        .filter(|s| !s.is_empty())
+                };
 
-                let bpm_tempo = find_column_index(&headers, "BPM/Tempo")
-                    .and_then(|idx| record.get(idx).map(|s| s.to_string()))
-                    .filter(|s| !s.is_empty());
+                let artist_names = value(CsvField::ArtistNames);
+                let track_name = value(CsvField::TrackName);
 
                 let search_query = match (&artist_names, &track_name) {
                     (Some(artist), Some(track)) => format!("{} - {}", artist, track),
                     (Some(artist), None) => artist.clone(),
                     (None, Some(track)) => track.clone(),
                     (None, None) => {
-                        errors.push(format!(
-                            "Row {}: Missing both 'Artist Name(s)' and 'Track Name' - cannot create search query",
-                            row_number
+                        errors.push(InputError::new(
+                            row_number,
+                            "",
+                            "Missing both artist and track name - cannot create search query",
                         ));
                         continue;
                     }
                 };
 
+                let metadata = CsvTrackMetadata {
+                    artist_names,
+                    track_name,
+                    album_name: value(CsvField::AlbumName),
+                    artist_genres: value(CsvField::ArtistGenres),
+                    album_release_date: value(CsvField::AlbumReleaseDate),
+                    bpm_tempo: value(CsvField::BpmTempo),
+                    isrc: value(CsvField::Isrc),
+                    duration: value(CsvField::Duration),
+                    track_number: value(CsvField::TrackNumber),
+                    disc_number: value(CsvField::DiscNumber),
+                    album_artist_names: value(CsvField::AlbumArtistNames),
+                    label: value(CsvField::Label),
+                    explicit: value(CsvField::Explicit),
+                    popularity: value(CsvField::Popularity),
+                    spotify_uri: value(CsvField::SpotifyUri),
+                };
+
                 tracks.push(CsvTrackEntry {
                     row_number,
-                    metadata: CsvTrackMetadata {
-                        artist_names,
-                        track_name,
-                        album_name,
-                        artist_genres,
-                        album_release_date,
-                        bpm_tempo,
-                    },
+                    duration_seconds: metadata.duration_seconds(profile.duration_unit()),
+                    metadata,
                     search_query: search_query.clone(),
                 });
                 success_count += 1;
             }
             Err(e) => {
-                errors.push(format!(
-                    "Row {}: Failed to parse CSV record: {}",
-                    row_number, e
+                errors.push(InputError::new(
+                    row_number,
+                    "",
+                    format!("Failed to parse CSV record: {}", e),
                 ));
             }
         }
@@ -149,35 +222,28 @@ pub fn parse_csv_content(content: &str) -> Result<CsvImportResult, String> {
         success_count,
         error_count: errors.len(),
         errors,
+        profile: Some(profile.name),
+        format: Some(format),
     })
 }
 
-pub fn validate_csv_headers(content: &str) -> Result<Vec<String>, String> {
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(Cursor::new(content));
-
-    let headers: Vec<String> = reader
-        .headers()
-        .map_err(|e| format!("Failed to read CSV headers: {}", e))?
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-
-    let mut found_headers: Vec<String> = Vec::new();
-
-    for expected in EXPECTED_HEADERS {
-        if find_column_index(&headers, expected).is_some() {
-            found_headers.push(expected.to_string());
-        }
-    }
-
-    Ok(found_headers)
+/// Returns the source columns the best matching profile would read.
+#[cfg(any(feature = "gui", test))]
+pub fn validate_csv_headers(
+    content: &[u8],
+    profiles: &[CsvProfile],
+) -> Result<Vec<String>, String> {
+    let headers = read_csv_headers(content)?;
+
+    Ok(detect_profile(profiles, &headers)
+        .map(|detection| detection.columns.into_iter().map(|c| c.column).collect())
+        .unwrap_or_default())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::csv_profiles::builtin_csv_profiles;
 
     #[test]
     fn test_parse_csv_content_with_valid_data() {
@@ -186,7 +252,8 @@ The Beatles,Hey Jude,Abbey Road,rock,1968,140
 Queen,Bohemian A Night At The Opera,rock,1975,145
 Led Zeppelin,Stairway To Heaven,Led Zeppelin IV,rock,1971,146"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.total_count, 3);
         assert_eq!(result.success_count, 3);
@@ -226,7 +293,8 @@ Led Zeppelin,Stairway To Heaven,Led Zeppelin IV,rock,1971,146"#;
         let csv_content = r#"Artist Name(s),Track Name
 The Beatles,Hey Jude"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.total_count, 1);
         assert_eq!(result.success_count, 1);
@@ -239,11 +307,13 @@ The Beatles,Hey Jude"#;
         let csv_content = r#"Artist Name(s),Track Name,Album Name
 ,,"Abbey Road""#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 0);
         assert_eq!(result.error_count, 1);
-        assert!(result.errors[0].contains("Missing both"));
+        assert_eq!(result.errors[0].row_number, 2);
+        assert!(result.errors[0].message.contains("Missing both"));
     }
 
     #[test]
@@ -251,7 +321,8 @@ The Beatles,Hey Jude"#;
         let csv_content = r#"Artist Name(s),Track Name,Album Name
 ,Hey Jude,Abbey Road"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 1);
         assert_eq!(result.error_count, 0);
@@ -263,7 +334,8 @@ The Beatles,Hey Jude"#;
         let csv_content = r#"Artist Name(s),Track Name,Album Name
 The Beatles,,Abbey Road"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 1);
         assert_eq!(result.error_count, 0);
@@ -277,7 +349,8 @@ The Beatles,Hey Jude,Abbey Road
 
 Queen,Bohemian A Night At The Opera,A Night At The Opera"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 2);
         assert_eq!(result.error_count, 0);
@@ -288,7 +361,8 @@ Queen,Bohemian A Night At The Opera,A Night At The Opera"#;
         let csv_content = r#"Artist Name(s),Track Name,Album Name
 Artist & Band,Song "Title" (Remix),Greatest Hits"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 1);
         assert_eq!(
@@ -301,7 +375,8 @@ Artist & Band,Song "Title" (Remix),Greatest Hits"#;
     fn test_parse_csv_content_handles_missing_header_row() {
         let csv_content = r#"Artist Name(s),Track Name"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 0);
         assert_eq!(result.error_count, 0);
@@ -313,7 +388,7 @@ Artist & Band,Song "Title" (Remix),Greatest Hits"#;
         let csv_content = r#"Artist Name(s),Track Name,Album Name,Artist Genres,Album Release Date,BPM/Tempo
 The Beatles,Hey Jude,Abbey Road,rock,1968,140"#;
 
-        let found = validate_csv_headers(csv_content).unwrap();
+        let found = validate_csv_headers(csv_content.as_bytes(), &builtin_csv_profiles()).unwrap();
 
         assert_eq!(found.len(), 6);
         assert!(found.contains(&"Artist Name(s)".to_string()));
@@ -326,7 +401,7 @@ The Beatles,Hey Jude,Abbey Road,rock,1968,140"#;
         let csv_content = r#"Artist,Track,Album,Genres,Release Date,Tempo
 The Beatles,Hey Jude,Abbey Road,rock,1968,140"#;
 
-        let found = validate_csv_headers(csv_content).unwrap();
+        let found = validate_csv_headers(csv_content.as_bytes(), &builtin_csv_profiles()).unwrap();
 
         assert!(found.len() > 0);
     }
@@ -336,6 +411,180 @@ The Beatles,Hey Jude,Abbey Road,rock,1968,140"#;
         assert_eq!(normalize_header("Artist Name(s)"), "artist_names");
         assert_eq!(normalize_header("BPM/Tempo"), "bpm_tempo");
         assert_eq!(normalize_header("Album Release Date"), "album_release_date");
+        assert_eq!(normalize_header(" Artist - Name "), "artist_name");
+    }
+
+    #[test]
+    fn test_parse_csv_content_with_extended_exportify_columns() {
+        let csv_content = r#"Track URI,Track Name,Artist Name(s),Album Name,Album Artist Name(s),Disc Number,Track Number,Track Duration (ms),Explicit,Popularity,ISRC,Record Label
+spotify:track:2Foc5Q5nqNiosCNqttzHof,Get Lucky,Daft Punk;Pharrell Williams,Random Access Memories,Daft Punk,1,8,369626,false,80,USQX91300108,Columbia"#;
+
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
+        let track = &result.tracks[0];
+
+        assert_eq!(result.profile, Some("Exportify".to_string()));
+        assert_eq!(
+            track.metadata.artist_names,
+            Some("Daft Punk;Pharrell Williams".to_string())
+        );
+        assert_eq!(
+            track.metadata.album_artist_names,
+            Some("Daft Punk".to_string())
+        );
+        assert_eq!(track.metadata.isrc, Some("USQX91300108".to_string()));
+        assert_eq!(track.metadata.track_number, Some("8".to_string()));
+        assert_eq!(track.metadata.disc_number, Some("1".to_string()));
+        assert_eq!(track.metadata.label, Some("Columbia".to_string()));
+        assert_eq!(track.metadata.explicit, Some("false".to_string()));
+        assert_eq!(track.metadata.popularity, Some("80".to_string()));
+        assert_eq!(
+            track.metadata.spotify_uri,
+            Some("spotify:track:2Foc5Q5nqNiosCNqttzHof".to_string())
+        );
+        assert_eq!(track.duration_seconds, Some(370));
+    }
+
+    #[test]
+    fn test_duration_seconds_formats() {
+        let duration = |raw: &str, unit: Option<DurationUnit>| {
+            CsvTrackMetadata {
+                duration: Some(raw.to_string()),
+                ..CsvTrackMetadata::default()
+            }
+            .duration_seconds(unit)
+        };
+
+        assert_eq!(
+            duration("369626", Some(DurationUnit::Milliseconds)),
+            Some(370)
+        );
+        // Short songs in milliseconds are not mistaken for seconds, nor long mixes the other way
+        assert_eq!(duration("9500", Some(DurationUnit::Milliseconds)), Some(10));
+        assert_eq!(duration("12000", Some(DurationUnit::Seconds)), Some(12000));
+        assert_eq!(duration("245", None), Some(245));
+        assert_eq!(duration("4:05", Some(DurationUnit::Clock)), Some(245));
+        assert_eq!(duration("4:05", None), Some(245));
+        assert_eq!(duration("1:02:03", None), Some(3723));
+        assert_eq!(duration("4:05", Some(DurationUnit::Seconds)), None);
+        assert_eq!(duration("n/a", None), None);
+        assert_eq!(CsvTrackMetadata::default().duration_seconds(None), None);
+    }
+
+    #[test]
+    fn test_parse_csv_content_reports_detected_profile() {
+        let csv_content = r#"Track name,Artist name,Album,Playlist name
+Hey Jude,The Beatles,Abbey Road,Favourites"#;
+
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
+
+        assert_eq!(result.profile, Some("TuneMyMusic".to_string()));
+        assert_eq!(result.tracks[0].search_query, "The Beatles - Hey Jude");
+    }
+
+    #[test]
+    fn test_parse_csv_content_album_artist_does_not_shadow_artist() {
+        let csv_content = r#"Album Artist Name(s),Track Name,Artist Name(s)
+Various Artists,Hey Jude,The Beatles"#;
+
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
+
+        assert_eq!(
+            result.tracks[0].metadata.artist_names,
+            Some("The Beatles".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_content_with_named_profile() {
+        let csv_content = r#"Name,Artist,Album,Genre,Year
+Hey Jude,The Beatles,Abbey Road,Rock,1968"#;
+
+        let result = parse_csv_content(
+            csv_content.as_bytes(),
+            &builtin_csv_profiles(),
+            Some("apple music"),
+        )
+        .unwrap();
+
+        assert_eq!(result.profile, Some("Apple Music".to_string()));
+        assert_eq!(
+            result.tracks[0].metadata.album_release_date,
+            Some("1968".to_string())
+        );
+
+        let err = parse_csv_content(
+            csv_content.as_bytes(),
+            &builtin_csv_profiles(),
+            Some("Nope"),
+        )
+        .unwrap_err();
+        assert!(err.contains("Unknown CSV profile"));
+    }
+
+    #[test]
+    fn test_parse_csv_content_semicolon_windows_1252() {
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(
+            "Artist Name(s);Track Name;Album Name\r\nBeyoncé;Crazy in Love;Dangerously in Love\r\n",
+        );
+
+        let result = parse_csv_content(&bytes, &builtin_csv_profiles(), None).unwrap();
+
+        let format = result.format.unwrap();
+        assert_eq!(format.encoding, "windows-1252");
+        assert_eq!(format.delimiter, ';');
+        assert_eq!(result.tracks[0].search_query, "Beyoncé - Crazy in Love");
+    }
+
+    #[test]
+    fn test_parse_csv_content_utf16_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "Artist Name(s)\tTrack Name\nSigur Rós\tHoppípolla\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        let result = parse_csv_content(&bytes, &builtin_csv_profiles(), None).unwrap();
+
+        let format = result.format.as_ref().unwrap();
+        assert_eq!(format.encoding, "UTF-16LE");
+        assert!(format.bom);
+        assert_eq!(result.tracks[0].search_query, "Sigur Rós - Hoppípolla");
+    }
+
+    #[test]
+    fn test_parse_csv_content_utf8_bom_does_not_leak_into_header() {
+        let csv_content = "\u{feff}Artist Name(s),Track Name\nThe Beatles,Hey Jude";
+
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
+
+        assert!(result.format.as_ref().unwrap().bom);
+        assert_eq!(
+            result.tracks[0].metadata.artist_names,
+            Some("The Beatles".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_content_unrecognised_columns() {
+        let csv_content = r#"Foo,Bar
+1,2"#;
+
+        assert!(parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_content_falls_back_to_generic_profile() {
+        let csv_content = r#"Song Title,Performer,Rating
+Hey Jude,The Beatles,5"#;
+
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
+
+        assert_eq!(result.profile, Some("Generic".to_string()));
+        assert_eq!(result.tracks[0].search_query, "The Beatles - Hey Jude");
     }
 
     #[test]
@@ -344,7 +593,8 @@ The Beatles,Hey Jude,Abbey Road,rock,1968,140"#;
 Artist One & Artist Two,Great Song,Great Album
 Artist One,Solo Song,Great Album"#;
 
-        let result = parse_csv_content(csv_content).unwrap();
+        let result =
+            parse_csv_content(csv_content.as_bytes(), &builtin_csv_profiles(), None).unwrap();
 
         assert_eq!(result.success_count, 2);
         assert_eq!(
@@ -0,0 +1,487 @@
+use serde::{Deserialize, Serialize};
+
+use crate::csv_parser::normalize_header;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvField {
+    ArtistNames,
+    TrackName,
+    AlbumName,
+    ArtistGenres,
+    AlbumReleaseDate,
+    BpmTempo,
+    Isrc,
+    Duration,
+    TrackNumber,
+    DiscNumber,
+    AlbumArtistNames,
+    Label,
+    Explicit,
+    Popularity,
+    SpotifyUri,
+}
+
+/// How a duration column writes its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationUnit {
+    Seconds,
+    Milliseconds,
+    /// "4:05" or "1:02:03".
+    Clock,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvColumnMapping {
+    pub field: CsvField,
+    /// Source column names to look for, in order of preference.
+    pub columns: Vec<String>,
+    /// Only for durations. Without one, clock times and plain seconds are both read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<DurationUnit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvProfile {
+    pub name: String,
+    pub mappings: Vec<CsvColumnMapping>,
+}
+
+impl CsvProfile {
+    pub fn duration_unit(&self) -> Option<DurationUnit> {
+        self.mappings
+            .iter()
+            .find(|m| m.field == CsvField::Duration)
+            .and_then(|m| m.unit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnMatch {
+    pub field: CsvField,
+    pub column: String,
+    pub index: usize,
+    pub exact: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvProfileDetection {
+    pub profile: String,
+    pub columns: Vec<ColumnMatch>,
+}
+
+fn mapping(field: CsvField, columns: &[&str]) -> CsvColumnMapping {
+    CsvColumnMapping {
+        field,
+        columns: columns.iter().map(|c| c.to_string()).collect(),
+        unit: None,
+    }
+}
+
+fn duration_mapping(columns: &[&str], unit: DurationUnit) -> CsvColumnMapping {
+    CsvColumnMapping {
+        unit: Some(unit),
+        ..mapping(CsvField::Duration, columns)
+    }
+}
+
+pub fn builtin_csv_profiles() -> Vec<CsvProfile> {
+    vec![
+        CsvProfile {
+            name: "Exportify".to_string(),
+            mappings: vec![
+                mapping(CsvField::ArtistNames, &["Artist Name(s)"]),
+                mapping(CsvField::TrackName, &["Track Name"]),
+                mapping(CsvField::AlbumName, &["Album Name"]),
+                mapping(CsvField::ArtistGenres, &["Artist Genres", "Genres"]),
+                mapping(
+                    CsvField::AlbumReleaseDate,
+                    &["Album Release Date", "Release Date"],
+                ),
+                mapping(CsvField::BpmTempo, &["BPM/Tempo", "Tempo"]),
+                mapping(CsvField::Isrc, &["ISRC"]),
+                duration_mapping(
+                    &["Track Duration (ms)", "Duration (ms)"],
+                    DurationUnit::Milliseconds,
+                ),
+                mapping(CsvField::TrackNumber, &["Track Number"]),
+                mapping(CsvField::DiscNumber, &["Disc Number"]),
+                mapping(CsvField::AlbumArtistNames, &["Album Artist Name(s)"]),
+                mapping(CsvField::Label, &["Record Label", "Label"]),
+                mapping(CsvField::Explicit, &["Explicit"]),
+                mapping(CsvField::Popularity, &["Popularity"]),
+                mapping(CsvField::SpotifyUri, &["Track URI"]),
+            ],
+        },
+        CsvProfile {
+            name: "TuneMyMusic".to_string(),
+            mappings: vec![
+                mapping(CsvField::ArtistNames, &["Artist name"]),
+                mapping(CsvField::TrackName, &["Track name"]),
+                mapping(CsvField::AlbumName, &["Album"]),
+                mapping(CsvField::Isrc, &["ISRC"]),
+            ],
+        },
+        CsvProfile {
+            name: "Soundiiz".to_string(),
+            mappings: vec![
+                mapping(CsvField::ArtistNames, &["Artist"]),
+                mapping(CsvField::TrackName, &["Title"]),
+                mapping(CsvField::AlbumName, &["Album"]),
+                mapping(CsvField::AlbumReleaseDate, &["Release date"]),
+                mapping(CsvField::Isrc, &["ISRC"]),
+            ],
+        },
+        CsvProfile {
+            name: "Apple Music".to_string(),
+            mappings: vec![
+                mapping(CsvField::ArtistNames, &["Artist"]),
+                mapping(CsvField::TrackName, &["Name"]),
+                mapping(CsvField::AlbumName, &["Album"]),
+                mapping(CsvField::ArtistGenres, &["Genre"]),
+                mapping(CsvField::AlbumReleaseDate, &["Year"]),
+                mapping(CsvField::BpmTempo, &["BPM"]),
+                duration_mapping(&["Time"], DurationUnit::Seconds),
+                mapping(CsvField::TrackNumber, &["Track Number"]),
+                mapping(CsvField::DiscNumber, &["Disc Number"]),
+                mapping(CsvField::AlbumArtistNames, &["Album Artist"]),
+            ],
+        },
+    ]
+}
+
+/// Used when no profile recognises a file's columns, guessing from common column names.
+pub fn generic_csv_profile() -> CsvProfile {
+    CsvProfile {
+        name: "Generic".to_string(),
+        mappings: vec![
+            mapping(
+                CsvField::ArtistNames,
+                &[
+                    "Artist Name(s)",
+                    "Artist",
+                    "Artists",
+                    "Performer",
+                    "Creator",
+                ],
+            ),
+            mapping(
+                CsvField::TrackName,
+                &["Track Name", "Track", "Title", "Song", "Name"],
+            ),
+            mapping(CsvField::AlbumName, &["Album Name", "Album"]),
+            mapping(CsvField::ArtistGenres, &["Genre", "Genres"]),
+            mapping(CsvField::AlbumReleaseDate, &["Release Date", "Year"]),
+            mapping(CsvField::BpmTempo, &["BPM", "Tempo"]),
+            mapping(CsvField::Isrc, &["ISRC"]),
+            mapping(CsvField::Duration, &["Duration"]),
+        ],
+    }
+}
+
+/// The built-in profiles followed by the user's own. A user profile with a built-in's name
+/// replaces it.
+pub fn merge_csv_profiles(custom: &[CsvProfile]) -> Vec<CsvProfile> {
+    let mut profiles: Vec<CsvProfile> = builtin_csv_profiles()
+        .into_iter()
+        .map(|builtin| {
+            custom
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(&builtin.name))
+                .cloned()
+                .unwrap_or(builtin)
+        })
+        .collect();
+    for profile in custom {
+        if !profiles
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(&profile.name))
+        {
+            profiles.push(profile.clone());
+        }
+    }
+    profiles
+}
+
+pub fn validate_csv_profiles(profiles: &[CsvProfile]) -> Result<(), String> {
+    for (index, profile) in profiles.iter().enumerate() {
+        if profile.name.trim().is_empty() {
+            return Err(format!("CSV profile {} has no name", index + 1));
+        }
+        if profiles[..index]
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(&profile.name))
+        {
+            return Err(format!("Duplicate CSV profile name: {}", profile.name));
+        }
+        let maps_track = profile.mappings.iter().any(|m| {
+            matches!(m.field, CsvField::ArtistNames | CsvField::TrackName)
+                && m.columns.iter().any(|c| !c.trim().is_empty())
+        });
+        if !maps_track {
+            return Err(format!(
+                "CSV profile '{}' must map an artist or track name column",
+                profile.name
+            ));
+        }
+        if let Some(m) = profile
+            .mappings
+            .iter()
+            .find(|m| m.unit.is_some() && m.field != CsvField::Duration)
+        {
+            return Err(format!(
+                "CSV profile '{}' gives a unit to {:?}, but only durations have one",
+                profile.name, m.field
+            ));
+        }
+    }
+    Ok(())
+}
+
+// "track" matches "track_name" and vice versa, but "artist" never matches "album_artist_names"
+fn is_token_prefix(value: &str, prefix: &str) -> bool {
+    value
+        .strip_prefix(prefix)
+        .map(|rest| rest.is_empty() || rest.starts_with('_'))
+        .unwrap_or(false)
+}
+
+fn is_fuzzy_match(header: &str, column: &str) -> bool {
+    !header.is_empty()
+        && !column.is_empty()
+        && (is_token_prefix(header, column) || is_token_prefix(column, header))
+}
+
+/// Maps profile fields to header indexes. Exact matches for every field are claimed
+/// before any fuzzy match so a loose match can never steal another field's column.
+pub fn match_columns(profile: &CsvProfile, headers: &[String]) -> Vec<ColumnMatch> {
+    let normalized: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+    let mut claimed = vec![false; headers.len()];
+    let mut matches: Vec<ColumnMatch> = Vec::new();
+
+    for exact in [true, false] {
+        for mapping in &profile.mappings {
+            if matches.iter().any(|m| m.field == mapping.field) {
+                continue;
+            }
+
+            let found = mapping.columns.iter().find_map(|column| {
+                let target = normalize_header(column);
+                (0..headers.len()).find(|&index| {
+                    !claimed[index]
+                        && if exact {
+                            normalized[index] == target
+                        } else {
+                            is_fuzzy_match(&normalized[index], &target)
+                        }
+                })
+            });
+
+            if let Some(index) = found {
+                claimed[index] = true;
+                matches.push(ColumnMatch {
+                    field: mapping.field,
+                    column: headers[index].clone(),
+                    index,
+                    exact,
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+pub fn detect_profile(profiles: &[CsvProfile], headers: &[String]) -> Option<CsvProfileDetection> {
+    let mut best: Option<(usize, usize, CsvProfileDetection)> = None;
+
+    for profile in profiles {
+        let columns = match_columns(profile, headers);
+        let identifies_track = columns
+            .iter()
+            .any(|c| matches!(c.field, CsvField::ArtistNames | CsvField::TrackName));
+        if !identifies_track {
+            continue;
+        }
+
+        let exact_count = columns.iter().filter(|c| c.exact).count();
+        let total_count = columns.len();
+        let is_better = best
+            .as_ref()
+            .map(|(exact, total, _)| (exact_count, total_count) > (*exact, *total))
+            .unwrap_or(true);
+
+        if is_better {
+            best = Some((
+                exact_count,
+                total_count,
+                CsvProfileDetection {
+                    profile: profile.name.clone(),
+                    columns,
+                },
+            ));
+        }
+    }
+
+    best.map(|(_, _, detection)| detection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn profile(name: &str) -> CsvProfile {
+        builtin_csv_profiles()
+            .into_iter()
+            .find(|p| p.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_match_columns_exact_beats_fuzzy() {
+        let headers = headers(&["Album Artist Name(s)", "Track Name", "Artist Name(s)"]);
+        let columns = match_columns(&profile("Exportify"), &headers);
+
+        let artist = columns
+            .iter()
+            .find(|c| c.field == CsvField::ArtistNames)
+            .unwrap();
+        assert_eq!(artist.index, 2);
+        assert!(artist.exact);
+    }
+
+    #[test]
+    fn test_match_columns_fuzzy_does_not_cross_words() {
+        let profile = CsvProfile {
+            name: "Custom".to_string(),
+            mappings: vec![mapping(CsvField::ArtistNames, &["Artist"])],
+        };
+        let columns = match_columns(&profile, &headers(&["Album Artist Name(s)", "Track"]));
+        assert!(columns.is_empty());
+    }
+
+    #[test]
+    fn test_match_columns_fuzzy_prefix() {
+        let headers = headers(&["Artist", "Track", "Album"]);
+        let columns = match_columns(&profile("Exportify"), &headers);
+
+        assert_eq!(columns.len(), 3);
+        assert!(columns.iter().all(|c| !c.exact));
+    }
+
+    #[test]
+    fn test_match_columns_does_not_reuse_a_column() {
+        let profile = CsvProfile {
+            name: "Custom".to_string(),
+            mappings: vec![
+                mapping(CsvField::TrackName, &["Name"]),
+                mapping(CsvField::AlbumName, &["Name"]),
+            ],
+        };
+        let columns = match_columns(&profile, &headers(&["Name"]));
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].field, CsvField::TrackName);
+    }
+
+    #[test]
+    fn test_detect_profile_builtin_exports() {
+        let cases = [
+            (
+                vec![
+                    "Track URI",
+                    "Track Name",
+                    "Artist Name(s)",
+                    "Album Name",
+                    "Album Release Date",
+                ],
+                "Exportify",
+            ),
+            (
+                vec![
+                    "Track name",
+                    "Artist name",
+                    "Album",
+                    "Playlist name",
+                    "Type",
+                    "ISRC",
+                ],
+                "TuneMyMusic",
+            ),
+            (vec!["Title", "Artist", "Album", "ISRC"], "Soundiiz"),
+            (
+                vec![
+                    "Name", "Artist", "Composer", "Album", "Genre", "Time", "Year",
+                ],
+                "Apple Music",
+            ),
+        ];
+
+        for (names, expected) in cases {
+            let detection = detect_profile(&builtin_csv_profiles(), &headers(&names)).unwrap();
+            assert_eq!(detection.profile, expected, "{:?}", names);
+        }
+    }
+
+    #[test]
+    fn test_detect_profile_unknown_headers() {
+        let detection = detect_profile(&builtin_csv_profiles(), &headers(&["Foo", "Bar"]));
+        assert!(detection.is_none());
+    }
+
+    #[test]
+    fn test_generic_profile_guesses_common_columns() {
+        let headers = headers(&["Song Title", "Artist", "Album", "Year"]);
+        let columns = match_columns(&generic_csv_profile(), &headers);
+
+        let field_of = |index: usize| columns.iter().find(|c| c.index == index).map(|c| c.field);
+        assert_eq!(field_of(0), Some(CsvField::TrackName));
+        assert_eq!(field_of(1), Some(CsvField::ArtistNames));
+        assert_eq!(field_of(2), Some(CsvField::AlbumName));
+        assert_eq!(field_of(3), Some(CsvField::AlbumReleaseDate));
+    }
+
+    #[test]
+    fn test_merge_csv_profiles() {
+        let custom = vec![
+            CsvProfile {
+                name: "exportify".to_string(),
+                mappings: vec![mapping(CsvField::TrackName, &["Song"])],
+            },
+            CsvProfile {
+                name: "Mine".to_string(),
+                mappings: vec![mapping(CsvField::TrackName, &["Song"])],
+            },
+        ];
+
+        let merged = merge_csv_profiles(&custom);
+
+        assert_eq!(merged.len(), builtin_csv_profiles().len() + 1);
+        assert_eq!(merged[0], custom[0]);
+        assert_eq!(merged.last(), Some(&custom[1]));
+        assert_eq!(merge_csv_profiles(&[]), builtin_csv_profiles());
+    }
+
+    #[test]
+    fn test_validate_csv_profiles() {
+        assert!(validate_csv_profiles(&builtin_csv_profiles()).is_ok());
+
+        let mut duplicated = builtin_csv_profiles();
+        duplicated.push(profile("Exportify"));
+        assert!(validate_csv_profiles(&duplicated).is_err());
+
+        let no_track = CsvProfile {
+            name: "Albums only".to_string(),
+            mappings: vec![mapping(CsvField::AlbumName, &["Album"])],
+        };
+        assert!(validate_csv_profiles(&[no_track]).is_err());
+
+        let mut timed_title = profile("Soundiiz");
+        timed_title.mappings[0].unit = Some(DurationUnit::Seconds);
+        assert!(validate_csv_profiles(&[timed_title]).is_err());
+    }
+}
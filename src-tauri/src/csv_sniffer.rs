@@ -0,0 +1,294 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+
+const DELIMITERS: &[u8] = b",;\t|";
+const QUOTES: &[u8] = b"\"'";
+const SNIFF_LINES: usize = 20;
+const SNIFF_BYTES: usize = 4096;
+
+/// How a CSV file was decoded, reported back so users can see why columns look the way they do.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvFormat {
+    pub encoding: String,
+    pub bom: bool,
+    pub delimiter: char,
+    pub quote: char,
+}
+
+// Excel writes UTF-16 without a BOM when saving as "Unicode Text"; ASCII text then has a NUL in every other byte
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+
+    if odd_nuls * 10 >= pairs * 4 && even_nuls * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_nuls * 10 >= pairs * 4 && odd_nuls * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+pub fn decode_csv_bytes(bytes: &[u8]) -> (String, &'static Encoding, bool) {
+    let (encoding, bom_length) = match Encoding::for_bom(bytes) {
+        Some((encoding, length)) => (encoding, length),
+        None => {
+            let encoding = sniff_utf16(bytes).unwrap_or_else(|| {
+                if std::str::from_utf8(bytes).is_ok() {
+                    UTF_8
+                } else {
+                    let mut detector = EncodingDetector::new();
+                    detector.feed(bytes, true);
+                    detector.guess(None, true)
+                }
+            });
+            (encoding, 0)
+        }
+    };
+
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+    (text.into_owned(), encoding, bom_length > 0)
+}
+
+fn count_outside_quotes(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+
+    for byte in line.bytes() {
+        if byte == quote {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+// A quote character is in use when fields consistently open and close with it, so an apostrophe
+// that merely starts a title like 'Round Midnight doesn't count
+fn sniff_quote(lines: &[&str]) -> u8 {
+    let quoted_fields = |quote: u8| {
+        let (opened, closed) = lines.iter().fold((0, 0), |(opened, closed), line| {
+            let bytes = line.trim_end_matches('\r').as_bytes();
+            let at_start = usize::from(bytes.first() == Some(&quote));
+            let at_end = usize::from(bytes.len() > 1 && bytes.last() == Some(&quote));
+            let after_delimiter = bytes
+                .windows(2)
+                .filter(|pair| DELIMITERS.contains(&pair[0]) && pair[1] == quote)
+                .count();
+            let before_delimiter = bytes
+                .windows(2)
+                .filter(|pair| pair[0] == quote && DELIMITERS.contains(&pair[1]))
+                .count();
+            (
+                opened + at_start + after_delimiter,
+                closed + at_end + before_delimiter,
+            )
+        });
+        if opened == closed {
+            opened
+        } else {
+            0
+        }
+    };
+
+    QUOTES
+        .iter()
+        .copied()
+        .max_by_key(|quote| (quoted_fields(*quote), *quote == b'"'))
+        .unwrap_or(b'"')
+}
+
+// The delimiter is the candidate whose count is non-zero on the header and consistent on the most rows
+fn sniff_delimiter(lines: &[&str], quote: u8) -> u8 {
+    let mut best = (b',', 0, 0);
+
+    for &delimiter in DELIMITERS {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_outside_quotes(line, delimiter, quote))
+            .collect();
+        let header_count = counts.first().copied().unwrap_or(0);
+        if header_count == 0 {
+            continue;
+        }
+
+        let consistent_rows = counts.iter().filter(|c| **c == header_count).count();
+        if (consistent_rows, header_count) > (best.1, best.2) {
+            best = (delimiter, consistent_rows, header_count);
+        }
+    }
+
+    best.0
+}
+
+/// Whether every line splits into the same number of fields, which tells a CSV whose columns no
+/// profile recognises apart from a plain list of tracks.
+pub fn has_delimited_rows(text: &str) -> bool {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    let Some(first) = lines.first() else {
+        return false;
+    };
+    if first.trim().to_lowercase().starts_with("sep=") {
+        return true;
+    }
+
+    let quote = sniff_quote(&lines);
+    let delimiter = sniff_delimiter(&lines, quote);
+    let header_count = count_outside_quotes(first, delimiter, quote);
+    header_count > 0
+        && lines
+            .iter()
+            .all(|line| count_outside_quotes(line, delimiter, quote) == header_count)
+}
+
+/// Decodes raw CSV bytes and works out the delimiter and quote character. Returns the text with any
+/// Excel `sep=` hint line removed.
+pub fn decode_and_sniff(bytes: &[u8]) -> (String, CsvFormat) {
+    let (mut text, encoding, bom) = decode_csv_bytes(bytes);
+
+    let mut delimiter_hint = None;
+    if let Some(first_line) = text.lines().next() {
+        let hint = first_line.trim();
+        if hint.len() == 5 && hint.to_lowercase().starts_with("sep=") && hint.is_ascii() {
+            delimiter_hint = Some(hint.as_bytes()[4]);
+            let skip = text.find('\n').map(|i| i + 1).unwrap_or(text.len());
+            text.drain(..skip);
+        }
+    }
+
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    let quote = sniff_quote(&lines);
+    let delimiter = delimiter_hint.unwrap_or_else(|| sniff_delimiter(&lines, quote));
+
+    let format = CsvFormat {
+        encoding: encoding.name().to_string(),
+        bom,
+        delimiter: delimiter as char,
+        quote: quote as char,
+    };
+    (text, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+        let mut bytes = if bom { vec![0xFF, 0xFE] } else { Vec::new() };
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_utf8_with_bom() {
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice("Track Name,Artist Name(s)\nHey Jude,The Beatles".as_bytes());
+
+        let (text, format) = decode_and_sniff(&bytes);
+
+        assert!(text.starts_with("Track Name"));
+        assert_eq!(format.encoding, "UTF-8");
+        assert!(format.bom);
+        assert_eq!(format.delimiter, ',');
+    }
+
+    #[test]
+    fn test_decode_utf16_with_and_without_bom() {
+        let csv = "Track Name\tArtist Name(s)\nCafé del Mar\tEnergy 52\n";
+
+        for bom in [true, false] {
+            let (text, format) = decode_and_sniff(&utf16le(csv, bom));
+            assert_eq!(text, csv);
+            assert_eq!(format.encoding, "UTF-16LE");
+            assert_eq!(format.bom, bom);
+            assert_eq!(format.delimiter, '\t');
+        }
+    }
+
+    #[test]
+    fn test_decode_windows_1252() {
+        let (encoded, _, _) =
+            encoding_rs::WINDOWS_1252.encode("Track Name;Artist Name(s)\nCafé Society;Beyoncé\n");
+
+        let (text, format) = decode_and_sniff(&encoded);
+
+        assert!(text.contains("Beyoncé"));
+        assert_eq!(format.encoding, "windows-1252");
+        assert!(!format.bom);
+        assert_eq!(format.delimiter, ';');
+    }
+
+    #[test]
+    fn test_sniff_delimiter_ignores_quoted_separators() {
+        let csv =
+            "Track Name;Artist Name(s)\n\"Hey, Jude\";The Beatles\n\"One, Two, Three\";Someone\n";
+        let (_, format) = decode_and_sniff(csv.as_bytes());
+        assert_eq!(format.delimiter, ';');
+        assert_eq!(format.quote, '"');
+    }
+
+    #[test]
+    fn test_sniff_pipe_delimiter_and_single_quotes() {
+        let csv = "Track Name|Artist Name(s)\n'Hey | Jude'|'The Beatles'\n";
+        let (_, format) = decode_and_sniff(csv.as_bytes());
+        assert_eq!(format.delimiter, '|');
+        assert_eq!(format.quote, '\'');
+    }
+
+    #[test]
+    fn test_sniff_keeps_double_quotes_for_titles_with_apostrophes() {
+        let csv = "Track Name,Artist Name(s)\n'Round Midnight,Thelonious Monk\nDon't Stop,Fleetwood Mac\n'Til I Collapse,Eminem\n";
+        let (_, format) = decode_and_sniff(csv.as_bytes());
+        assert_eq!(format.delimiter, ',');
+        assert_eq!(format.quote, '"');
+    }
+
+    #[test]
+    fn test_excel_separator_hint() {
+        let csv = "sep=;\nTrack Name;Artist Name(s)\nHey, Jude;The Beatles\n";
+        let (text, format) = decode_and_sniff(csv.as_bytes());
+        assert_eq!(format.delimiter, ';');
+        assert!(text.starts_with("Track Name"));
+    }
+
+    #[test]
+    fn test_has_delimited_rows() {
+        assert!(has_delimited_rows("Foo,Bar\n1,2\n\n3,4\n"));
+        assert!(has_delimited_rows("sep=;\nFoo\n"));
+        assert!(!has_delimited_rows(
+            "Daft Punk - One More Time\nEarth, Wind & Fire - September\n"
+        ));
+        assert!(!has_delimited_rows("Track Name\nHey Jude\n"));
+        assert!(!has_delimited_rows(""));
+    }
+
+    #[test]
+    fn test_single_column_defaults_to_comma() {
+        let (_, format) = decode_and_sniff(b"Track Name\nHey Jude\n");
+        assert_eq!(format.delimiter, ',');
+        assert_eq!(format.quote, '"');
+    }
+}
@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    VideoUnavailable,
+    AgeRestricted,
+    GeoBlocked,
+    RateLimited,
+    Network,
+    /// yt-dlp ran past its timeout. Not retried, as another attempt would likely take as long.
+    TimedOut,
+    YtdlpMissing,
+    FfmpegMissing,
+    DiskFull,
+    PermissionDenied,
+    FileNotFound,
+    InvalidInput,
+    /// yt-dlp or ffmpeg exited with an error that did not match a known pattern.
+    ToolFailed,
+    Internal,
+}
+
+impl ErrorKind {
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorKind::RateLimited | ErrorKind::Network)
+    }
+
+    pub fn hint(self) -> Option<&'static str> {
+        let hint = match self {
+            ErrorKind::VideoUnavailable => {
+                "The video was removed or made private. Try a different search result."
+            }
+            ErrorKind::AgeRestricted => {
+                "YouTube requires a signed-in account for this video. Try another upload of the same track."
+            }
+            ErrorKind::GeoBlocked => "The video is not available in your country.",
+            ErrorKind::RateLimited => {
+                "YouTube is throttling requests. Wait a few minutes before trying again."
+            }
+            ErrorKind::Network => "Check your internet connection and try again.",
+            ErrorKind::TimedOut => {
+                "Raise the yt-dlp timeout in settings for slow connections or long videos."
+            }
+            ErrorKind::YtdlpMissing => "Install yt-dlp from the setup screen.",
+            ErrorKind::FfmpegMissing => "Install FFmpeg from the setup screen.",
+            ErrorKind::DiskFull => "Free up disk space or choose another output folder.",
+            ErrorKind::PermissionDenied => "Choose an output folder you can write to.",
+            ErrorKind::FileNotFound
+            | ErrorKind::InvalidInput
+            | ErrorKind::ToolFailed
+            | ErrorKind::Internal => return None,
+        };
+        Some(hint)
+    }
+}
+
+/// The error every command returns to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub retryable: bool,
+    pub hint: Option<String>,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+// Checked in order, so the more specific yt-dlp messages come before the generic ones
+const STDERR_PATTERNS: &[(ErrorKind, &[&str])] = &[
+    (
+        ErrorKind::RateLimited,
+        &[
+            "http error 429",
+            "too many requests",
+            "confirm you're not a bot",
+            "confirm you’re not a bot",
+        ],
+    ),
+    (
+        ErrorKind::AgeRestricted,
+        &[
+            "confirm your age",
+            "age-restricted",
+            "inappropriate for some users",
+        ],
+    ),
+    (
+        ErrorKind::GeoBlocked,
+        &[
+            "available in your country",
+            "blocked it in your country",
+            "geo restriction",
+            "geo-restricted",
+        ],
+    ),
+    (
+        ErrorKind::VideoUnavailable,
+        &[
+            "video unavailable",
+            "private video",
+            "has been removed",
+            "no longer available",
+            "account associated with this video has been terminated",
+            "this live event will begin",
+        ],
+    ),
+    (
+        ErrorKind::FfmpegMissing,
+        &[
+            "ffmpeg not found",
+            "ffprobe and ffmpeg not found",
+            "ffmpeg is not installed",
+        ],
+    ),
+    (
+        ErrorKind::DiskFull,
+        &["no space left on device", "errno 28", "disk full"],
+    ),
+    (
+        ErrorKind::PermissionDenied,
+        &["permission denied", "errno 13"],
+    ),
+    (
+        ErrorKind::Network,
+        &[
+            "unable to download webpage",
+            "unable to download api page",
+            "timed out",
+            "connection reset",
+            "connection refused",
+            "temporary failure in name resolution",
+            "name or service not known",
+            "getaddrinfo failed",
+            "network is unreachable",
+            "http error 5",
+            "ssl:",
+        ],
+    ),
+    (
+        ErrorKind::InvalidInput,
+        &["unsupported url", "is not a valid url"],
+    ),
+];
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> AppError {
+        AppError {
+            kind,
+            message: message.into(),
+            retryable: kind.is_retryable(),
+            hint: kind.hint().map(|h| h.to_string()),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> AppError {
+        AppError::new(ErrorKind::Internal, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> AppError {
+        AppError::new(ErrorKind::InvalidInput, message)
+    }
+
+    /// Wraps an I/O error, keeping disk-full and permission problems distinguishable.
+    pub fn io(context: &str, error: io::Error) -> AppError {
+        let kind = match error.kind() {
+            io::ErrorKind::StorageFull => ErrorKind::DiskFull,
+            io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+            io::ErrorKind::NotFound => ErrorKind::FileNotFound,
+            _ if matches!(error.raw_os_error(), Some(28) | Some(112)) => ErrorKind::DiskFull,
+            _ => ErrorKind::Internal,
+        };
+        AppError::new(kind, format!("{}: {}", context, error))
+    }
+
+    /// An error from starting an external tool; a missing executable maps to `missing`.
+    pub fn spawn(tool: &str, missing: ErrorKind, error: io::Error) -> AppError {
+        if error.kind() == io::ErrorKind::NotFound {
+            AppError::new(missing, format!("{} not found: {}", tool, error))
+        } else {
+            AppError::io(&format!("Failed to run {}", tool), error)
+        }
+    }
+
+    /// An unsuccessful HTTP response. Only throttling and server errors are worth retrying; a
+    /// 4xx means the request itself is wrong.
+    pub fn http_status(context: &str, status: u16) -> AppError {
+        let kind = match status {
+            429 => ErrorKind::RateLimited,
+            500..=599 => ErrorKind::Network,
+            _ => ErrorKind::Internal,
+        };
+        AppError::new(kind, format!("{}: HTTP {}", context, status))
+    }
+
+    /// Classifies the stderr of a failed yt-dlp or ffmpeg run.
+    pub fn from_tool_stderr(tool: &str, stderr: &str) -> AppError {
+        let lowered = stderr.to_lowercase();
+        let kind = STDERR_PATTERNS
+            .iter()
+            .find(|(_, patterns)| patterns.iter().any(|p| lowered.contains(p)))
+            .map(|(kind, _)| *kind)
+            .unwrap_or(ErrorKind::ToolFailed);
+
+        AppError::new(kind, format!("{} failed: {}", tool, error_summary(stderr)))
+    }
+}
+
+// yt-dlp prints warnings and progress before the "ERROR:" lines that actually explain the failure
+fn error_summary(stderr: &str) -> String {
+    let errors: Vec<&str> = stderr
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("ERROR:"))
+        .map(str::trim)
+        .collect();
+
+    if errors.is_empty() {
+        stderr.trim().to_string()
+    } else {
+        errors.join("\n")
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_ytdlp_stderr() {
+        let cases = [
+            (
+                "ERROR: [youtube] abc: Video unavailable. This video has been removed by the uploader",
+                ErrorKind::VideoUnavailable,
+            ),
+            (
+                "ERROR: [youtube] abc: Private video. Sign in if you've been granted access",
+                ErrorKind::VideoUnavailable,
+            ),
+            (
+                "ERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate for some users.",
+                ErrorKind::AgeRestricted,
+            ),
+            (
+                "ERROR: [youtube] abc: The uploader has not made this video available in your country",
+                ErrorKind::GeoBlocked,
+            ),
+            (
+                "ERROR: [youtube] abc: Video unavailable. The uploader has not made this video available in your country",
+                ErrorKind::GeoBlocked,
+            ),
+            (
+                "ERROR: Unable to download webpage: HTTP Error 429: Too Many Requests",
+                ErrorKind::RateLimited,
+            ),
+            (
+                "ERROR: [youtube] abc: Sign in to confirm you're not a bot",
+                ErrorKind::RateLimited,
+            ),
+            (
+                "ERROR: Unable to download webpage: <urlopen error [Errno -3] Temporary failure in name resolution>",
+                ErrorKind::Network,
+            ),
+            (
+                "ERROR: Postprocessing: ffprobe and ffmpeg not found. Please install or provide the path",
+                ErrorKind::FfmpegMissing,
+            ),
+            (
+                "ERROR: unable to write data: [Errno 28] No space left on device",
+                ErrorKind::DiskFull,
+            ),
+            (
+                "ERROR: unable to open for writing: [Errno 13] Permission denied: '/music/a.webm'",
+                ErrorKind::PermissionDenied,
+            ),
+            ("ERROR: Unsupported URL: https://example.com/", ErrorKind::InvalidInput),
+            ("ERROR: something new went wrong", ErrorKind::ToolFailed),
+        ];
+
+        for (stderr, kind) in cases {
+            assert_eq!(
+                AppError::from_tool_stderr("yt-dlp", stderr).kind,
+                kind,
+                "{}",
+                stderr
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_tool_stderr_keeps_error_lines_only() {
+        let stderr = "WARNING: [youtube] falling back\nERROR: [youtube] abc: Video unavailable\n";
+        let error = AppError::from_tool_stderr("yt-dlp", stderr);
+        assert_eq!(
+            error.message,
+            "yt-dlp failed: [youtube] abc: Video unavailable"
+        );
+        assert!(!error.retryable);
+        assert!(error.hint.is_some());
+    }
+
+    #[test]
+    fn test_retryable_kinds() {
+        assert!(AppError::new(ErrorKind::RateLimited, "slow down").retryable);
+        assert!(AppError::new(ErrorKind::Network, "offline").retryable);
+        assert!(!AppError::new(ErrorKind::VideoUnavailable, "gone").retryable);
+        assert!(!AppError::new(ErrorKind::TimedOut, "slow").retryable);
+        assert!(!AppError::internal("bug").retryable);
+    }
+
+    #[test]
+    fn test_http_status_kinds() {
+        let not_found = AppError::http_status("Failed to download FFmpeg", 404);
+        assert_eq!(not_found.kind, ErrorKind::Internal);
+        assert!(!not_found.retryable);
+        assert_eq!(not_found.message, "Failed to download FFmpeg: HTTP 404");
+
+        assert!(AppError::http_status("x", 503).retryable);
+        assert_eq!(AppError::http_status("x", 429).kind, ErrorKind::RateLimited);
+    }
+
+    #[test]
+    fn test_io_error_kinds() {
+        let full = io::Error::from_raw_os_error(28);
+        assert_eq!(AppError::io("write", full).kind, ErrorKind::DiskFull);
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            AppError::io("write", denied).kind,
+            ErrorKind::PermissionDenied
+        );
+
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(
+            AppError::spawn("yt-dlp", ErrorKind::YtdlpMissing, missing).kind,
+            ErrorKind::YtdlpMissing
+        );
+    }
+
+    #[test]
+    fn test_serializes_for_frontend() {
+        let json = serde_json::to_value(AppError::new(ErrorKind::GeoBlocked, "blocked")).unwrap();
+        assert_eq!(json["kind"], "geo_blocked");
+        assert_eq!(json["message"], "blocked");
+        assert_eq!(json["retryable"], false);
+        assert_eq!(json["hint"], "The video is not available in your country.");
+    }
+}
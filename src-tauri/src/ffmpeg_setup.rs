@@ -3,7 +3,8 @@ use std::fs;
 use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use tauri::Manager;
+
+use crate::error::{AppError, AppResult, ErrorKind};
 
 fn candidate_filenames() -> &'static [&'static str] {
     if cfg!(windows) {
@@ -52,7 +53,7 @@ fn is_executable(path: &Path) -> bool {
     }
 }
 
-fn ffmpeg_zip_url() -> Result<&'static str, String> {
+fn ffmpeg_zip_url() -> AppResult<&'static str> {
     if cfg!(target_os = "windows") && cfg!(target_arch = "x86_64") {
         return Ok(
             "https://github.com/ffbinaries/ffbinaries-prebuilt/releases/download/v6.1/ffmpeg-6.1-win-64.zip",
@@ -79,35 +80,37 @@ fn ffmpeg_zip_url() -> Result<&'static str, String> {
         );
     }
 
-    Err("Unsupported platform for automatic FFmpeg download".to_string())
+    Err(AppError::internal(
+        "Unsupported platform for automatic FFmpeg download",
+    ))
+}
+
+fn get_app_ffmpeg_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("ffmpeg").join("6.1").join(ffmpeg_filename())
 }
 
-fn get_app_ffmpeg_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    Ok(data_dir.join("ffmpeg").join("6.1").join(ffmpeg_filename()))
+fn not_runnable() -> AppError {
+    AppError::new(ErrorKind::FfmpegMissing, "ffmpeg found but is not runnable")
 }
 
-fn verify_ffmpeg_runnable(ffmpeg_path: &Path) -> Result<(), String> {
+fn verify_ffmpeg_runnable(ffmpeg_path: &Path) -> AppResult<()> {
     let status = Command::new(ffmpeg_path)
         .args(["-version"])
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
-        .map_err(|_| "ffmpeg found but is not runnable".to_string())?;
+        .map_err(|_| not_runnable())?;
 
     if status.success() {
         Ok(())
     } else {
-        Err("ffmpeg found but is not runnable".to_string())
+        Err(not_runnable())
     }
 }
 
-fn find_ffmpeg(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let local_ffmpeg = get_app_ffmpeg_path(app_handle)?;
+fn find_ffmpeg(data_dir: &Path) -> AppResult<PathBuf> {
+    let local_ffmpeg = get_app_ffmpeg_path(data_dir);
     if local_ffmpeg.is_file() && is_executable(&local_ffmpeg) {
         if verify_ffmpeg_runnable(&local_ffmpeg).is_ok() {
             return Ok(local_ffmpeg);
@@ -120,7 +123,7 @@ fn find_ffmpeg(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
         }
     }
 
-    Err("ffmpeg not found".to_string())
+    Err(AppError::new(ErrorKind::FfmpegMissing, "ffmpeg not found"))
 }
 
 fn zip_entry_is_ffmpeg(entry_name: &str) -> bool {
@@ -128,32 +131,32 @@ fn zip_entry_is_ffmpeg(entry_name: &str) -> bool {
     normalized.ends_with(&format!("/{}", ffmpeg_filename())) || normalized == ffmpeg_filename()
 }
 
-async fn download_ffmpeg_zip() -> Result<Vec<u8>, String> {
+async fn download_ffmpeg_zip() -> AppResult<Vec<u8>> {
     let url = ffmpeg_zip_url()?;
     let response = reqwest::get(url)
         .await
-        .map_err(|e| format!("Failed to download FFmpeg: {}", e))?;
+        .map_err(|e| AppError::new(ErrorKind::Network, format!("Failed to download FFmpeg: {}", e)))?;
     if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download FFmpeg: HTTP {}",
-            response.status()
+        return Err(AppError::http_status(
+            "Failed to download FFmpeg",
+            response.status().as_u16(),
         ));
     }
     response
         .bytes()
         .await
-        .map_err(|e| format!("Failed to read FFmpeg download: {}", e))
+        .map_err(|e| AppError::new(ErrorKind::Network, format!("Failed to read FFmpeg download: {}", e)))
         .map(|b| b.to_vec())
 }
 
-fn extract_ffmpeg_from_zip(zip_bytes: &[u8]) -> Result<Vec<u8>, String> {
+fn extract_ffmpeg_from_zip(zip_bytes: &[u8]) -> AppResult<Vec<u8>> {
     let cursor = Cursor::new(zip_bytes);
-    let mut archive = zip::ZipArchive::new(cursor).map_err(|e| format!("Invalid FFmpeg zip: {}", e))?;
+    let mut archive = zip::ZipArchive::new(cursor).map_err(|e| AppError::internal(format!("Invalid FFmpeg zip: {}", e)))?;
 
     for i in 0..archive.len() {
         let mut file = archive
             .by_index(i)
-            .map_err(|e| format!("Failed to read FFmpeg zip: {}", e))?;
+            .map_err(|e| AppError::internal(format!("Failed to read FFmpeg zip: {}", e)))?;
         if !file.is_file() {
             continue;
         }
@@ -163,45 +166,43 @@ fn extract_ffmpeg_from_zip(zip_bytes: &[u8]) -> Result<Vec<u8>, String> {
         }
         let mut buf = Vec::new();
         file.read_to_end(&mut buf)
-            .map_err(|e| format!("Failed to extract FFmpeg: {}", e))?;
+            .map_err(|e| AppError::io("Failed to extract FFmpeg", e))?;
         return Ok(buf);
     }
 
-    Err("FFmpeg binary not found inside zip".to_string())
+    Err(AppError::internal("FFmpeg binary not found inside zip"))
 }
 
-fn ensure_parent_dir(path: &Path) -> Result<(), String> {
+fn ensure_parent_dir(path: &Path) -> AppResult<()> {
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        fs::create_dir_all(parent).map_err(|e| AppError::io("Failed to create directory", e))?;
     }
     Ok(())
 }
 
-fn write_executable(path: &Path, bytes: &[u8]) -> Result<(), String> {
+fn write_executable(path: &Path, bytes: &[u8]) -> AppResult<()> {
     ensure_parent_dir(path)?;
-    fs::write(path, bytes).map_err(|e| format!("Failed to write FFmpeg binary: {}", e))?;
+    fs::write(path, bytes).map_err(|e| AppError::io("Failed to write FFmpeg binary", e))?;
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
         fs::set_permissions(path, fs::Permissions::from_mode(0o755))
-            .map_err(|e| format!("Failed to set FFmpeg permissions: {}", e))?;
+            .map_err(|e| AppError::io("Failed to set FFmpeg permissions", e))?;
     }
     Ok(())
 }
 
-#[tauri::command]
-pub fn check_ffmpeg(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let ffmpeg_path = find_ffmpeg(&app_handle)?;
+pub fn check_ffmpeg(data_dir: &Path) -> AppResult<String> {
+    let ffmpeg_path = find_ffmpeg(data_dir)?;
     Ok(ffmpeg_path.to_string_lossy().to_string())
 }
 
-#[tauri::command]
-pub async fn download_ffmpeg(app_handle: tauri::AppHandle) -> Result<String, String> {
-    if let Ok(existing) = find_ffmpeg(&app_handle) {
+pub async fn download_ffmpeg(data_dir: &Path) -> AppResult<String> {
+    if let Ok(existing) = find_ffmpeg(data_dir) {
         return Ok(existing.to_string_lossy().to_string());
     }
 
-    let app_ffmpeg_path = get_app_ffmpeg_path(&app_handle)?;
+    let app_ffmpeg_path = get_app_ffmpeg_path(data_dir);
 
     let zip_bytes = download_ffmpeg_zip().await?;
     let ffmpeg_bytes = extract_ffmpeg_from_zip(&zip_bytes)?;
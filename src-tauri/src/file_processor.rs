@@ -1,88 +1,111 @@
-use regex::Regex;
+#[cfg(any(feature = "gui", test))]
 use std::path::Path;
+#[cfg(any(feature = "gui", test))]
 use std::process::{Command, Stdio};
+use unicode_normalization::UnicodeNormalization;
 
-const BANNED_STRINGS: &[&str] = &[
-    "[Audio HD]",
-    "(Radio Mix)",
-    "(Official Video)",
-    "(lyrics)",
-    "(Radio Edit)",
-    "[High Quality]",
-    "(Official Music Video)",
-    "(Audio)",
-    "[Clean version]",
-    "[visualizer]",
-    "[Official]",
-    "[Lyric Video]",
-    "[Lyrics]",
-    "(Lyric Video)",
-    "(Explicit)",
-    "[Explicit]",
-    "(Clean)",
-    "[Live]",
-    "(Studio)",
-    "[Studio]",
-    "[Remastered]",
-    "[Remix]",
-    "(Remix)",
-    "[DJ Mix]",
-    "(DJ Mix)",
-    "[Acoustic]",
-    "(Acoustic)",
-    "[Instrumental]",
-    "(Instrumental)",
-    "[Extended]",
-    "(Extended)",
-    "[Edit]",
-    "(Edit)",
-    "[Version]",
-    "(Version)",
-    "[Mixed]",
-    "(Mixed)",
+#[cfg(any(feature = "gui", test))]
+use crate::error::{AppError, AppResult, ErrorKind};
+#[cfg(any(feature = "gui", test))]
+use crate::cleanup_rules::{apply_cleanup_rules, CleanupRule};
+
+// Leaves room for the extension and a conflict suffix within the common 255-byte limit
+pub const MAX_FILENAME_BYTES: usize = 240;
+
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
 ];
 
-pub fn clean_filename(original_name: &str) -> String {
-    let mut result = original_name.to_string();
+#[cfg(any(feature = "gui", test))]
+pub fn clean_filename_with_rules(original_name: &str, rules: &[CleanupRule]) -> String {
+    apply_cleanup_rules(original_name, rules).display_name()
+}
 
-    for banned in BANNED_STRINGS {
-        let pattern = format!("(?i)\\s*{}", regex::escape(banned));
-        if let Ok(regex) = Regex::new(&pattern) {
-            result = regex.replace_all(&result, "").to_string();
-        }
+fn substitute_illegal_char(c: char) -> Option<&'static str> {
+    match c {
+        '/' | '\\' | '|' => Some("-"),
+        ':' => Some(" -"),
+        '"' => Some("'"),
+        '<' | '>' | '?' | '*' => Some(""),
+        c if c.is_control() => Some(""),
+        _ => None,
     }
+}
 
-    let patterns = [r"\s+", r"^\s+|\s+$"];
+fn truncate_to_bytes(value: &str, max_bytes: usize) -> &str {
+    if value.len() <= max_bytes {
+        return value;
+    }
+    let mut end = max_bytes;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
 
-    for pattern in &patterns {
-        if let Ok(regex) = Regex::new(pattern) {
-            result = regex.replace_all(&result, " ").to_string();
+pub fn sanitize_filename(name: &str) -> String {
+    let normalized: String = name.nfc().collect();
+
+    let mut substituted = String::with_capacity(normalized.len());
+    for c in normalized.chars() {
+        match substitute_illegal_char(c) {
+            Some(replacement) => substituted.push_str(replacement),
+            None => substituted.push(c),
         }
     }
 
-    result.trim().to_string()
+    let collapsed = substituted.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trim_edges = |s: &str| {
+        s.trim_start_matches(['.', ' '])
+            .trim_end_matches(['.', ' '])
+            .to_string()
+    };
+
+    let mut result = trim_edges(truncate_to_bytes(&trim_edges(&collapsed), MAX_FILENAME_BYTES));
+
+    let base_name = result.split('.').next().unwrap_or("").trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(base_name))
+    {
+        result = format!("{}_{}", base_name, &result[base_name.len()..]);
+    }
+
+    if result.is_empty() {
+        "Untitled".to_string()
+    } else {
+        result
+    }
 }
 
-pub fn convert_to_mp3(input_path: &str, output_path: &str) -> Result<String, String> {
+#[cfg(any(feature = "gui", test))]
+pub fn convert_to_mp3(input_path: &str, output_path: &str) -> AppResult<String> {
     convert_to_mp3_with_ffmpeg("ffmpeg", input_path, output_path)
 }
 
+#[cfg(any(feature = "gui", test))]
 pub fn convert_to_mp3_with_ffmpeg(
     ffmpeg_path: &str,
     input_path: &str,
     output_path: &str,
-) -> Result<String, String> {
+) -> AppResult<String> {
     let input = Path::new(input_path);
     let output = Path::new(output_path);
 
     if !input.exists() {
-        return Err(format!("Input file does not exist: {}", input_path));
+        return Err(AppError::new(
+            ErrorKind::FileNotFound,
+            format!("Input file does not exist: {}", input_path),
+        ));
     }
 
-    let output_dir = output.parent().ok_or("Invalid output path")?;
+    let output_dir = output
+        .parent()
+        .ok_or_else(|| AppError::invalid_input("Invalid output path"))?;
     if !output_dir.exists() {
         std::fs::create_dir_all(output_dir)
-            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+            .map_err(|e| AppError::io("Failed to create output directory", e))?;
     }
 
     let child = Command::new(ffmpeg_path)
@@ -100,15 +123,15 @@ pub fn convert_to_mp3_with_ffmpeg(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .map_err(|e| format!("Failed to spawn ffmpeg: {}", e))?;
+        .map_err(|e| AppError::spawn("ffmpeg", ErrorKind::FfmpegMissing, e))?;
 
     let output = child
         .wait_with_output()
-        .map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+        .map_err(|e| AppError::io("Failed to wait for ffmpeg", e))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ffmpeg conversion failed: {}", stderr));
+        return Err(AppError::from_tool_stderr("ffmpeg conversion", &stderr));
     }
 
     Ok(output_path.to_string())
@@ -119,166 +142,116 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_clean_filename_removes_audio_hd() {
-        let result = clean_filename("Song Name [Audio HD]");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_radio_mix() {
-        let result = clean_filename("Song Name (Radio Mix)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_official_video() {
-        let result = clean_filename("Song Name (Official Video)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_lyrics() {
-        let result = clean_filename("Song Name (lyrics)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_radio_edit() {
-        let result = clean_filename("Song Name (Radio Edit)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_high_quality() {
-        let result = clean_filename("Song Name [High Quality]");
-        assert_eq!(result, "Song Name");
+    fn test_clean_filename_with_custom_rules() {
+        let rules = vec![CleanupRule {
+            pattern: "[HQ]".to_string(),
+            kind: crate::cleanup_rules::MatchKind::Literal,
+            action: crate::cleanup_rules::CleanupAction::Remove,
+            replacement: None,
+        }];
+        let result = clean_filename_with_rules("Song Name [HQ] (Official Video)", &rules);
+        assert_eq!(result, "Song Name (Official Video)");
     }
 
     #[test]
-    fn test_clean_filename_removes_official_music_video() {
-        let result = clean_filename("Song Name (Official Music Video)");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_preserves_safe_names() {
+        assert_eq!(sanitize_filename("Artist - Song (Remix)"), "Artist - Song (Remix)");
     }
 
     #[test]
-    fn test_clean_filename_removes_audio() {
-        let result = clean_filename("Song Name (Audio)");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_replaces_path_separators() {
+        assert_eq!(sanitize_filename("AC/DC - Back\\In Black"), "AC-DC - Back-In Black");
     }
 
     #[test]
-    fn test_clean_filename_removes_clean_version() {
-        let result = clean_filename("Song Name [Clean version]");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_replaces_colon() {
+        assert_eq!(sanitize_filename("Artist: Song"), "Artist - Song");
     }
 
     #[test]
-    fn test_clean_filename_removes_visualizer() {
-        let result = clean_filename("Song Name [visualizer]");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_removes_wildcards_and_quotes() {
+        assert_eq!(
+            sanitize_filename("Why? \"Because\" *yes* <ok> a|b"),
+            "Why 'Because' yes ok a-b"
+        );
     }
 
     #[test]
-    fn test_clean_filename_removes_multiple_patterns() {
-        let result = clean_filename("Song Name [Audio HD] (Official Video)");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_strips_control_characters() {
+        assert_eq!(sanitize_filename("Song\u{0}Name\t\n"), "SongName");
     }
 
     #[test]
-    fn test_clean_filename_handles_case_insensitive() {
-        let result = clean_filename("Song Name (LYRICS)");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_trims_trailing_dots_and_spaces() {
+        assert_eq!(sanitize_filename("Song Name... "), "Song Name");
+        assert_eq!(sanitize_filename("...hidden"), "hidden");
     }
 
     #[test]
-    fn test_clean_filename_handles_lowercase() {
-        let result = clean_filename("Song Name (lyrics)");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_windows_reserved_names() {
+        assert_eq!(sanitize_filename("CON"), "CON_");
+        assert_eq!(sanitize_filename("con"), "con_");
+        assert_eq!(sanitize_filename("LPT1.live"), "LPT1_.live");
+        assert_eq!(sanitize_filename("Console"), "Console");
     }
 
     #[test]
-    fn test_clean_filename_trims_whitespace() {
-        let result = clean_filename("   Song Name   ");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_normalizes_to_nfc() {
+        let decomposed = "Beyonce\u{301}";
+        let result = sanitize_filename(decomposed);
+        assert_eq!(result, "Beyonc\u{e9}");
     }
 
     #[test]
-    fn test_clean_filename_preserves_actual_text() {
-        let result = clean_filename("Song Name with Meaningful Text");
-        assert_eq!(result, "Song Name with Meaningful Text");
-    }
-
-    #[test]
-    fn test_clean_filename_handles_empty_string() {
-        let result = clean_filename("");
-        assert_eq!(result, "");
-    }
+    fn test_sanitize_filename_truncates_on_char_boundary() {
+        let long_name = "é".repeat(200);
+        let result = sanitize_filename(&long_name);
 
-    #[test]
-    fn test_clean_filename_removes_live() {
-        let result = clean_filename("Song Name [Live]");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_studio() {
-        let result = clean_filename("Song Name (Studio)");
-        assert_eq!(result, "Song Name");
+        assert!(result.len() <= MAX_FILENAME_BYTES);
+        assert!(result.chars().all(|c| c == 'é'));
+        assert_eq!(result.chars().count(), MAX_FILENAME_BYTES / 2);
     }
 
     #[test]
-    fn test_clean_filename_removes_remastered() {
-        let result = clean_filename("Song Name [Remastered]");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_remix() {
-        let result = clean_filename("Song Name (Remix)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_acoustic() {
-        let result = clean_filename("Song Name [Acoustic]");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_extended() {
-        let result = clean_filename("Song Name (Extended)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_complex_example() {
-        let result = clean_filename("Artist Name - Song Title (Official Video) [Audio HD]");
-        assert_eq!(result, "Artist Name - Song Title");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_explicit() {
-        let result = clean_filename("Song Name (Explicit)");
-        assert_eq!(result, "Song Name");
-    }
-
-    #[test]
-    fn test_clean_filename_removes_instrumental() {
-        let result = clean_filename("Song Name [Instrumental]");
-        assert_eq!(result, "Song Name");
+    fn test_sanitize_filename_empty_result() {
+        assert_eq!(sanitize_filename(""), "Untitled");
+        assert_eq!(sanitize_filename("???"), "Untitled");
     }
 
     #[test]
     fn test_convert_to_mp3_nonexistent_input_file() {
         let result = convert_to_mp3("/nonexistent/file.mp3", "/output/song.mp3");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Input file does not exist"));
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::FileNotFound);
+        assert!(error.message.contains("Input file does not exist"));
     }
 
     #[test]
     fn test_convert_to_mp3_empty_input_path() {
         let result = convert_to_mp3("", "/output/song.mp3");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Input file does not exist"));
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::FileNotFound);
+        assert!(error.message.contains("Input file does not exist"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_convert_to_mp3_with_fake_ffmpeg() {
+        let tools = crate::test_support::FakeTools::new("convert_to_mp3");
+        let input = tools.output_dir().join("input.webm");
+        let output = tools.output_dir().join("converted").join("song.mp3");
+        std::fs::create_dir_all(tools.output_dir()).unwrap();
+        std::fs::write(&input, b"webm audio").unwrap();
+
+        let result = convert_to_mp3_with_ffmpeg(
+            &tools.ffmpeg_path(),
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(result, output.to_str().unwrap());
+        assert_eq!(std::fs::read(&output).unwrap(), b"webm audio");
+        assert!(tools.ffmpeg_calls()[0].contains("-acodec libmp3lame"));
     }
 }
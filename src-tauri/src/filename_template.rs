@@ -0,0 +1,433 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::file_processor::sanitize_filename;
+use crate::metadata::TrackMetadata;
+use crate::youtube_client::VideoInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateField {
+    Title,
+    Version,
+    Artist,
+    Album,
+    AlbumArtist,
+    Year,
+    Genre,
+    Track,
+    Id,
+    Uploader,
+    VideoTitle,
+}
+
+impl TemplateField {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "title" => Some(TemplateField::Title),
+            "version" | "subtitle" => Some(TemplateField::Version),
+            "artist" => Some(TemplateField::Artist),
+            "album" => Some(TemplateField::Album),
+            "album_artist" | "albumartist" => Some(TemplateField::AlbumArtist),
+            "year" => Some(TemplateField::Year),
+            "genre" => Some(TemplateField::Genre),
+            "track" | "track_number" => Some(TemplateField::Track),
+            "id" | "video_id" => Some(TemplateField::Id),
+            "uploader" | "channel" => Some(TemplateField::Uploader),
+            "video_title" => Some(TemplateField::VideoTitle),
+            _ => None,
+        }
+    }
+
+    fn default_value(self) -> Option<&'static str> {
+        match self {
+            TemplateField::Title => Some("Unknown Title"),
+            TemplateField::Artist | TemplateField::AlbumArtist => Some("Unknown Artist"),
+            TemplateField::Album => Some("Unknown Album"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field {
+        fields: Vec<TemplateField>,
+        width: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilenameTemplate {
+    segments: Vec<Segment>,
+}
+
+fn parse_placeholder(body: &str) -> Result<Segment, String> {
+    let (names, width) = match body.split_once(':') {
+        Some((names, spec)) => {
+            let width = spec
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid width '{}' in placeholder '{{{}}}'", spec, body))?;
+            (names, Some(width))
+        }
+        None => (body, None),
+    };
+
+    let fields = names
+        .split('|')
+        .map(|name| {
+            TemplateField::from_name(name)
+                .ok_or_else(|| format!("Unknown template field '{}'", name.trim()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Segment::Field { fields, width })
+}
+
+impl FilenameTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err("Filename template is empty".to_string());
+        }
+        if template.starts_with('/') || template.starts_with('\\') {
+            return Err("Filename template must be a relative path".to_string());
+        }
+        if template
+            .split(['/', '\\'])
+            .any(|component| component.trim() == "..")
+        {
+            return Err("Filename template must not contain '..'".to_string());
+        }
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut body = String::new();
+                    let mut closed = false;
+                    for inner in chars.by_ref() {
+                        if inner == '}' {
+                            closed = true;
+                            break;
+                        }
+                        body.push(inner);
+                    }
+                    if !closed {
+                        return Err(format!("Unclosed placeholder '{{{}'", body));
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_placeholder(&body)?);
+                }
+                '}' => return Err("Unexpected '}' in filename template".to_string()),
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if !segments.iter().any(|s| matches!(s, Segment::Field { .. })) {
+            return Err("Filename template must contain at least one placeholder".to_string());
+        }
+
+        Ok(FilenameTemplate { segments })
+    }
+
+    pub fn render(&self, metadata: &TrackMetadata, video: Option<&VideoInfo>) -> PathBuf {
+        let mut rendered = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => rendered.push_str(text),
+                Segment::Field { fields, width } => {
+                    let value = fields
+                        .iter()
+                        .find_map(|field| field_value(*field, metadata, video))
+                        .or_else(|| fields.first().and_then(|f| f.default_value()).map(String::from))
+                        .unwrap_or_default();
+                    rendered.push_str(&escape_separators(&pad_number(&value, *width)));
+                }
+            }
+        }
+
+        rendered
+            .split(['/', '\\'])
+            .map(tidy_component)
+            .filter(|component| !component.is_empty())
+            .map(|component| sanitize_filename(&component))
+            .collect()
+    }
+
+    /// Like `render`, but uses `fallback` as the name when every placeholder came out empty.
+    pub fn render_file(
+        &self,
+        metadata: &TrackMetadata,
+        video: Option<&VideoInfo>,
+        fallback: &str,
+    ) -> PathBuf {
+        let rendered = self.render(metadata, video);
+        if rendered.as_os_str().is_empty() {
+            PathBuf::from(sanitize_filename(fallback))
+        } else {
+            rendered
+        }
+    }
+}
+
+fn non_empty(value: Option<&String>) -> Option<String> {
+    value.map(|v| v.trim()).filter(|v| !v.is_empty()).map(String::from)
+}
+
+fn field_value(
+    field: TemplateField,
+    metadata: &TrackMetadata,
+    video: Option<&VideoInfo>,
+) -> Option<String> {
+    match field {
+        TemplateField::Title => non_empty(metadata.title.as_ref())
+            .or_else(|| video.and_then(|v| non_empty(Some(&v.title)))),
+        TemplateField::Version => non_empty(metadata.subtitle.as_ref()),
+        TemplateField::Artist => non_empty(metadata.artist.as_ref()),
+        TemplateField::Album => non_empty(metadata.album.as_ref()),
+        TemplateField::AlbumArtist => non_empty(metadata.album_artist.as_ref())
+            .or_else(|| non_empty(metadata.artist.as_ref())),
+        TemplateField::Year => non_empty(metadata.year.as_ref())
+            .or_else(|| video.and_then(|v| non_empty(v.upload_date.as_ref())))
+            .map(|date| date.chars().take(4).collect()),
+        TemplateField::Genre => non_empty(metadata.genre.as_ref()),
+        TemplateField::Track => non_empty(metadata.track_number.as_ref())
+            .map(|track| track.split('/').next().unwrap_or("").trim().to_string())
+            .filter(|track| !track.is_empty()),
+        TemplateField::Id => video.and_then(|v| non_empty(Some(&v.id))),
+        TemplateField::Uploader => video.and_then(|v| non_empty(v.uploader.as_ref())),
+        TemplateField::VideoTitle => video.and_then(|v| non_empty(Some(&v.title))),
+    }
+}
+
+fn pad_number(value: &str, width: Option<usize>) -> String {
+    match (width, value.parse::<u64>()) {
+        (Some(width), Ok(number)) => format!("{:0width$}", number, width = width),
+        _ => value.to_string(),
+    }
+}
+
+fn escape_separators(value: &str) -> String {
+    value.replace(['/', '\\'], "-")
+}
+
+fn tidy_component(component: &str) -> String {
+    // Missing optional fields can leave dangling separators like " - Song" or "Song ()" behind
+    component
+        .replace("()", "")
+        .replace("[]", "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c == '-' || c == '_' || c.is_whitespace())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_metadata() -> TrackMetadata {
+        TrackMetadata {
+            title: Some("Hey Jude".to_string()),
+            artist: Some("The Beatles".to_string()),
+            album: Some("Hey Jude".to_string()),
+            year: Some("1968".to_string()),
+            track_number: Some("7/12".to_string()),
+            ..TrackMetadata::default()
+        }
+    }
+
+    fn sample_video() -> VideoInfo {
+        VideoInfo {
+            id: "A_MjCqQoLLA".to_string(),
+            title: "The Beatles - Hey Jude (Official Video)".to_string(),
+            url: "https://www.youtube.com/watch?v=A_MjCqQoLLA".to_string(),
+            thumbnail_url: None,
+            uploader: Some("The Beatles".to_string()),
+            duration_seconds: Some(431),
+            upload_date: Some("20150908".to_string()),
+            description: None,
+            source: None,
+            match_score: None,
+        }
+    }
+
+    fn render(template: &str, metadata: &TrackMetadata, video: Option<&VideoInfo>) -> String {
+        FilenameTemplate::parse(template)
+            .unwrap()
+            .render(metadata, video)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    #[test]
+    fn test_render_artist_title() {
+        let result = render("{artist} - {title}", &sample_metadata(), None);
+        assert_eq!(result, "The Beatles - Hey Jude");
+    }
+
+    #[test]
+    fn test_render_padded_track_number() {
+        let result = render("{track:02} {title}", &sample_metadata(), None);
+        assert_eq!(result, "07 Hey Jude");
+    }
+
+    #[test]
+    fn test_render_nested_directories() {
+        let result = render("{album_artist}/{album}/{title}", &sample_metadata(), None);
+        assert_eq!(result, "The Beatles/Hey Jude/Hey Jude");
+    }
+
+    #[test]
+    fn test_render_missing_artist_uses_default() {
+        let metadata = TrackMetadata {
+            title: Some("Song".to_string()),
+            ..TrackMetadata::default()
+        };
+        let result = render("{artist} - {title}", &metadata, None);
+        assert_eq!(result, "Unknown Artist - Song");
+    }
+
+    #[test]
+    fn test_render_explicit_fallback_chain() {
+        let metadata = TrackMetadata {
+            title: Some("Song".to_string()),
+            ..TrackMetadata::default()
+        };
+        let result = render("{artist|uploader} - {title}", &metadata, Some(&sample_video()));
+        assert_eq!(result, "The Beatles - Song");
+    }
+
+    #[test]
+    fn test_render_missing_track_drops_leading_space() {
+        let metadata = TrackMetadata {
+            title: Some("Song".to_string()),
+            ..TrackMetadata::default()
+        };
+        let result = render("{track:02} {title}", &metadata, None);
+        assert_eq!(result, "Song");
+    }
+
+    #[test]
+    fn test_render_title_falls_back_to_video_title() {
+        let result = render("{title}", &TrackMetadata::default(), Some(&sample_video()));
+        assert_eq!(result, "The Beatles - Hey Jude (Official Video)");
+    }
+
+    #[test]
+    fn test_render_year_from_upload_date() {
+        let result = render("{year} - {id}", &TrackMetadata::default(), Some(&sample_video()));
+        assert_eq!(result, "2015 - A_MjCqQoLLA");
+    }
+
+    #[test]
+    fn test_render_escapes_separators_in_values() {
+        let metadata = TrackMetadata {
+            title: Some("AC/DC Tribute".to_string()),
+            artist: Some("AC/DC".to_string()),
+            ..TrackMetadata::default()
+        };
+        let result = render("{artist}/{title}", &metadata, None);
+        assert_eq!(result, "AC-DC/AC-DC Tribute");
+    }
+
+    #[test]
+    fn test_render_sanitizes_illegal_characters() {
+        let metadata = TrackMetadata {
+            title: Some("What? Now: Live".to_string()),
+            artist: Some("CON".to_string()),
+            ..TrackMetadata::default()
+        };
+        let result = render("{artist}/{title}...", &metadata, None);
+        assert_eq!(result, "CON_/What Now - Live");
+    }
+
+    #[test]
+    fn test_render_version_in_parentheses() {
+        let metadata = TrackMetadata {
+            subtitle: Some("Live".to_string()),
+            ..sample_metadata()
+        };
+        assert_eq!(render("{title} ({version})", &metadata, None), "Hey Jude (Live)");
+        assert_eq!(render("{title} ({version})", &sample_metadata(), None), "Hey Jude");
+    }
+
+    #[test]
+    fn test_render_drops_empty_directory_components() {
+        let metadata = TrackMetadata {
+            title: Some("Song".to_string()),
+            ..TrackMetadata::default()
+        };
+        let result = render("{genre}/{title}", &metadata, None);
+        assert_eq!(result, "Song");
+    }
+
+    #[test]
+    fn test_render_file_falls_back_when_everything_is_empty() {
+        let template = FilenameTemplate::parse("{track} - {version}").unwrap();
+        let metadata = TrackMetadata::default();
+
+        assert_eq!(template.render(&metadata, None), PathBuf::new());
+        assert_eq!(
+            template.render_file(&metadata, None, "Hey Jude"),
+            PathBuf::from("Hey Jude")
+        );
+        assert_eq!(
+            template.render_file(&sample_metadata(), None, "Hey Jude"),
+            PathBuf::from("7")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_field() {
+        let result = FilenameTemplate::parse("{artist} - {bogus}");
+        assert!(result.unwrap_err().contains("bogus"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unclosed_placeholder() {
+        assert!(FilenameTemplate::parse("{artist - {title}").is_err());
+        assert!(FilenameTemplate::parse("{artist").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_stray_closing_brace() {
+        assert!(FilenameTemplate::parse("artist} - {title}").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_width() {
+        assert!(FilenameTemplate::parse("{track:xx} {title}").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_empty_and_literal_only() {
+        assert!(FilenameTemplate::parse("").is_err());
+        assert!(FilenameTemplate::parse("   ").is_err());
+        assert!(FilenameTemplate::parse("static name").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_escaping_paths() {
+        assert!(FilenameTemplate::parse("/{title}").is_err());
+        assert!(FilenameTemplate::parse("{artist}/../{title}").is_err());
+    }
+
+    #[test]
+    fn test_parse_accepts_field_aliases() {
+        assert!(FilenameTemplate::parse("{albumartist}/{track_number} {channel}").is_ok());
+    }
+}
@@ -1,25 +1,61 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tauri::{Emitter, Manager};
-use tauri_plugin_dialog::DialogExt;
-use std::path::Path;
-use std::fs;
 
+mod error;
 mod csv_parser;
+mod csv_profiles;
+mod csv_sniffer;
+mod playlist_import;
+mod batch_report;
 mod file_processor;
 mod youtube_client;
 mod metadata;
 mod ytdlp_setup;
 mod ffmpeg_setup;
-use crate::csv_parser::{parse_csv_content, validate_csv_headers, CsvImportResult, CsvTrackEntry};
-use crate::file_processor::{clean_filename, convert_to_mp3_with_ffmpeg};
-use crate::youtube_client::{download_stream, search_video, VideoInfo};
-use crate::metadata::{tag_mp3, TrackMetadata, parse_title_for_metadata};
-use crate::ytdlp_setup::{check_ytdlp, download_ytdlp, get_ytdlp_command};
-use crate::ffmpeg_setup::{check_ffmpeg, download_ffmpeg};
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+mod subtitles;
+mod filename_template;
+mod organizer;
+mod conflict;
+mod cleanup_rules;
+mod settings;
+mod title_parser;
+mod music_description;
+mod search;
+mod search_scoring;
+mod retry;
+mod rate_limit;
+mod ytdlp_command;
+mod ytdlp;
+mod pipeline;
+#[cfg(any(feature = "gui", test))]
+mod queue;
+#[cfg(any(feature = "gui", test))]
+mod http_server;
+#[cfg(any(feature = "gui", test))]
+mod watch_folder;
+#[cfg(test)]
+mod test_support;
+mod sources;
+mod link_resolver;
+mod cli;
+#[cfg(feature = "gui")]
+mod commands;
+use crate::batch_report::InputError;
+use crate::csv_parser::{CsvImportResult, CsvTrackEntry};
+use crate::playlist_import::import_track_list;
+use crate::youtube_client::VideoInfo;
+use crate::sources::{is_youtube_collection, SourceSite};
+use crate::link_resolver::{LinkResolver, StreamingLink};
+use crate::settings::AppSettings;
+use crate::conflict::ConflictResolution;
+use crate::metadata::TrackMetadata;
+use crate::error::{AppError, AppResult};
+
+pub use crate::cli::run_cli;
+#[cfg(feature = "gui")]
+pub use crate::commands::run;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AudioMode {
     Official,
@@ -35,10 +71,26 @@ pub enum InputType {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessedItem {
+    /// The input line this item came from; every track of a resolved link shares its line.
+    pub line_number: usize,
     pub input_type: InputType,
     pub original_input: String,
     pub processed_query: String,
     pub video_id: Option<String>,
+    pub source: Option<SourceSite>,
+    pub metadata_override: Option<TrackMetadata>,
+    pub duration_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessItemResult {
+    pub output_path: String,
+    pub conflict: ConflictResolution,
+    pub conflicting_path: Option<String>,
+    /// Download attempts across every candidate that was tried.
+    pub attempts: u32,
+    /// The video that was actually downloaded, which differs from the requested one after a fallback.
+    pub video_info: Option<VideoInfo>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -47,6 +99,7 @@ pub struct ProcessInputResult {
     pub total_count: usize,
     pub url_count: usize,
     pub search_count: usize,
+    pub errors: Vec<InputError>,
 }
 
 fn get_audio_mode_suffix(mode: &AudioMode) -> &'static str {
@@ -57,10 +110,6 @@ fn get_audio_mode_suffix(mode: &AudioMode) -> &'static str {
     }
 }
 
-fn is_youtube_url(text: &str) -> bool {
-    extract_video_id(text).is_some()
-}
-
 fn extract_video_id(url: &str) -> Option<String> {
     let patterns =
         [r"(?:youtube\.com/watch\?v=|youtu\.be/|youtube\.com/shorts/)([a-zA-Z0-9_-]{11})"];
@@ -77,239 +126,22 @@ fn extract_video_id(url: &str) -> Option<String> {
     None
 }
 
+// Downloads are saved as "Title [id]"; only the final bracket group is the id, whatever the site
+fn strip_id_suffix(file_stem: &str) -> String {
+    let regex = Regex::new(r"\s*\[[^\[\]]+\]$").expect("valid id suffix regex");
+    regex.replace(file_stem, "").to_string()
+}
+
 fn construct_search_query(line: &str, mode: &AudioMode) -> String {
     let suffix = get_audio_mode_suffix(mode);
     format!("{} {}", line.trim(), suffix)
 }
 
-#[tauri::command]
-async fn set_download_path(window: tauri::Window) -> Result<Option<String>, String> {
-    let path = window
-        .dialog()
-        .file()
-        .set_title("Select Download Path")
-        .blocking_pick_folder();
-
-    Ok(path.map(|fp| fp.to_string()))
-}
-
-#[tauri::command]
-fn open_folder(path: String) -> Result<(), String> {
-    tauri_plugin_opener::reveal_item_in_dir(&path).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-async fn search_video_command(query: String, app_handle: tauri::AppHandle) -> Result<Option<VideoInfo>, String> {
-    let ytdlp_path = get_ytdlp_command(&app_handle)?;
-    search_video(&ytdlp_path, &query)
-}
-
-#[tauri::command]
-async fn download_video_command(
-    video_id: String,
-    output_path: String,
-    window: tauri::Window,
-) -> Result<String, String> {
-    let ytdlp_path = get_ytdlp_command(&window.app_handle())?;
-    let ffmpeg_path = match check_ffmpeg(window.app_handle().clone()) {
-        Ok(p) => p,
-        Err(_) => download_ffmpeg(window.app_handle().clone()).await.map_err(|e| {
-            format!(
-                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
-                e
-            )
-        })?,
-    };
-    let window_clone = window.clone();
-    download_stream(
-        &ytdlp_path,
-        &video_id,
-        &output_path,
-        Some(&ffmpeg_path),
-        move |progress, message| {
-        let _ = window_clone.emit(
-            "download-progress",
-            serde_json::json!({
-                "progress": progress,
-                "message": message
-            }),
-        );
-    },
-    )
-}
-
-#[tauri::command]
-async fn process_item(
-    video_id: String,
-    output_path: String,
-    metadata_override: Option<TrackMetadata>,
-    window: tauri::Window,
-) -> Result<String, String> {
-    let ytdlp_path = get_ytdlp_command(&window.app_handle())?;
-    let ffmpeg_path = match check_ffmpeg(window.app_handle().clone()) {
-        Ok(p) => p,
-        Err(_) => download_ffmpeg(window.app_handle().clone()).await.map_err(|e| {
-            format!(
-                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
-                e
-            )
-        })?,
-    };
-    let window_clone = window.clone();
-
-    // 1. Download
-    let downloaded_path = download_stream(
-        &ytdlp_path,
-        &video_id,
-        &output_path,
-        Some(&ffmpeg_path),
-        move |progress, message| {
-        let _ = window_clone.emit(
-            "download-progress",
-            serde_json::json!({
-                "progress": progress,
-                "message": message
-            }),
-        );
-    },
-    )?;
-
-    // 2. Clean Filename
-    let path = Path::new(&downloaded_path);
-    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
-    
-    // Remove the video ID from the filename if present (e.g. "Title [id]")
-    // Escape the video_id for regex
-    let escaped_id = regex::escape(&video_id);
-    let id_pattern = format!(r"\s*\[{}\]", escaped_id);
-    let regex = Regex::new(&id_pattern).map_err(|e| e.to_string())?;
-    let stem_without_id = regex.replace(file_stem, "");
-    
-    let cleaned_stem = clean_filename(&stem_without_id);
-    let new_filename = format!("{}.mp3", cleaned_stem);
-    let new_path = path.parent().ok_or("Invalid path")?.join(&new_filename);
-    
-    // Rename/Move if different
-    let final_path_str = if new_path != path {
-        fs::rename(&path, &new_path).map_err(|e| format!("Failed to rename file: {}", e))?;
-        new_path.to_str().ok_or("Invalid path")?.to_string()
-    } else {
-        downloaded_path
-    };
-
-    // 3. Tagging
-    let mut final_metadata = metadata_override.unwrap_or_default();
-    
-    // Infer metadata if not provided
-    if final_metadata.title.is_none() {
-         let inferred = parse_title_for_metadata(&cleaned_stem);
-         final_metadata.title = inferred.title;
-         
-         if final_metadata.artist.is_none() {
-             final_metadata.artist = inferred.artist;
-         }
-    }
-
-    tag_mp3(&final_path_str, final_metadata)?;
-
-    Ok(final_path_str)
-}
-
-#[tauri::command]
-fn clean_filename_command(original_name: String) -> String {
-    clean_filename(&original_name)
-}
-
-#[tauri::command]
-async fn convert_to_mp3_command(
-    input_path: String,
-    output_path: String,
-    window: tauri::Window,
-) -> Result<String, String> {
-    let window_clone = window.clone();
-    let ffmpeg_path = match check_ffmpeg(window.app_handle().clone()) {
-        Ok(p) => p,
-        Err(_) => download_ffmpeg(window.app_handle().clone()).await.map_err(|e| {
-            format!(
-                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
-                e
-            )
-        })?,
-    };
-    convert_to_mp3_with_ffmpeg(&ffmpeg_path, &input_path, &output_path).map_err(|e| {
-        let _ = window_clone.emit(
-            "conversion-error",
-            serde_json::json!({
-                "error": e
-            }),
-        );
-        e
-    })
-}
-
-#[tauri::command]
-fn read_file_command(path: String) -> Result<String, String> {
-    fs::read_to_string(path).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-fn parse_csv_command(csv_content: String, audio_mode: AudioMode) -> Result<CsvImportResult, String> {
-    let result = parse_csv_content(&csv_content)?;
-
-    let suffix = match audio_mode {
-        AudioMode::Official => "official audio",
-        AudioMode::Raw => "raw audio",
-        AudioMode::Clean => "clean audio",
-    };
-
-    let mut processed_entries: Vec<CsvTrackEntry> = Vec::new();
-
-    for mut entry in result.tracks {
-        let search_query = format!("{} {}", entry.search_query.trim(), suffix);
-        entry.search_query = search_query;
-        processed_entries.push(entry);
-    }
-
-    Ok(CsvImportResult {
-        tracks: processed_entries,
-        total_count: result.total_count,
-        success_count: result.success_count,
-        error_count: result.error_count,
-        errors: result.errors,
-    })
-}
-
-#[tauri::command]
-fn validate_csv_command(csv_content: String) -> Result<Vec<String>, String> {
-    validate_csv_headers(&csv_content)
-}
-
-#[tauri::command]
-fn tag_mp3_command(
-    file_path: String,
-    title: Option<String>,
-    artist: Option<String>,
-    album: Option<String>,
-    year: Option<String>,
-    genre: Option<String>,
-    track_number: Option<String>,
-) -> Result<(), String> {
-    let metadata = TrackMetadata {
-        title,
-        artist,
-        album,
-        year,
-        genre,
-        track_number,
-        album_artist: None,
-        comment: Some("Downloaded from YouTube".to_string()),
-    };
-
-    tag_mp3(&file_path, metadata)
-}
-
-#[tauri::command]
-fn process_input(input_text: String, audio_mode: AudioMode) -> Result<ProcessInputResult, String> {
+async fn build_input_items(
+    input_text: &str,
+    audio_mode: &AudioMode,
+    resolver: &impl LinkResolver,
+) -> ProcessInputResult {
     let lines: Vec<&str> = input_text
         .lines()
         .filter(|line| !line.trim().is_empty())
@@ -318,83 +150,148 @@ fn process_input(input_text: String, audio_mode: AudioMode) -> Result<ProcessInp
     let mut items: Vec<ProcessedItem> = Vec::new();
     let mut url_count = 0;
     let mut search_count = 0;
+    let mut errors = Vec::new();
 
-    for line in lines {
+    for (index, line) in lines.into_iter().enumerate() {
+        let line_number = index + 1;
         let trimmed_line = line.trim();
 
-        if is_youtube_url(trimmed_line) {
+        // Streaming service links can't be downloaded, so each track becomes a search instead
+        if let Some(link) = StreamingLink::parse(trimmed_line) {
+            match resolver.resolve(&link).await {
+                Ok(resolved) => {
+                    for error in resolved.errors {
+                        errors.push(InputError::new(line_number, trimmed_line, error.message));
+                    }
+                    for track in resolved.tracks {
+                        items.push(ProcessedItem {
+                            line_number,
+                            input_type: InputType::SearchQuery,
+                            original_input: track.search_query(),
+                            processed_query: construct_search_query(&track.search_query(), audio_mode),
+                            video_id: None,
+                            source: None,
+                            metadata_override: Some(track.to_metadata()),
+                            duration_seconds: track.duration_seconds,
+                        });
+                        search_count += 1;
+                    }
+                }
+                Err(e) => errors.push(InputError::new(line_number, trimmed_line, e.message)),
+            }
+        } else if is_youtube_collection(trimmed_line) {
+            errors.push(InputError::new(
+                line_number,
+                trimmed_line,
+                "YouTube playlists and channels can't be downloaded as one item - paste the individual video links instead",
+            ));
+        } else if let Some(source) = SourceSite::from_url(trimmed_line) {
             let video_id = extract_video_id(trimmed_line);
             items.push(ProcessedItem {
+                line_number,
                 input_type: InputType::Url,
                 original_input: trimmed_line.to_string(),
                 processed_query: trimmed_line.to_string(),
                 video_id,
+                source: Some(source),
+                metadata_override: None,
+                duration_seconds: None,
             });
             url_count += 1;
         } else {
-            let processed_query = construct_search_query(trimmed_line, &audio_mode);
+            let processed_query = construct_search_query(trimmed_line, audio_mode);
             items.push(ProcessedItem {
+                line_number,
                 input_type: InputType::SearchQuery,
                 original_input: trimmed_line.to_string(),
                 processed_query,
                 video_id: None,
+                source: None,
+                metadata_override: None,
+                duration_seconds: None,
             });
             search_count += 1;
         }
     }
 
     let total_count = items.len();
-    Ok(ProcessInputResult {
+    ProcessInputResult {
         items,
         total_count,
         url_count,
         search_count,
-    })
+        errors,
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::metadata::parse_title_for_metadata;
+fn import_csv_tracks(
+    content: &[u8],
+    settings: &AppSettings,
+    profile: Option<&str>,
+    audio_mode: &AudioMode,
+) -> AppResult<CsvImportResult> {
+    let result = import_track_list(content, &settings.all_csv_profiles(), profile)
+        .map_err(AppError::invalid_input)?;
+    let suffix = get_audio_mode_suffix(audio_mode);
 
-    #[test]
-    fn test_open_folder_with_valid_path() {
-        let result = open_folder("/tmp".to_string());
-        assert!(result.is_ok() || result.is_err());
-    }
+    let mut processed_entries: Vec<CsvTrackEntry> = Vec::new();
 
-    #[test]
-    fn test_open_folder_with_nonexistent_path() {
-        let result = open_folder("/nonexistent/path/that/does/not/exist".to_string());
-        assert!(result.is_err());
+    for mut entry in result.tracks {
+        // Playlist entries can point straight at a video, which must not get a search suffix
+        if SourceSite::from_url(&entry.search_query).is_none() {
+            let search_query = format!("{} {}", entry.search_query.trim(), suffix);
+            entry.search_query = search_query;
+        }
+        processed_entries.push(entry);
     }
 
-    #[test]
-    fn test_open_folder_does_not_panic() {
-        let _ = open_folder("".to_string());
-    }
+    Ok(CsvImportResult {
+        tracks: processed_entries,
+        total_count: result.total_count,
+        success_count: result.success_count,
+        error_count: result.error_count,
+        errors: result.errors,
+        profile: result.profile,
+        format: result.format,
+    })
+}
 
-    #[test]
-    fn test_is_youtube_url_with_standard_url() {
-        assert!(is_youtube_url(
-            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
-        ));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link_resolver::{ResolvedLink, ResolvedTrack};
+    use crate::metadata::parse_title_for_metadata;
 
-    #[test]
-    fn test_is_youtube_url_with_shortened_url() {
-        assert!(is_youtube_url("https://youtu.be/dQw4w9WgXcQ"));
-    }
+    struct FixtureResolver;
 
-    #[test]
-    fn test_is_youtube_url_with_shorts_url() {
-        assert!(is_youtube_url("https://www.youtube.com/shorts/dQw4w9WgXcQ"));
+    impl LinkResolver for FixtureResolver {
+        async fn resolve(&self, link: &StreamingLink) -> AppResult<ResolvedLink> {
+            if link.url.path().contains("missing") {
+                return Err(AppError::invalid_input("Not found"));
+            }
+            let tracks = vec![
+                ResolvedTrack {
+                    title: "One More Time".to_string(),
+                    artist: Some("Daft Punk".to_string()),
+                    album: Some("Discovery".to_string()),
+                    duration_seconds: Some(320),
+                },
+                ResolvedTrack {
+                    title: "Aerodynamic".to_string(),
+                    artist: Some("Daft Punk".to_string()),
+                    album: Some("Discovery".to_string()),
+                    duration_seconds: None,
+                },
+            ];
+            Ok(ResolvedLink {
+                tracks,
+                errors: Vec::new(),
+            })
+        }
     }
 
-    #[test]
-    fn test_is_youtube_url_with_non_url() {
-        assert!(!is_youtube_url("Just a search query"));
-        assert!(!is_youtube_url(""));
+    fn process_input_offline(input: &str, audio_mode: AudioMode) -> ProcessInputResult {
+        crate::test_support::block_on(build_input_items(input, &audio_mode, &FixtureResolver))
     }
 
     #[test]
@@ -448,7 +345,7 @@ mod tests {
     #[test]
     fn test_process_input_with_urls() {
         let input = "https://www.youtube.com/watch?v=abc123defgh\nhttps://youtu.be/xyz789abcde";
-        let result = process_input(input.to_string(), AudioMode::Official).unwrap();
+        let result = process_input_offline(input, AudioMode::Official);
 
         assert_eq!(result.total_count, 2);
         assert_eq!(result.url_count, 2);
@@ -458,10 +355,73 @@ mod tests {
         assert_eq!(result.items[1].video_id, Some("xyz789abcde".to_string()));
     }
 
+    #[test]
+    fn test_process_input_passes_through_other_sites() {
+        let input = "https://soundcloud.com/artist/track\nhttps://artist.bandcamp.com/track/song\nhttps://example.com/media/clip";
+        let result = process_input_offline(input, AudioMode::Official);
+
+        assert_eq!(result.url_count, 3);
+        assert_eq!(result.search_count, 0);
+        assert_eq!(result.items[0].input_type, InputType::Url);
+        assert_eq!(result.items[0].video_id, None);
+        assert_eq!(result.items[0].source, Some(SourceSite::Soundcloud));
+        assert_eq!(result.items[0].processed_query, "https://soundcloud.com/artist/track");
+        assert_eq!(result.items[1].source, Some(SourceSite::Bandcamp));
+        assert_eq!(result.items[2].source, Some(SourceSite::Other));
+    }
+
+    #[test]
+    fn test_process_input_resolves_streaming_links() {
+        let input = "https://open.spotify.com/album/2noRn2Aes5aoNVsU6iWThc\nhttps://open.spotify.com/track/missing\nSong One";
+        let result = process_input_offline(input, AudioMode::Official);
+
+        assert_eq!(result.total_count, 3);
+        assert_eq!(result.search_count, 3);
+        assert_eq!(result.url_count, 0);
+        assert_eq!(result.items[0].input_type, InputType::SearchQuery);
+        assert_eq!(result.items[0].processed_query, "Daft Punk - One More Time official audio");
+        assert_eq!(result.items[0].duration_seconds, Some(320));
+
+        let metadata = result.items[0].metadata_override.as_ref().unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("One More Time"));
+        assert_eq!(metadata.album.as_deref(), Some("Discovery"));
+
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].row_number, 2);
+        assert_eq!(result.errors[0].input, "https://open.spotify.com/track/missing");
+        assert!(result.items[2].metadata_override.is_none());
+        let lines: Vec<usize> = result.items.iter().map(|item| item.line_number).collect();
+        assert_eq!(lines, vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn test_process_input_rejects_youtube_playlists_and_channels() {
+        let input = "https://www.youtube.com/playlist?list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs\nhttps://www.youtube.com/@daftpunk\nhttps://www.youtube.com/watch?v=abc123defgh&list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs";
+        let result = process_input_offline(input, AudioMode::Official);
+
+        assert_eq!(result.total_count, 1);
+        assert_eq!(result.url_count, 1);
+        assert_eq!(result.items[0].video_id, Some("abc123defgh".to_string()));
+        assert_eq!(result.errors.len(), 2);
+        assert!(result.errors[0].input.starts_with("https://www.youtube.com/playlist?list="));
+        assert!(result.errors[0].message.contains("paste the individual video links"));
+        assert_eq!(result.errors[1].row_number, 2);
+        assert_eq!(result.errors[1].input, "https://www.youtube.com/@daftpunk");
+        assert_eq!(result.items[0].line_number, 3);
+    }
+
+    #[test]
+    fn test_strip_id_suffix() {
+        assert_eq!(strip_id_suffix("Song Title [dQw4w9WgXcQ]"), "Song Title");
+        assert_eq!(strip_id_suffix("Track [Live] [1234567890]"), "Track [Live]");
+        assert_eq!(strip_id_suffix("artist-track-name [artist/track]"), "artist-track-name");
+        assert_eq!(strip_id_suffix("No Id Here"), "No Id Here");
+    }
+
     #[test]
     fn test_process_input_with_search_queries() {
         let input = "Song One\nSong Two";
-        let result = process_input(input.to_string(), AudioMode::Official).unwrap();
+        let result = process_input_offline(input, AudioMode::Official);
 
         assert_eq!(result.total_count, 2);
         assert_eq!(result.url_count, 0);
@@ -475,7 +435,7 @@ mod tests {
     fn test_process_input_with_mixed_input() {
         let input =
             "https://www.youtube.com/watch?v=abc123defgh\nSong One\nhttps://youtu.be/xyz789abcde";
-        let result = process_input(input.to_string(), AudioMode::Clean).unwrap();
+        let result = process_input_offline(input, AudioMode::Clean);
 
         assert_eq!(result.total_count, 3);
         assert_eq!(result.url_count, 2);
@@ -486,7 +446,7 @@ mod tests {
     #[test]
     fn test_process_input_filters_empty_lines() {
         let input = "\nhttps://www.youtube.com/watch?v=abc123defgh\n\nSong One\n";
-        let result = process_input(input.to_string(), AudioMode::Official).unwrap();
+        let result = process_input_offline(input, AudioMode::Official);
 
         assert_eq!(result.total_count, 2);
     }
@@ -494,7 +454,7 @@ mod tests {
     #[test]
     fn test_process_input_with_empty_input() {
         let input = "";
-        let result = process_input(input.to_string(), AudioMode::Official).unwrap();
+        let result = process_input_offline(input, AudioMode::Official);
 
         assert_eq!(result.total_count, 0);
         assert_eq!(result.url_count, 0);
@@ -504,7 +464,7 @@ mod tests {
     #[test]
     fn test_process_input_with_only_whitespace_lines() {
         let input = "   \n\n   \n\t\n   ";
-        let result = process_input(input.to_string(), AudioMode::Official).unwrap();
+        let result = process_input_offline(input, AudioMode::Official);
 
         assert_eq!(result.total_count, 0);
     }
@@ -512,7 +472,7 @@ mod tests {
     #[test]
     fn test_process_input_search_query_with_special_characters() {
         let input = "Song & Dance\nRock n Roll";
-        let result = process_input(input.to_string(), AudioMode::Clean).unwrap();
+        let result = process_input_offline(input, AudioMode::Clean);
 
         assert_eq!(result.total_count, 2);
         assert_eq!(result.search_count, 2);
@@ -523,7 +483,7 @@ mod tests {
     #[test]
     fn test_process_input_handles_long_video_ids() {
         let input = "https://www.youtube.com/watch?v=abcdefghijk";
-        let result = process_input(input.to_string(), AudioMode::Official).unwrap();
+        let result = process_input_offline(input, AudioMode::Official);
 
         assert_eq!(result.total_count, 1);
         assert_eq!(result.items[0].video_id, Some("abcdefghijk".to_string()));
@@ -540,6 +500,7 @@ use crate::metadata::{tag_mp3, TrackMetadata};
 
         let metadata = TrackMetadata {
             title: Some("Test Song".to_string()),
+            subtitle: None,
             artist: Some("Test Artist".to_string()),
             album: Some("Test Album".to_string()),
             year: Some("2024".to_string()),
@@ -547,6 +508,12 @@ use crate::metadata::{tag_mp3, TrackMetadata};
             track_number: Some("1".to_string()),
             album_artist: Some("Various Artists".to_string()),
             comment: Some("Test comment".to_string()),
+            lyrics: None,
+            label: None,
+            copyright: None,
+            release_date: None,
+            disc_number: None,
+            isrc: None,
         };
 
         let result = tag_mp3(test_file.to_str().unwrap(), metadata);
@@ -562,7 +529,7 @@ use crate::metadata::{tag_mp3, TrackMetadata};
             TrackMetadata::default(),
         );
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("File does not exist"));
+        assert!(result.unwrap_err().message.contains("File does not exist"));
     }
 
     #[test]
@@ -579,30 +546,3 @@ use crate::metadata::{tag_mp3, TrackMetadata};
         assert!(metadata.artist.is_none());
     }
 }
-
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![
-            check_ytdlp,
-            download_ytdlp,
-            check_ffmpeg,
-            download_ffmpeg,
-            set_download_path,
-            open_folder,
-            process_input,
-            search_video_command,
-            download_video_command,
-            process_item,
-            clean_filename_command,
-            convert_to_mp3_command,
-            read_file_command,
-            parse_csv_command,
-            validate_csv_command,
-            tag_mp3_command
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
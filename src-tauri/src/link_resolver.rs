@@ -0,0 +1,741 @@
+use regex::Regex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::metadata::TrackMetadata;
+use crate::subtitles::decode_entities;
+use crate::title_parser::parse_title;
+
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; lyricut-yt-downloader)";
+const MAX_COLLECTION_TRACKS: usize = 200;
+const MAX_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const SERVICE_SUFFIXES: &[&str] = &[" on Apple Music", " on Spotify", " on Deezer", " | Spotify"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingService {
+    Spotify,
+    AppleMusic,
+    Deezer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Track,
+    Album,
+    Playlist,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamingLink {
+    pub service: StreamingService,
+    pub kind: LinkKind,
+    pub url: Url,
+}
+
+impl StreamingLink {
+    pub fn parse(text: &str) -> Option<StreamingLink> {
+        let url = Url::parse(text.trim()).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        let host = url.host_str()?.to_lowercase();
+        let service = match host.strip_prefix("www.").unwrap_or(&host) {
+            "open.spotify.com" => StreamingService::Spotify,
+            "music.apple.com" => StreamingService::AppleMusic,
+            "deezer.com" => StreamingService::Deezer,
+            _ => return None,
+        };
+
+        let mut kind = url.path_segments()?.find_map(|segment| match segment {
+            "track" | "song" => Some(LinkKind::Track),
+            "album" => Some(LinkKind::Album),
+            "playlist" => Some(LinkKind::Playlist),
+            _ => None,
+        })?;
+
+        // Apple Music shares single songs as album links with the track in `?i=`
+        if service == StreamingService::AppleMusic
+            && kind == LinkKind::Album
+            && url.query_pairs().any(|(key, _)| key == "i")
+        {
+            kind = LinkKind::Track;
+        }
+
+        Some(StreamingLink { service, kind, url })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedTrack {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_seconds: Option<u64>,
+}
+
+impl ResolvedTrack {
+    pub fn search_query(&self) -> String {
+        match &self.artist {
+            Some(artist) => format!("{} - {}", artist, self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    pub fn to_metadata(&self) -> TrackMetadata {
+        TrackMetadata {
+            title: Some(self.title.clone()),
+            artist: self.artist.clone(),
+            album: self.album.clone(),
+            ..TrackMetadata::default()
+        }
+    }
+}
+
+/// The tracks read from a link. Albums and playlists keep whatever tracks could be read, with
+/// an error for each that couldn't.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResolvedLink {
+    pub tracks: Vec<ResolvedTrack>,
+    pub errors: Vec<AppError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResolverSettings {
+    /// Replaces the service's scheme and host, e.g. `http://127.0.0.1:8080` fetches
+    /// `http://127.0.0.1:8080/open.spotify.com/track/<id>`.
+    pub base_url: Option<String>,
+    /// How long a single page or oEmbed request may take.
+    pub timeout_seconds: u64,
+}
+
+impl Default for ResolverSettings {
+    fn default() -> Self {
+        ResolverSettings {
+            base_url: None,
+            timeout_seconds: 15,
+        }
+    }
+}
+
+impl ResolverSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_seconds == 0 {
+            return Err("The link resolver timeout must be at least one second".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[allow(async_fn_in_trait)]
+pub trait LinkResolver {
+    async fn resolve(&self, link: &StreamingLink) -> AppResult<ResolvedLink>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PageMeta {
+    entries: Vec<(String, String)>,
+}
+
+impl PageMeta {
+    pub fn parse(html: &str) -> PageMeta {
+        let meta_regex = Regex::new(r"(?is)<meta\s([^>]*)>").expect("valid meta regex");
+        let attribute_regex = Regex::new(r#"(?s)([a-zA-Z:_-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+            .expect("valid attribute regex");
+
+        let entries = meta_regex
+            .captures_iter(html)
+            .filter_map(|tag| {
+                let mut key = None;
+                let mut content = None;
+                for attribute in attribute_regex.captures_iter(&tag[1]) {
+                    let value = attribute
+                        .get(2)
+                        .or_else(|| attribute.get(3))
+                        .map(|v| v.as_str())
+                        .unwrap_or_default();
+                    match attribute[1].to_lowercase().as_str() {
+                        "property" | "name" => key = Some(value.to_lowercase()),
+                        "content" => content = Some(decode_entities(value).trim().to_string()),
+                        _ => {}
+                    }
+                }
+                Some((key?, content?)).filter(|(_, c)| !c.is_empty())
+            })
+            .collect();
+
+        PageMeta { entries }
+    }
+
+    pub fn first(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn all(&self, key: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+}
+
+fn parse_duration(value: &str) -> Option<u64> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(seconds);
+    }
+
+    // ISO 8601 durations such as "PT3M45S"
+    let iso_regex = Regex::new(r"^PT(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)(?:\.\d+)?S)?$")
+        .expect("valid duration regex");
+    let captures = iso_regex.captures(value.trim())?;
+    let part = |index: usize| {
+        captures
+            .get(index)
+            .and_then(|m| m.as_str().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    Some(part(1) * 3600 + part(2) * 60 + part(3))
+}
+
+fn strip_service_suffix(title: &str) -> &str {
+    SERVICE_SUFFIXES
+        .iter()
+        .find_map(|suffix| title.strip_suffix(suffix))
+        .unwrap_or(title)
+        .trim()
+}
+
+/// Reads a track (or the album/playlist itself) from a page's OpenGraph tags.
+pub fn track_from_page(service: StreamingService, page: &PageMeta) -> Option<ResolvedTrack> {
+    let og_title = strip_service_suffix(page.first("og:title")?);
+    let duration_seconds = page
+        .first("music:duration")
+        .or_else(|| page.first("music:song:duration"))
+        .and_then(parse_duration);
+
+    let listen_regex =
+        Regex::new(r"(?i)^listen to (.+) by (.+?) on (?:Apple Music|Spotify|Deezer)")
+            .expect("valid description regex");
+    let by_regex = Regex::new(r"^(.+) by (.+)$").expect("valid by regex");
+
+    let (title, artist) = if let Some(artist) = page.first("music:musician_description") {
+        (og_title.to_string(), Some(artist.to_string()))
+    } else if let Some(captures) = page
+        .first("og:description")
+        .and_then(|description| listen_regex.captures(description))
+    {
+        (captures[1].to_string(), Some(captures[2].to_string()))
+    } else if let Some(captures) = by_regex
+        .captures(og_title)
+        .filter(|_| service == StreamingService::AppleMusic)
+    {
+        (captures[1].to_string(), Some(captures[2].to_string()))
+    } else {
+        let parsed = parse_title(og_title, None);
+        (parsed.title, parsed.artist)
+    };
+
+    if title.trim().is_empty() {
+        return None;
+    }
+
+    Some(ResolvedTrack {
+        title: title.trim().to_string(),
+        artist,
+        album: page.first("music:album_description").map(|a| a.to_string()),
+        duration_seconds,
+    })
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OEmbed {
+    pub title: Option<String>,
+    pub author_name: Option<String>,
+}
+
+// Apple Music has no public oEmbed endpoint
+fn oembed_endpoint(service: StreamingService) -> Option<&'static str> {
+    match service {
+        StreamingService::Spotify => Some("https://open.spotify.com/oembed"),
+        StreamingService::Deezer => Some("https://api.deezer.com/oembed"),
+        StreamingService::AppleMusic => None,
+    }
+}
+
+/// Reads a track from a service's oEmbed data, which has at most a title and an author.
+pub fn track_from_oembed(oembed: &OEmbed) -> Option<ResolvedTrack> {
+    let title = strip_service_suffix(oembed.title.as_deref()?.trim());
+    let author = oembed
+        .author_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty());
+    let (title, artist) = match author {
+        Some(author) => (title.to_string(), Some(author.to_string())),
+        None => {
+            let parsed = parse_title(title, None);
+            (parsed.title, parsed.artist)
+        }
+    };
+    if title.trim().is_empty() {
+        return None;
+    }
+
+    Some(ResolvedTrack {
+        title: title.trim().to_string(),
+        artist,
+        album: None,
+        duration_seconds: None,
+    })
+}
+
+pub struct OpenGraphResolver {
+    client: reqwest::Client,
+    base_url: Option<String>,
+}
+
+impl OpenGraphResolver {
+    pub fn new(settings: &ResolverSettings) -> AppResult<OpenGraphResolver> {
+        let timeout = Duration::from_secs(settings.timeout_seconds);
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(timeout.min(MAX_CONNECT_TIMEOUT))
+            .timeout(timeout)
+            .build()
+            .map_err(|e| AppError::internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(OpenGraphResolver {
+            client,
+            base_url: settings
+                .base_url
+                .as_deref()
+                .map(|b| b.trim().trim_end_matches('/').to_string())
+                .filter(|b| !b.is_empty()),
+        })
+    }
+
+    fn page_url(&self, url: &Url) -> String {
+        match &self.base_url {
+            Some(base) => {
+                let mut page_url = format!(
+                    "{}/{}{}",
+                    base,
+                    url.host_str().unwrap_or_default(),
+                    url.path()
+                );
+                if let Some(query) = url.query() {
+                    page_url.push('?');
+                    page_url.push_str(query);
+                }
+                page_url
+            }
+            None => url.to_string(),
+        }
+    }
+
+    async fn fetch(&self, url: &Url) -> AppResult<reqwest::Response> {
+        let response = self
+            .client
+            .get(self.page_url(url))
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::new(
+                    ErrorKind::Network,
+                    format!("Failed to fetch {}: {}", url, e),
+                )
+            })?;
+        if !response.status().is_success() {
+            return Err(AppError::http_status(
+                &format!("Failed to fetch {}", url),
+                response.status().as_u16(),
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn fetch_page(&self, url: &Url) -> AppResult<PageMeta> {
+        let html = self.fetch(url).await?.text().await.map_err(|e| {
+            AppError::new(ErrorKind::Network, format!("Failed to read {}: {}", url, e))
+        })?;
+        Ok(PageMeta::parse(&html))
+    }
+
+    async fn fetch_oembed(&self, service: StreamingService, url: &Url) -> AppResult<OEmbed> {
+        let endpoint = oembed_endpoint(service)
+            .ok_or_else(|| AppError::invalid_input(format!("No oEmbed endpoint for {}", url)))?;
+        let mut oembed_url = Url::parse(endpoint)
+            .map_err(|e| AppError::internal(format!("Invalid oEmbed endpoint: {}", e)))?;
+        oembed_url
+            .query_pairs_mut()
+            .append_pair("url", url.as_str());
+        let body = self.fetch(&oembed_url).await?.text().await.map_err(|e| {
+            AppError::new(
+                ErrorKind::Network,
+                format!("Failed to read oEmbed data for {}: {}", url, e),
+            )
+        })?;
+        serde_json::from_str(&body).map_err(|e| {
+            AppError::invalid_input(format!("Failed to read oEmbed data for {}: {}", url, e))
+        })
+    }
+
+    // Pages that can't be read or carry no OpenGraph track tags fall back to oEmbed, keeping
+    // the page's error when that doesn't help either
+    async fn resolve_track(
+        &self,
+        service: StreamingService,
+        url: &Url,
+    ) -> AppResult<ResolvedTrack> {
+        let error = match self.fetch_page(url).await {
+            Ok(page) => match track_from_page(service, &page) {
+                Some(track) => return Ok(track),
+                None => AppError::invalid_input(format!("No track information found at {}", url)),
+            },
+            Err(e) => e,
+        };
+        match self.fetch_oembed(service, url).await {
+            Ok(oembed) => track_from_oembed(&oembed).ok_or(error),
+            Err(_) => Err(error),
+        }
+    }
+}
+
+impl LinkResolver for OpenGraphResolver {
+    async fn resolve(&self, link: &StreamingLink) -> AppResult<ResolvedLink> {
+        if link.kind == LinkKind::Track {
+            let track = self.resolve_track(link.service, &link.url).await?;
+            return Ok(ResolvedLink {
+                tracks: vec![track],
+                errors: Vec::new(),
+            });
+        }
+
+        let page = self.fetch_page(&link.url).await?;
+
+        let song_urls: Vec<Url> = page
+            .all("music:song")
+            .into_iter()
+            .filter_map(|song| link.url.join(song).ok())
+            .take(MAX_COLLECTION_TRACKS)
+            .collect();
+        if song_urls.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "No tracks found at {}",
+                link.url
+            )));
+        }
+
+        // Album pages describe the album itself; its title fills in each track's album
+        let album = match link.kind {
+            LinkKind::Album => track_from_page(link.service, &page).map(|a| a.title),
+            _ => None,
+        };
+
+        // One unreadable track doesn't cost the rest of the album
+        let mut resolved = ResolvedLink::default();
+        for song_url in song_urls {
+            match self.resolve_track(link.service, &song_url).await {
+                Ok(mut track) => {
+                    if track.album.is_none() {
+                        track.album = album.clone();
+                    }
+                    resolved.tracks.push(track);
+                }
+                Err(e) => resolved.errors.push(e),
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    const SPOTIFY_TRACK: &str = r#"<html><head>
+<meta property="og:title" content="Bohemian Rhapsody - Remastered 2011"/>
+<meta property="og:description" content="Listen to Bohemian Rhapsody - Remastered 2011 on Spotify. Song · Queen · 1975"/>
+<meta name="music:duration" content="354"/>
+<meta name="music:musician_description" content="Queen"/>
+</head></html>"#;
+
+    const APPLE_TRACK: &str = r#"<meta property="og:title" content="Stand by Me by Ben E. King on Apple Music">
+<meta property="music:song:duration" content="PT2M58S">"#;
+
+    const DEEZER_TRACK: &str = r#"<meta property="og:title" content="Daft Punk - One More Time">
+<meta property="og:description" content="Listen to One More Time by Daft Punk on Deezer. With music streaming on Deezer…">
+<meta property="music:duration" content="320">"#;
+
+    fn serve_fixtures(pages: HashMap<String, String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap() == 0 || header == "\r\n" {
+                        break;
+                    }
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = match pages.get(path) {
+                    Some(body) => ("200 OK", body.as_str()),
+                    None => ("404 Not Found", ""),
+                };
+                let mut stream = stream;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn test_parse_streaming_links() {
+        let cases = [
+            ("https://open.spotify.com/track/4u7EnebtmKWzUH433cf5Qv", StreamingService::Spotify, LinkKind::Track),
+            ("https://open.spotify.com/intl-de/album/1GbtB4zTqAsyfZEsm1RZfx?si=x", StreamingService::Spotify, LinkKind::Album),
+            ("https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M", StreamingService::Spotify, LinkKind::Playlist),
+            ("https://music.apple.com/us/album/bohemian-rhapsody/1440806041?i=1440806326", StreamingService::AppleMusic, LinkKind::Track),
+            ("https://music.apple.com/us/album/a-night-at-the-opera/1440806041", StreamingService::AppleMusic, LinkKind::Album),
+            ("https://music.apple.com/us/song/bohemian-rhapsody/1440806326", StreamingService::AppleMusic, LinkKind::Track),
+            ("https://music.apple.com/us/playlist/todays-hits/pl.f4d106fed2bd41149aaacabb233eb5eb", StreamingService::AppleMusic, LinkKind::Playlist),
+            ("https://www.deezer.com/en/track/3135556", StreamingService::Deezer, LinkKind::Track),
+            ("https://www.deezer.com/album/302127", StreamingService::Deezer, LinkKind::Album),
+        ];
+
+        for (url, service, kind) in cases {
+            let link = StreamingLink::parse(url).unwrap_or_else(|| panic!("{}", url));
+            assert_eq!((link.service, link.kind), (service, kind), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_streaming_links_rejects_other_urls() {
+        assert!(StreamingLink::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").is_none());
+        assert!(
+            StreamingLink::parse("https://open.spotify.com/artist/1dfeR4HaWDbWqFHLkxsg1d")
+                .is_none()
+        );
+        assert!(StreamingLink::parse("Queen - Bohemian Rhapsody").is_none());
+    }
+
+    #[test]
+    fn test_page_meta_parse() {
+        let page = PageMeta::parse(
+            r#"<meta content="Rock &amp; Roll" property="og:title"><meta name='music:song' content='/track/1'><meta name="music:song" content="/track/2">"#,
+        );
+
+        assert_eq!(page.first("og:title"), Some("Rock & Roll"));
+        assert_eq!(page.all("music:song"), vec!["/track/1", "/track/2"]);
+        assert_eq!(page.first("og:image"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("354"), Some(354));
+        assert_eq!(parse_duration("PT3M45S"), Some(225));
+        assert_eq!(parse_duration("PT1H2M3S"), Some(3723));
+        assert_eq!(parse_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_track_from_spotify_page() {
+        let track =
+            track_from_page(StreamingService::Spotify, &PageMeta::parse(SPOTIFY_TRACK)).unwrap();
+
+        assert_eq!(track.title, "Bohemian Rhapsody - Remastered 2011");
+        assert_eq!(track.artist.as_deref(), Some("Queen"));
+        assert_eq!(track.duration_seconds, Some(354));
+    }
+
+    #[test]
+    fn test_track_from_apple_music_page() {
+        let track =
+            track_from_page(StreamingService::AppleMusic, &PageMeta::parse(APPLE_TRACK)).unwrap();
+
+        assert_eq!(track.title, "Stand by Me");
+        assert_eq!(track.artist.as_deref(), Some("Ben E. King"));
+        assert_eq!(track.duration_seconds, Some(178));
+    }
+
+    #[test]
+    fn test_track_from_deezer_page() {
+        let track =
+            track_from_page(StreamingService::Deezer, &PageMeta::parse(DEEZER_TRACK)).unwrap();
+
+        assert_eq!(track.title, "One More Time");
+        assert_eq!(track.artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(track.search_query(), "Daft Punk - One More Time");
+    }
+
+    #[test]
+    fn test_track_from_page_without_title() {
+        assert!(track_from_page(StreamingService::Spotify, &PageMeta::default()).is_none());
+    }
+
+    #[test]
+    fn test_open_graph_resolver_with_fixture_server() {
+        let album = r#"<meta property="og:title" content="Discovery">
+<meta name="music:musician_description" content="Daft Punk">
+<meta name="music:song" content="https://open.spotify.com/track/one">
+<meta name="music:song" content="https://open.spotify.com/track/two">"#;
+        let one = r#"<meta property="og:title" content="One More Time"><meta name="music:musician_description" content="Daft Punk"><meta name="music:duration" content="320">"#;
+        let two = r#"<meta property="og:title" content="Aerodynamic"><meta name="music:musician_description" content="Daft Punk">"#;
+
+        let base_url = serve_fixtures(HashMap::from([
+            (
+                "/open.spotify.com/album/discovery".to_string(),
+                album.to_string(),
+            ),
+            ("/open.spotify.com/track/one".to_string(), one.to_string()),
+            ("/open.spotify.com/track/two".to_string(), two.to_string()),
+        ]));
+        let resolver = OpenGraphResolver::new(&ResolverSettings {
+            base_url: Some(base_url),
+            ..ResolverSettings::default()
+        })
+        .unwrap();
+        let link = StreamingLink::parse("https://open.spotify.com/album/discovery").unwrap();
+
+        let resolved = crate::test_support::block_on(resolver.resolve(&link)).unwrap();
+        let tracks = resolved.tracks;
+
+        assert!(resolved.errors.is_empty());
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].title, "One More Time");
+        assert_eq!(tracks[0].album.as_deref(), Some("Discovery"));
+        assert_eq!(tracks[0].duration_seconds, Some(320));
+        assert_eq!(tracks[1].title, "Aerodynamic");
+    }
+
+    #[test]
+    fn test_open_graph_resolver_missing_page() {
+        let base_url = serve_fixtures(HashMap::new());
+        let resolver = OpenGraphResolver::new(&ResolverSettings {
+            base_url: Some(base_url),
+            ..ResolverSettings::default()
+        })
+        .unwrap();
+        let link = StreamingLink::parse("https://open.spotify.com/track/missing").unwrap();
+
+        let error = crate::test_support::block_on(resolver.resolve(&link)).unwrap_err();
+        assert!(error.message.contains("HTTP 404"));
+        assert!(!error.retryable);
+    }
+
+    #[test]
+    fn test_open_graph_resolver_gives_up_on_a_silent_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        // Accepts the page and oEmbed requests but never answers either
+        std::thread::spawn(move || {
+            let _held: Vec<_> = listener.incoming().take(2).collect();
+            std::thread::sleep(Duration::from_secs(5));
+        });
+        let resolver = OpenGraphResolver::new(&ResolverSettings {
+            base_url: Some(format!("http://{}", address)),
+            timeout_seconds: 1,
+        })
+        .unwrap();
+        let link = StreamingLink::parse("https://open.spotify.com/track/slow").unwrap();
+
+        let error = crate::test_support::block_on(resolver.resolve(&link)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Network);
+        assert!(error.retryable);
+        assert!(error.message.contains("track/slow"));
+    }
+
+    #[test]
+    fn test_settings_reject_a_zero_timeout() {
+        assert!(ResolverSettings::default().validate().is_ok());
+        let settings = ResolverSettings {
+            timeout_seconds: 0,
+            ..ResolverSettings::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_track_from_oembed() {
+        let track = track_from_oembed(&OEmbed {
+            title: Some("Queen - Bohemian Rhapsody".to_string()),
+            author_name: None,
+        })
+        .unwrap();
+        assert_eq!(track.title, "Bohemian Rhapsody");
+        assert_eq!(track.artist.as_deref(), Some("Queen"));
+
+        let track = track_from_oembed(&OEmbed {
+            title: Some("One More Time".to_string()),
+            author_name: Some("Daft Punk".to_string()),
+        })
+        .unwrap();
+        assert_eq!(track.search_query(), "Daft Punk - One More Time");
+        assert!(track_from_oembed(&OEmbed::default()).is_none());
+    }
+
+    #[test]
+    fn test_collection_keeps_readable_tracks_and_falls_back_to_oembed() {
+        let playlist = r#"<meta property="og:title" content="Mix">
+<meta name="music:song" content="https://open.spotify.com/track/one">
+<meta name="music:song" content="https://open.spotify.com/track/two">
+<meta name="music:song" content="https://open.spotify.com/track/gone">"#;
+        let one = r#"<meta property="og:title" content="One More Time"><meta name="music:musician_description" content="Daft Punk">"#;
+        // A page without OpenGraph tags, whose oEmbed data still names the track
+        let two = "<html><title>Spotify</title></html>";
+        let two_oembed = r#"{"title": "Daft Punk - Aerodynamic", "type": "rich"}"#;
+
+        let base_url = serve_fixtures(HashMap::from([
+            (
+                "/open.spotify.com/playlist/mix".to_string(),
+                playlist.to_string(),
+            ),
+            ("/open.spotify.com/track/one".to_string(), one.to_string()),
+            ("/open.spotify.com/track/two".to_string(), two.to_string()),
+            (
+                "/open.spotify.com/oembed?url=https%3A%2F%2Fopen.spotify.com%2Ftrack%2Ftwo"
+                    .to_string(),
+                two_oembed.to_string(),
+            ),
+        ]));
+        let resolver = OpenGraphResolver::new(&ResolverSettings {
+            base_url: Some(base_url),
+            ..ResolverSettings::default()
+        })
+        .unwrap();
+        let link = StreamingLink::parse("https://open.spotify.com/playlist/mix").unwrap();
+
+        let resolved = crate::test_support::block_on(resolver.resolve(&link)).unwrap();
+
+        let titles: Vec<&str> = resolved.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["One More Time", "Aerodynamic"]);
+        assert_eq!(resolved.tracks[1].artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(resolved.errors.len(), 1);
+        assert!(resolved.errors[0].message.contains("track/gone"));
+    }
+}
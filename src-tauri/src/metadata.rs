@@ -3,9 +3,13 @@ use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::title_parser::parse_title;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TrackMetadata {
     pub title: Option<String>,
+    pub subtitle: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
     pub year: Option<String>,
@@ -13,17 +17,60 @@ pub struct TrackMetadata {
     pub track_number: Option<String>,
     pub album_artist: Option<String>,
     pub comment: Option<String>,
+    pub lyrics: Option<String>,
+    pub label: Option<String>,
+    pub copyright: Option<String>,
+    pub release_date: Option<String>,
+    pub disc_number: Option<String>,
+    pub isrc: Option<String>,
+}
+
+impl TrackMetadata {
+    /// Fills any fields that are still empty from a lower-priority source.
+    pub fn with_fallback(self, fallback: TrackMetadata) -> TrackMetadata {
+        TrackMetadata {
+            title: self.title.or(fallback.title),
+            subtitle: self.subtitle.or(fallback.subtitle),
+            artist: self.artist.or(fallback.artist),
+            album: self.album.or(fallback.album),
+            year: self.year.or(fallback.year),
+            genre: self.genre.or(fallback.genre),
+            track_number: self.track_number.or(fallback.track_number),
+            album_artist: self.album_artist.or(fallback.album_artist),
+            comment: self.comment.or(fallback.comment),
+            lyrics: self.lyrics.or(fallback.lyrics),
+            label: self.label.or(fallback.label),
+            copyright: self.copyright.or(fallback.copyright),
+            release_date: self.release_date.or(fallback.release_date),
+            disc_number: self.disc_number.or(fallback.disc_number),
+            isrc: self.isrc.or(fallback.isrc),
+        }
+    }
 }
 
-pub fn tag_mp3(file_path: &str, metadata: TrackMetadata) -> Result<(), String> {
+fn missing_file(file_path: &str) -> AppError {
+    AppError::new(
+        ErrorKind::FileNotFound,
+        format!("File does not exist: {}", file_path),
+    )
+}
+
+fn id3_error(context: &str, error: id3::Error) -> AppError {
+    match error.kind {
+        id3::ErrorKind::Io(io_error) => AppError::io(context, io_error),
+        _ => AppError::internal(format!("{}: {}", context, error.description)),
+    }
+}
+
+pub fn tag_mp3(file_path: &str, metadata: TrackMetadata) -> AppResult<()> {
     let path = Path::new(file_path);
 
     if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
+        return Err(missing_file(file_path));
     }
 
     if !file_path.ends_with(".mp3") {
-        return Err("File is not an MP3 file".to_string());
+        return Err(AppError::invalid_input("File is not an MP3 file"));
     }
 
     let mut tag = Tag::new();
@@ -32,6 +79,10 @@ pub fn tag_mp3(file_path: &str, metadata: TrackMetadata) -> Result<(), String> {
         tag.add_frame(Frame::text("TIT2", title.to_string()));
     }
 
+    if let Some(subtitle) = &metadata.subtitle {
+        tag.add_frame(Frame::text("TIT3", subtitle.to_string()));
+    }
+
     if let Some(artist) = &metadata.artist {
         tag.add_frame(Frame::text("TPE1", artist.to_string()));
     }
@@ -55,6 +106,10 @@ pub fn tag_mp3(file_path: &str, metadata: TrackMetadata) -> Result<(), String> {
         tag.add_frame(Frame::text("TRCK", track.to_string()));
     }
 
+    if let Some(disc) = &metadata.disc_number {
+        tag.add_frame(Frame::text("TPOS", disc.to_string()));
+    }
+
     if let Some(album_artist) = &metadata.album_artist {
         tag.add_frame(Frame::text("TPE2", album_artist.to_string()));
     }
@@ -70,43 +125,88 @@ pub fn tag_mp3(file_path: &str, metadata: TrackMetadata) -> Result<(), String> {
         ));
     }
 
+    if let Some(label) = &metadata.label {
+        tag.add_frame(Frame::text("TPUB", label.to_string()));
+    }
+
+    if let Some(copyright) = &metadata.copyright {
+        tag.add_frame(Frame::text("TCOP", copyright.to_string()));
+    }
+
+    if let Some(release_date) = &metadata.release_date {
+        tag.add_frame(Frame::text("TDRL", release_date.to_string()));
+    }
+
+    if let Some(isrc) = &metadata.isrc {
+        tag.add_frame(Frame::text("TSRC", isrc.to_string()));
+    }
+
+    if let Some(lyrics) = &metadata.lyrics {
+        tag.add_frame(Frame::with_content(
+            "USLT",
+            id3::Content::Lyrics(id3::frame::Lyrics {
+                lang: "eng".to_string(),
+                description: String::new(),
+                text: lyrics.to_string(),
+            }),
+        ));
+    }
+
     tag.write_to_path(path, id3::Version::Id3v24)
-        .map_err(|e| format!("Failed to write ID3 tags: {}", e))?;
+        .map_err(|e| id3_error("Failed to write ID3 tags", e))?;
 
     Ok(())
 }
 
+#[cfg(any(feature = "gui", test))]
+fn text_frame(tag: &Tag, id: &str) -> Option<String> {
+    tag.get(id)
+        .and_then(|frame| frame.content().text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(any(feature = "gui", test))]
+pub fn read_mp3_tags(file_path: &str) -> AppResult<TrackMetadata> {
+    let path = Path::new(file_path);
+
+    if !path.exists() {
+        return Err(missing_file(file_path));
+    }
+
+    let tag = Tag::read_from_path(path)
+        .map_err(|e| id3_error("Failed to read ID3 tags", e))?;
+
+    Ok(TrackMetadata {
+        title: text_frame(&tag, "TIT2"),
+        subtitle: text_frame(&tag, "TIT3"),
+        artist: text_frame(&tag, "TPE1"),
+        album: text_frame(&tag, "TALB"),
+        year: text_frame(&tag, "TYER")
+            .or_else(|| text_frame(&tag, "TDRC"))
+            .or_else(|| text_frame(&tag, "TDOR")),
+        genre: text_frame(&tag, "TCON"),
+        track_number: text_frame(&tag, "TRCK"),
+        album_artist: text_frame(&tag, "TPE2"),
+        comment: None,
+        lyrics: None,
+        label: text_frame(&tag, "TPUB"),
+        copyright: text_frame(&tag, "TCOP"),
+        release_date: text_frame(&tag, "TDRL"),
+        disc_number: text_frame(&tag, "TPOS"),
+        isrc: text_frame(&tag, "TSRC"),
+    })
+}
+
 #[allow(dead_code)]
 pub fn parse_title_for_metadata(title: &str) -> TrackMetadata {
-    let mut metadata = TrackMetadata::default();
-
-    let cleaned_title = title.trim();
-
-    let patterns = [r"(.+?)\s*[-–—]\s*(.+)", r"(.+?)\s*:\s*(.+)"];
-
-    for pattern in &patterns {
-        if let Ok(regex) = regex::Regex::new(pattern) {
-            if let Some(captures) = regex.captures(cleaned_title) {
-                if let (Some(artist_cap), Some(title_cap)) = (captures.get(1), captures.get(2)) {
-                    let artist = artist_cap.as_str().trim().to_string();
-                    let song_title = title_cap.as_str().trim().to_string();
-
-                    if !artist.is_empty()
-                        && !song_title.is_empty()
-                        && artist.len() < 100
-                        && song_title.len() < 200
-                    {
-                        metadata.artist = Some(artist);
-                        metadata.title = Some(song_title);
-                        return metadata;
-                    }
-                }
-            }
-        }
-    }
+    let parsed = parse_title(title, None);
 
-    metadata.title = Some(cleaned_title.to_string());
-    metadata
+    TrackMetadata {
+        title: Some(parsed.title),
+        artist: parsed.artist,
+        ..TrackMetadata::default()
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +227,7 @@ mod tests {
     fn test_track_metadata_with_values() {
         let metadata = TrackMetadata {
             title: Some("Test Song".to_string()),
+            subtitle: None,
             artist: Some("Test Artist".to_string()),
             album: Some("Test Album".to_string()),
             year: Some("2024".to_string()),
@@ -134,6 +235,12 @@ mod tests {
             track_number: Some("1".to_string()),
             album_artist: Some("Various Artists".to_string()),
             comment: Some("Downloaded".to_string()),
+            lyrics: None,
+            label: None,
+            copyright: None,
+            release_date: None,
+            disc_number: None,
+            isrc: None,
         };
 
         assert_eq!(metadata.title, Some("Test Song".to_string()));
@@ -190,16 +297,105 @@ mod tests {
         assert_eq!(metadata.title, Some("Hey Jude".to_string()));
     }
 
+    #[test]
+    fn test_with_fallback_keeps_existing_values() {
+        let primary = TrackMetadata {
+            title: Some("Primary".to_string()),
+            ..TrackMetadata::default()
+        };
+        let fallback = TrackMetadata {
+            title: Some("Fallback".to_string()),
+            album: Some("Album".to_string()),
+            ..TrackMetadata::default()
+        };
+
+        let merged = primary.with_fallback(fallback);
+        assert_eq!(merged.title, Some("Primary".to_string()));
+        assert_eq!(merged.album, Some("Album".to_string()));
+    }
+
     #[test]
     fn test_tag_mp3_nonexistent_file() {
         let result = tag_mp3("/nonexistent/file.mp3", TrackMetadata::default());
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("File does not exist"));
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::FileNotFound);
+        assert!(error.message.contains("File does not exist"));
+    }
+
+    #[test]
+    fn test_tag_mp3_embeds_lyrics() {
+        let test_file = std::env::temp_dir().join("test_metadata_lyrics.mp3");
+        std::fs::write(&test_file, b"dummy mp3 content").unwrap();
+
+        let metadata = TrackMetadata {
+            title: Some("Test Song".to_string()),
+            lyrics: Some("[00:01.00]First line".to_string()),
+            ..TrackMetadata::default()
+        };
+        tag_mp3(test_file.to_str().unwrap(), metadata).unwrap();
+
+        let tag = Tag::read_from_path(&test_file).unwrap();
+        let lyrics: Vec<_> = tag.lyrics().collect();
+        assert_eq!(lyrics.len(), 1);
+        assert_eq!(lyrics[0].text, "[00:01.00]First line");
+
+        std::fs::remove_file(&test_file).ok();
+    }
+
+    #[test]
+    fn test_read_mp3_tags_round_trip() {
+        let test_file = std::env::temp_dir().join("test_metadata_read_back.mp3");
+        std::fs::write(&test_file, b"dummy mp3 content").unwrap();
+
+        let metadata = TrackMetadata {
+            title: Some("Hey Jude".to_string()),
+            subtitle: Some("Remastered".to_string()),
+            artist: Some("The Beatles".to_string()),
+            album: Some("Hey Jude".to_string()),
+            year: Some("1968".to_string()),
+            genre: Some("Rock".to_string()),
+            track_number: Some("7/12".to_string()),
+            album_artist: Some("The Beatles".to_string()),
+            label: Some("Apple Records".to_string()),
+            copyright: Some("1968 Apple Corps Ltd".to_string()),
+            release_date: Some("1968-08-26".to_string()),
+            disc_number: Some("1/1".to_string()),
+            isrc: Some("GBAYE6800011".to_string()),
+            ..TrackMetadata::default()
+        };
+        tag_mp3(test_file.to_str().unwrap(), metadata).unwrap();
+
+        let read = read_mp3_tags(test_file.to_str().unwrap()).unwrap();
+        assert_eq!(read.title, Some("Hey Jude".to_string()));
+        assert_eq!(read.subtitle, Some("Remastered".to_string()));
+        assert_eq!(read.artist, Some("The Beatles".to_string()));
+        assert_eq!(read.year, Some("1968".to_string()));
+        assert_eq!(read.genre, Some("Rock".to_string()));
+        assert_eq!(read.track_number, Some("7/12".to_string()));
+        assert_eq!(read.album_artist, Some("The Beatles".to_string()));
+        assert_eq!(read.label, Some("Apple Records".to_string()));
+        assert_eq!(read.copyright, Some("1968 Apple Corps Ltd".to_string()));
+        assert_eq!(read.release_date, Some("1968-08-26".to_string()));
+        assert_eq!(read.disc_number, Some("1/1".to_string()));
+        assert_eq!(read.isrc, Some("GBAYE6800011".to_string()));
+
+        std::fs::remove_file(&test_file).ok();
+    }
+
+    #[test]
+    fn test_read_mp3_tags_nonexistent_file() {
+        let result = read_mp3_tags("/nonexistent/file.mp3");
+        assert!(result.unwrap_err().message.contains("File does not exist"));
     }
 
     #[test]
     fn test_tag_mp3_non_mp3_extension() {
-        let result = tag_mp3("/path/to/file.mp4", TrackMetadata::default());
-        assert!(result.is_err());
+        let test_file = std::env::temp_dir().join("test_metadata_not_mp3.mp4");
+        std::fs::write(&test_file, b"dummy mp4 content").unwrap();
+
+        let result = tag_mp3(test_file.to_str().unwrap(), TrackMetadata::default());
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidInput);
+
+        std::fs::remove_file(&test_file).ok();
     }
 }
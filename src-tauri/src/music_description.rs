@@ -0,0 +1,148 @@
+use crate::metadata::TrackMetadata;
+
+const PROVIDED_BY_PREFIX: &str = "Provided to YouTube by";
+const RELEASED_ON_PREFIX: &str = "Released on:";
+const ARTIST_SEPARATOR: &str = " · ";
+const PHONOGRAM_SYMBOL: char = '℗';
+const COPYRIGHT_SYMBOL: char = '©';
+
+// Auto-generated YouTube Music uploads describe the track as:
+//
+//   Provided to YouTube by <label>
+//
+//   <title> · <artist> · <artist>
+//
+//   <album>
+//
+//   ℗ <year> <owner>
+//
+//   Released on: <yyyy-mm-dd>
+pub fn parse_music_description(description: &str) -> Option<TrackMetadata> {
+    let lines: Vec<&str> = description
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let provided_index = lines
+        .iter()
+        .position(|line| line.starts_with(PROVIDED_BY_PREFIX))?;
+    let mut metadata = TrackMetadata {
+        label: Some(lines[provided_index][PROVIDED_BY_PREFIX.len()..].trim().to_string())
+            .filter(|label| !label.is_empty()),
+        ..TrackMetadata::default()
+    };
+
+    let mut remaining = lines[provided_index + 1..].iter().peekable();
+
+    let credits = remaining.next()?;
+    let mut parts = credits.split(ARTIST_SEPARATOR).map(str::trim);
+    metadata.title = parts.next().filter(|t| !t.is_empty()).map(str::to_string);
+    let artists: Vec<&str> = parts.filter(|a| !a.is_empty()).collect();
+    if !artists.is_empty() {
+        metadata.artist = Some(artists.join(", "));
+    }
+
+    // The album is the next free-form line, if one appears before the copyright notice
+    if let Some(line) = remaining.peek() {
+        if !is_notice_line(line) {
+            metadata.album = Some(line.to_string());
+            remaining.next();
+        }
+    }
+
+    for line in remaining {
+        if let Some(date) = line.strip_prefix(RELEASED_ON_PREFIX) {
+            let date = date.trim();
+            if !date.is_empty() {
+                metadata.release_date = Some(date.to_string());
+                metadata.year = leading_year(date);
+            }
+        } else if metadata.copyright.is_none()
+            && (line.starts_with(PHONOGRAM_SYMBOL) || line.starts_with(COPYRIGHT_SYMBOL))
+        {
+            let notice = line
+                .trim_start_matches([PHONOGRAM_SYMBOL, COPYRIGHT_SYMBOL])
+                .trim();
+            if !notice.is_empty() {
+                if metadata.year.is_none() {
+                    metadata.year = leading_year(notice);
+                }
+                metadata.copyright = Some(notice.to_string());
+            }
+        }
+    }
+
+    Some(metadata)
+}
+
+fn is_notice_line(line: &str) -> bool {
+    line.starts_with(PHONOGRAM_SYMBOL)
+        || line.starts_with(COPYRIGHT_SYMBOL)
+        || line.starts_with(RELEASED_ON_PREFIX)
+        || line.contains(": ")
+}
+
+fn leading_year(value: &str) -> Option<String> {
+    let year: String = value.chars().take(4).collect();
+    if year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()) {
+        Some(year)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPIC_DESCRIPTION: &str = "Provided to YouTube by Universal Music Group\n\nBohemian Rhapsody · Queen\n\nA Night At The Opera\n\n℗ 1975 Queen Productions Ltd\n\nReleased on: 1975-10-31\n\nProducer: Roy Thomas Baker\nComposer Lyricist: Freddie Mercury\n\nAuto-generated by YouTube.";
+
+    #[test]
+    fn test_parse_music_description_full() {
+        let metadata = parse_music_description(TOPIC_DESCRIPTION).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Bohemian Rhapsody"));
+        assert_eq!(metadata.artist.as_deref(), Some("Queen"));
+        assert_eq!(metadata.album.as_deref(), Some("A Night At The Opera"));
+        assert_eq!(metadata.label.as_deref(), Some("Universal Music Group"));
+        assert_eq!(metadata.copyright.as_deref(), Some("1975 Queen Productions Ltd"));
+        assert_eq!(metadata.release_date.as_deref(), Some("1975-10-31"));
+        assert_eq!(metadata.year.as_deref(), Some("1975"));
+    }
+
+    #[test]
+    fn test_parse_music_description_multiple_artists() {
+        let description = "Provided to YouTube by Columbia\n\nOld Town Road (Remix) · Lil Nas X · Billy Ray Cyrus\n\n7\n\n℗ 2019 Lil Nas X, under exclusive license to Columbia Records\n\nReleased on: 2019-05-03";
+        let metadata = parse_music_description(description).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Old Town Road (Remix)"));
+        assert_eq!(metadata.artist.as_deref(), Some("Lil Nas X, Billy Ray Cyrus"));
+        assert_eq!(metadata.album.as_deref(), Some("7"));
+        assert_eq!(metadata.release_date.as_deref(), Some("2019-05-03"));
+    }
+
+    #[test]
+    fn test_parse_music_description_without_album_or_release_date() {
+        let description = "Provided to YouTube by DistroKid\n\nSong · Artist\n\n℗ 2021 Artist\n\nAuto-generated by YouTube.";
+        let metadata = parse_music_description(description).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Song"));
+        assert!(metadata.album.is_none());
+        assert!(metadata.release_date.is_none());
+        assert_eq!(metadata.year.as_deref(), Some("2021"));
+    }
+
+    #[test]
+    fn test_parse_music_description_handles_crlf() {
+        let description = TOPIC_DESCRIPTION.replace('\n', "\r\n");
+        let metadata = parse_music_description(&description).unwrap();
+        assert_eq!(metadata.album.as_deref(), Some("A Night At The Opera"));
+    }
+
+    #[test]
+    fn test_parse_music_description_regular_video() {
+        let description = "Official music video for our new single!\nFollow us on Instagram.";
+        assert!(parse_music_description(description).is_none());
+    }
+}
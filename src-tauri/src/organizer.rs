@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "gui", test))]
+use std::fs;
+#[cfg(any(feature = "gui", test))]
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(any(feature = "gui", test))]
+use crate::error::ErrorKind;
+use crate::error::{AppError, AppResult};
+use crate::filename_template::FilenameTemplate;
+#[cfg(any(feature = "gui", test))]
+use crate::metadata::read_mp3_tags;
+use crate::metadata::TrackMetadata;
+use crate::youtube_client::VideoInfo;
+
+#[cfg(any(feature = "gui", test))]
+const SIDECAR_EXTENSIONS: &[&str] = &["lrc"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OrganizeMode {
+    #[default]
+    Flat,
+    ArtistAlbum,
+    GenreArtist,
+    Custom(String),
+}
+
+impl OrganizeMode {
+    fn directory_template(&self) -> Option<&str> {
+        match self {
+            OrganizeMode::Flat => None,
+            OrganizeMode::ArtistAlbum => Some("{album_artist}/{album}"),
+            OrganizeMode::GenreArtist => Some("{genre}/{artist}"),
+            OrganizeMode::Custom(template) => Some(template.as_str()),
+        }
+    }
+}
+
+#[cfg(any(feature = "gui", test))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrganizedFile {
+    pub from: String,
+    pub to: String,
+}
+
+#[cfg(any(feature = "gui", test))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct OrganizeReport {
+    pub moved: Vec<OrganizedFile>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl OrganizeMode {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.directory_template() {
+            Some(template) => FilenameTemplate::parse(template).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn organized_directory(
+    mode: &OrganizeMode,
+    metadata: &TrackMetadata,
+    video: Option<&VideoInfo>,
+) -> AppResult<PathBuf> {
+    match mode.directory_template() {
+        Some(template) => Ok(FilenameTemplate::parse(template)
+            .map_err(AppError::invalid_input)?
+            .render(metadata, video)),
+        None => Ok(PathBuf::new()),
+    }
+}
+
+#[cfg(any(feature = "gui", test))]
+fn move_with_sidecars(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::rename(from, to).map_err(|e| format!("Failed to move file: {}", e))?;
+
+    for extension in SIDECAR_EXTENSIONS {
+        let sidecar = from.with_extension(extension);
+        if sidecar.is_file() {
+            let _ = fs::rename(&sidecar, to.with_extension(extension));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(any(feature = "gui", test))]
+pub fn organize_folder(library_path: &str, mode: &OrganizeMode) -> AppResult<OrganizeReport> {
+    let root = Path::new(library_path);
+    if !root.is_dir() {
+        return Err(AppError::new(
+            ErrorKind::FileNotFound,
+            format!("Library folder does not exist: {}", library_path),
+        ));
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(root)
+        .map_err(|e| AppError::io("Failed to read library folder", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.eq_ignore_ascii_case("mp3"))
+                    .unwrap_or(false)
+        })
+        .collect();
+    entries.sort();
+
+    let mut report = OrganizeReport::default();
+
+    for file in entries {
+        let file_str = file.to_string_lossy().to_string();
+        let Some(file_name) = file.file_name() else {
+            continue;
+        };
+
+        let metadata = match read_mp3_tags(&file_str) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", file_str, e));
+                continue;
+            }
+        };
+
+        let directory = match organized_directory(mode, &metadata, None) {
+            Ok(directory) => directory,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", file_str, e));
+                continue;
+            }
+        };
+        let destination = root.join(directory).join(file_name);
+
+        if destination == file {
+            continue;
+        }
+        if destination.exists() {
+            report.skipped.push(format!(
+                "{}: destination already exists ({})",
+                file_str,
+                destination.to_string_lossy()
+            ));
+            continue;
+        }
+
+        match move_with_sidecars(&file, &destination) {
+            Ok(()) => report.moved.push(OrganizedFile {
+                from: file_str,
+                to: destination.to_string_lossy().to_string(),
+            }),
+            Err(e) => report.errors.push(format!("{}: {}", file_str, e)),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::tag_mp3;
+
+    fn sample_metadata() -> TrackMetadata {
+        TrackMetadata {
+            title: Some("Bohemian Rhapsody".to_string()),
+            artist: Some("Queen".to_string()),
+            album: Some("A Night at the Opera".to_string()),
+            genre: Some("Rock".to_string()),
+            ..TrackMetadata::default()
+        }
+    }
+
+    fn write_tagged_file(dir: &Path, name: &str, metadata: TrackMetadata) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, b"dummy mp3 content").unwrap();
+        tag_mp3(path.to_str().unwrap(), metadata).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_organized_directory_flat() {
+        let dir = organized_directory(&OrganizeMode::Flat, &sample_metadata(), None).unwrap();
+        assert_eq!(dir, PathBuf::new());
+    }
+
+    #[test]
+    fn test_organized_directory_artist_album() {
+        let dir = organized_directory(&OrganizeMode::ArtistAlbum, &sample_metadata(), None).unwrap();
+        assert_eq!(dir, PathBuf::from("Queen").join("A Night at the Opera"));
+    }
+
+    #[test]
+    fn test_organized_directory_prefers_album_artist() {
+        let metadata = TrackMetadata {
+            album_artist: Some("Various Artists".to_string()),
+            ..sample_metadata()
+        };
+        let dir = organized_directory(&OrganizeMode::ArtistAlbum, &metadata, None).unwrap();
+        assert_eq!(dir, PathBuf::from("Various Artists").join("A Night at the Opera"));
+    }
+
+    #[test]
+    fn test_organized_directory_genre_artist() {
+        let dir = organized_directory(&OrganizeMode::GenreArtist, &sample_metadata(), None).unwrap();
+        assert_eq!(dir, PathBuf::from("Rock").join("Queen"));
+    }
+
+    #[test]
+    fn test_organized_directory_missing_values() {
+        let dir = organized_directory(&OrganizeMode::ArtistAlbum, &TrackMetadata::default(), None)
+            .unwrap();
+        assert_eq!(dir, PathBuf::from("Unknown Artist").join("Unknown Album"));
+    }
+
+    #[test]
+    fn test_organized_directory_custom_template() {
+        let mode = OrganizeMode::Custom("{genre}/{year}".to_string());
+        let metadata = TrackMetadata {
+            year: Some("1975".to_string()),
+            ..sample_metadata()
+        };
+        let dir = organized_directory(&mode, &metadata, None).unwrap();
+        assert_eq!(dir, PathBuf::from("Rock").join("1975"));
+    }
+
+    #[test]
+    fn test_organized_directory_invalid_custom_template() {
+        let mode = OrganizeMode::Custom("{nope}".to_string());
+        assert!(organized_directory(&mode, &sample_metadata(), None).is_err());
+    }
+
+    #[test]
+    fn test_organize_folder_moves_files_and_sidecars() {
+        let root = std::env::temp_dir().join("organize_folder_moves_test");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(&root).unwrap();
+
+        let song = write_tagged_file(&root, "Bohemian Rhapsody.mp3", sample_metadata());
+        fs::write(song.with_extension("lrc"), "[00:00.00]Is this the real life").unwrap();
+        fs::write(root.join("notes.txt"), "not music").unwrap();
+
+        let report = organize_folder(root.to_str().unwrap(), &OrganizeMode::ArtistAlbum).unwrap();
+
+        let expected_dir = root.join("Queen").join("A Night at the Opera");
+        assert_eq!(report.moved.len(), 1);
+        assert!(expected_dir.join("Bohemian Rhapsody.mp3").is_file());
+        assert!(expected_dir.join("Bohemian Rhapsody.lrc").is_file());
+        assert!(!song.exists());
+        assert!(root.join("notes.txt").is_file());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_organize_folder_skips_existing_destination() {
+        let root = std::env::temp_dir().join("organize_folder_skip_test");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("Queen").join("A Night at the Opera")).unwrap();
+        fs::write(
+            root.join("Queen").join("A Night at the Opera").join("Song.mp3"),
+            b"existing",
+        )
+        .unwrap();
+
+        write_tagged_file(&root, "Song.mp3", sample_metadata());
+
+        let report = organize_folder(root.to_str().unwrap(), &OrganizeMode::ArtistAlbum).unwrap();
+
+        assert!(report.moved.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+        assert!(root.join("Song.mp3").is_file());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_organize_folder_records_template_errors_per_file() {
+        let root = std::env::temp_dir().join("organize_folder_template_error_test");
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(&root).unwrap();
+
+        write_tagged_file(&root, "One.mp3", sample_metadata());
+        write_tagged_file(&root, "Two.mp3", sample_metadata());
+
+        let mode = OrganizeMode::Custom("{nope}".to_string());
+        let report = organize_folder(root.to_str().unwrap(), &mode).unwrap();
+
+        assert!(report.moved.is_empty());
+        assert_eq!(report.errors.len(), 2);
+        assert!(report.errors[0].contains("One.mp3"));
+        assert!(root.join("One.mp3").is_file());
+        assert!(root.join("Two.mp3").is_file());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_organize_folder_nonexistent_folder() {
+        let result = organize_folder("/nonexistent/library", &OrganizeMode::ArtistAlbum);
+        assert_eq!(result.unwrap_err().kind, ErrorKind::FileNotFound);
+    }
+}
@@ -0,0 +1,751 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::batch_report::{BatchItemStatus, BatchReportRow};
+use crate::cleanup_rules::apply_cleanup_rules;
+use crate::conflict::{place_file, ConflictPolicy, ConflictResolution};
+use crate::csv_parser::CsvTrackEntry;
+use crate::error::{AppError, AppResult};
+use crate::file_processor::sanitize_filename;
+use crate::filename_template::FilenameTemplate;
+use crate::metadata::{tag_mp3, TrackMetadata};
+use crate::music_description::parse_music_description;
+use crate::organizer::{organized_directory, OrganizeMode};
+use crate::rate_limit;
+use crate::retry::{is_unavailable, RetryAttempt, RetryPolicy};
+use crate::search::{FallbackSearch, SearchBackend, YtDlpSearch};
+use crate::search_scoring::SearchHints;
+use crate::settings::AppSettings;
+use crate::sources::SourceSite;
+use crate::subtitles::{subtitle_file_to_lrc, SubtitleOptions};
+use crate::title_parser::parse_title;
+use crate::youtube_client::{find_subtitle_file, VideoInfo};
+use crate::ytdlp::{ProcessRunner, YtDlp, YtDlpRunner};
+use crate::{
+    extract_video_id, strip_id_suffix, AudioMode, InputType, ProcessItemResult, ProcessedItem,
+};
+
+/// Receives the progress, retry and fallback events of a running item. The app forwards them to
+/// the window; other callers can print or record them.
+pub trait EventSink {
+    fn emit_event(&self, event: &str, payload: serde_json::Value);
+}
+
+/// Everything the frontend passes for one queue item.
+#[derive(Debug, Clone, Default)]
+pub struct ItemRequest {
+    pub target: String,
+    pub output_path: String,
+    pub metadata_override: Option<TrackMetadata>,
+    pub subtitle_options: Option<SubtitleOptions>,
+    pub filename_template: Option<String>,
+    pub video_info: Option<VideoInfo>,
+    pub organize_mode: Option<OrganizeMode>,
+    pub conflict_policy: Option<ConflictPolicy>,
+    /// Set for items that came from a search, so an unavailable video can fall back to the next result.
+    pub search_query: Option<String>,
+    pub search_hints: Option<SearchHints>,
+    pub audio_mode: Option<AudioMode>,
+}
+
+/// One batch entry as the queue runs it: searched for first when there is no link to download.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueItem {
+    pub row_number: usize,
+    /// What the user entered, as shown in reports.
+    pub input: String,
+    /// A YouTube id, or a link from another site that yt-dlp downloads as-is.
+    pub target: Option<String>,
+    pub search_query: Option<String>,
+    pub search_hints: SearchHints,
+    pub metadata_override: Option<TrackMetadata>,
+}
+
+impl QueueItem {
+    /// Takes an item of typed or pasted input, numbered by the line it came from.
+    pub fn from_processed(item: ProcessedItem) -> QueueItem {
+        let target = match item.input_type {
+            InputType::Url => item.video_id.or_else(|| Some(item.processed_query.clone())),
+            InputType::SearchQuery => item.video_id,
+        };
+        let search_query = match item.input_type {
+            InputType::Url => None,
+            InputType::SearchQuery => Some(item.processed_query),
+        };
+        QueueItem {
+            row_number: item.line_number,
+            input: item.original_input,
+            target,
+            search_query,
+            search_hints: SearchHints {
+                isrc: None,
+                duration_seconds: item.duration_seconds,
+            },
+            metadata_override: item.metadata_override,
+        }
+    }
+
+    /// The row reported for this item before it has found anything.
+    pub fn report_row(&self) -> BatchReportRow {
+        BatchReportRow {
+            row_number: self.row_number,
+            query: self.input.clone(),
+            video_id: self
+                .target
+                .clone()
+                .filter(|target| SourceSite::from_url(target).is_none()),
+            video_title: None,
+            match_score: None,
+            status: BatchItemStatus::NotFound,
+            error: None,
+            output_path: None,
+        }
+    }
+
+    /// Takes an entry of an imported track list, whose search query already carries the audio
+    /// mode suffix.
+    pub fn from_csv(entry: CsvTrackEntry) -> QueueItem {
+        let query = entry.search_query.trim().to_string();
+        let metadata = entry.metadata;
+        // Playlist entries can point straight at a video
+        let (target, search_query) = match SourceSite::from_url(&query) {
+            Some(_) => (
+                Some(extract_video_id(&query).unwrap_or(query.clone())),
+                None,
+            ),
+            None => (None, Some(query.clone())),
+        };
+        QueueItem {
+            row_number: entry.row_number,
+            input: query,
+            target,
+            search_query,
+            search_hints: SearchHints {
+                isrc: metadata.isrc.clone(),
+                duration_seconds: entry.duration_seconds,
+            },
+            metadata_override: Some(TrackMetadata {
+                title: metadata.track_name,
+                artist: metadata.artist_names,
+                album: metadata.album_name,
+                genre: metadata.artist_genres,
+                year: metadata.album_release_date,
+                track_number: metadata.track_number,
+                disc_number: metadata.disc_number,
+                album_artist: metadata.album_artist_names,
+                label: metadata.label,
+                isrc: metadata.isrc,
+                ..TrackMetadata::default()
+            }),
+        }
+    }
+}
+
+/// The report row of a finished queue item, plus the error behind it when it failed.
+#[derive(Debug, Clone)]
+pub struct QueueOutcome {
+    pub row: BatchReportRow,
+    pub error: Option<AppError>,
+}
+
+impl QueueOutcome {
+    pub fn failed(mut row: BatchReportRow, error: AppError) -> QueueOutcome {
+        row.status = BatchItemStatus::Failed;
+        row.error = Some(error.to_string());
+        QueueOutcome {
+            row,
+            error: Some(error),
+        }
+    }
+}
+
+pub fn retry_event(stage: &str, target: &str, attempt: &RetryAttempt) -> serde_json::Value {
+    serde_json::json!({
+        "stage": stage,
+        "target": target,
+        "attempt": attempt.attempt,
+        "max_attempts": attempt.max_attempts,
+        "kind": attempt.kind,
+        "message": attempt.message,
+        "delay_ms": attempt.delay_ms
+    })
+}
+
+struct DownloadOutcome {
+    path: String,
+    video_info: Option<VideoInfo>,
+    attempts: u32,
+}
+
+struct FallbackSource<'a, R: YtDlpRunner> {
+    search: &'a FallbackSearch<YtDlpSearch<'a, R>>,
+    query: &'a str,
+    hints: &'a SearchHints,
+}
+
+/// Download, rename and tag, independent of the window that started it.
+pub struct Pipeline<'a, R: YtDlpRunner = ProcessRunner> {
+    ytdlp: &'a YtDlp<R>,
+    ffmpeg_path: &'a str,
+    settings: &'a AppSettings,
+}
+
+impl<'a, R: YtDlpRunner> Pipeline<'a, R> {
+    pub fn new(ytdlp: &'a YtDlp<R>, ffmpeg_path: &'a str, settings: &'a AppSettings) -> Self {
+        Pipeline {
+            ytdlp,
+            ffmpeg_path,
+            settings,
+        }
+    }
+
+    pub async fn process_item(
+        &self,
+        request: ItemRequest,
+        events: &impl EventSink,
+    ) -> AppResult<ProcessItemResult> {
+        let settings = self.settings;
+        let search = FallbackSearch::from_settings(
+            self.ytdlp,
+            &settings.search,
+            request.audio_mode.as_ref(),
+        );
+        let search_hints = request.search_hints.unwrap_or_default();
+        let fallback = request.search_query.as_deref().map(|query| FallbackSource {
+            search: &search,
+            query,
+            hints: &search_hints,
+        });
+        let subtitle_options = request
+            .subtitle_options
+            .or_else(|| settings.subtitles.clone());
+
+        // 1. Download
+        let DownloadOutcome {
+            path: downloaded_path,
+            video_info,
+            attempts,
+        } = self
+            .download_with_retry(
+                events,
+                &settings.retry,
+                fallback,
+                request.target,
+                request.video_info,
+                &request.output_path,
+                subtitle_options.as_ref(),
+            )
+            .await?;
+
+        // Subtitles are written next to the download; convert them before the audio is renamed
+        let lrc_lyrics = match (&subtitle_options, find_subtitle_file(&downloaded_path)) {
+            (Some(_), Some(subtitle_path)) => {
+                let lrc = subtitle_file_to_lrc(&subtitle_path).ok();
+                let _ = fs::remove_file(&subtitle_path);
+                lrc.filter(|l| !l.is_empty())
+            }
+            _ => None,
+        };
+
+        // 2. Clean Filename
+        let path = Path::new(&downloaded_path);
+        let file_stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+
+        let stem_without_id = strip_id_suffix(file_stem);
+
+        let cleanup = apply_cleanup_rules(&stem_without_id, &settings.cleanup_rules);
+        let cleaned_stem = cleanup.display_name();
+
+        // 3. Resolve metadata before naming so templates can use it.
+        // YouTube Music descriptions are authoritative, so they win over anything guessed from the title.
+        let described = video_info
+            .as_ref()
+            .and_then(|v| v.description.as_deref())
+            .and_then(parse_music_description)
+            .unwrap_or_default();
+        let mut final_metadata = request
+            .metadata_override
+            .unwrap_or_default()
+            .with_fallback(described);
+
+        // Infer metadata if not provided
+        if final_metadata.title.is_none() {
+            let uploader = video_info.as_ref().and_then(|v| v.uploader.as_deref());
+            let inferred = parse_title(&cleanup.title, uploader);
+            final_metadata.title = Some(inferred.title);
+
+            if final_metadata.artist.is_none() {
+                final_metadata.artist = inferred.artist;
+            }
+        }
+        if final_metadata.subtitle.is_none() {
+            final_metadata.subtitle = cleanup.version();
+        }
+
+        let organize_mode = request
+            .organize_mode
+            .unwrap_or_else(|| settings.organize_mode.clone());
+        let directory = organized_directory(&organize_mode, &final_metadata, video_info.as_ref())?;
+        let template = request
+            .filename_template
+            .or_else(|| settings.filename_template.clone());
+        let file_path = match template.as_deref().filter(|t| !t.trim().is_empty()) {
+            Some(template) => FilenameTemplate::parse(template)
+                .map_err(AppError::invalid_input)?
+                .render_file(&final_metadata, video_info.as_ref(), &cleaned_stem),
+            None => PathBuf::from(sanitize_filename(&cleaned_stem)),
+        };
+        let mut new_filename = directory.join(file_path).into_os_string();
+        new_filename.push(".mp3");
+        let new_path = path
+            .parent()
+            .ok_or_else(|| AppError::internal("Invalid path"))?
+            .join(new_filename);
+
+        // Rename/Move, resolving clashes with files that are already there
+        let policy = request.conflict_policy.unwrap_or(settings.conflict_policy);
+        let placement = place_file(path, &new_path, policy)?;
+        let final_path_str = placement.path.clone();
+        let result = ProcessItemResult {
+            output_path: placement.path,
+            conflict: placement.resolution,
+            conflicting_path: placement.conflicting_path,
+            attempts,
+            video_info,
+        };
+
+        if matches!(
+            result.conflict,
+            ConflictResolution::Skipped | ConflictResolution::KeptExisting
+        ) {
+            return Ok(result);
+        }
+
+        if let (Some(options), Some(lrc)) = (&subtitle_options, lrc_lyrics) {
+            if options.write_sidecar {
+                let sidecar_path = Path::new(&final_path_str).with_extension("lrc");
+                fs::write(&sidecar_path, &lrc)
+                    .map_err(|e| AppError::io("Failed to write lyrics file", e))?;
+            }
+            if options.embed && final_metadata.lyrics.is_none() {
+                final_metadata.lyrics = Some(lrc);
+            }
+        }
+
+        // 4. Tagging
+        tag_mp3(&final_path_str, final_metadata)?;
+
+        Ok(result)
+    }
+
+    /// Runs a batch entry the way the app's queue does. Failures are recorded in the outcome
+    /// rather than returned, so the rest of a batch can carry on.
+    pub async fn run_queue_item(
+        &self,
+        item: QueueItem,
+        output_path: &str,
+        audio_mode: Option<&AudioMode>,
+        events: &impl EventSink,
+    ) -> QueueOutcome {
+        let mut row = item.report_row();
+        match self
+            .queue_item(item, output_path, audio_mode, events, &mut row)
+            .await
+        {
+            Ok(()) => QueueOutcome { row, error: None },
+            Err(e) => QueueOutcome::failed(row, e),
+        }
+    }
+
+    async fn queue_item(
+        &self,
+        item: QueueItem,
+        output_path: &str,
+        audio_mode: Option<&AudioMode>,
+        events: &impl EventSink,
+        row: &mut BatchReportRow,
+    ) -> AppResult<()> {
+        let mut target = item.target;
+        let mut video_info = None;
+
+        if let (None, Some(query)) = (&target, item.search_query.as_deref()) {
+            let search =
+                FallbackSearch::from_settings(self.ytdlp, &self.settings.search, audio_mode);
+            video_info = self
+                .settings
+                .retry
+                .run(
+                    |attempt| {
+                        events.emit_event("item-retry", retry_event("search", query, attempt));
+                    },
+                    |_| search.search(query, &item.search_hints),
+                )
+                .await?;
+            if let Some(info) = &video_info {
+                target = Some(info.id.clone());
+                row.video_id = Some(info.id.clone());
+                row.video_title = Some(info.title.clone());
+                row.match_score = info.match_score;
+            }
+        }
+
+        let Some(target) = target else {
+            return Ok(());
+        };
+        let request = ItemRequest {
+            target,
+            output_path: output_path.to_string(),
+            metadata_override: item.metadata_override,
+            video_info,
+            search_query: item.search_query,
+            search_hints: Some(item.search_hints),
+            audio_mode: audio_mode.cloned(),
+            ..ItemRequest::default()
+        };
+        let processed = self.process_item(request, events).await?;
+
+        // A fallback to another search result replaces the video picked above
+        if let Some(info) = processed
+            .video_info
+            .as_ref()
+            .filter(|info| row.video_id.as_deref() != Some(info.id.as_str()))
+        {
+            row.video_id = Some(info.id.clone());
+            row.video_title = Some(info.title.clone());
+            row.match_score = info.match_score;
+        }
+        row.output_path = Some(processed.output_path);
+        row.status = match processed.conflict {
+            ConflictResolution::Skipped | ConflictResolution::KeptExisting => {
+                BatchItemStatus::Deduplicated
+            }
+            _ => BatchItemStatus::Downloaded,
+        };
+        Ok(())
+    }
+
+    // Retries transient failures; when the video itself can't be downloaded and the item came from a
+    // search, moves on to the next-best candidate that hasn't been tried yet
+    #[allow(clippy::too_many_arguments)]
+    async fn download_with_retry(
+        &self,
+        events: &impl EventSink,
+        retry: &RetryPolicy,
+        fallback: Option<FallbackSource<'_, R>>,
+        mut target: String,
+        mut video_info: Option<VideoInfo>,
+        output_path: &str,
+        subtitle_options: Option<&SubtitleOptions>,
+    ) -> AppResult<DownloadOutcome> {
+        let mut attempts = 0;
+        let mut tried: Vec<String> = Vec::new();
+        let mut candidates: Option<Vec<VideoInfo>> = None;
+
+        loop {
+            let current = target.as_str();
+            let result = retry
+                .run(
+                    |attempt| {
+                        events.emit_event("item-retry", retry_event("download", current, attempt));
+                    },
+                    |attempt| {
+                        attempts += 1;
+                        async move {
+                            let permit = rate_limit::global().start_download().await;
+                            self.ytdlp
+                                .download(
+                                    current,
+                                    output_path,
+                                    Some(self.ffmpeg_path),
+                                    subtitle_options,
+                                    permit.limit_rate(),
+                                    |progress, message| {
+                                        events.emit_event(
+                                            "download-progress",
+                                            serde_json::json!({
+                                                "progress": progress,
+                                                "message": message,
+                                                "attempt": attempt
+                                            }),
+                                        );
+                                    },
+                                )
+                                .await
+                        }
+                    },
+                )
+                .await;
+
+            let error = match result {
+                Ok(path) => {
+                    return Ok(DownloadOutcome {
+                        path,
+                        video_info,
+                        attempts,
+                    })
+                }
+                Err(error) => error,
+            };
+            let source = match &fallback {
+                Some(source) if retry.fallback_to_next_candidate && is_unavailable(error.kind) => {
+                    source
+                }
+                _ => return Err(error),
+            };
+
+            tried.push(target.clone());
+            if candidates.is_none() {
+                let found = retry
+                    .run(
+                        |attempt| {
+                            events.emit_event(
+                                "item-retry",
+                                retry_event("search", source.query, attempt),
+                            );
+                        },
+                        |_| source.search.candidates(source.query, source.hints),
+                    )
+                    .await;
+                // A failed re-search shouldn't hide why the original download failed
+                candidates = Some(found.unwrap_or_default());
+            }
+
+            let next = candidates.as_mut().and_then(|c| {
+                c.iter()
+                    .position(|v| !tried.contains(&v.id))
+                    .map(|i| c.remove(i))
+            });
+            let next = match next {
+                Some(next) => next,
+                None => return Err(error),
+            };
+
+            events.emit_event(
+                "item-fallback",
+                serde_json::json!({
+                    "from": target,
+                    "to": next.id,
+                    "kind": error.kind,
+                    "message": error.message
+                }),
+            );
+            target = next.id.clone();
+            video_info = Some(next);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::{FakeTools, RecordedEvents};
+    use id3::TagLike;
+    use serde_json::json;
+    use crate::test_support::block_on;
+
+    fn settings() -> AppSettings {
+        AppSettings {
+            retry: RetryPolicy {
+                initial_delay_ms: 0,
+                max_delay_ms: 0,
+                ..RetryPolicy::default()
+            },
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_search_download_rename_and_tag() {
+        let tools = FakeTools::new("pipeline_end_to_end");
+        tools.add_search_result(json!({
+            "id": "fJ9rUzIMcZQ",
+            "title": "Queen - Bohemian Rhapsody (Official Video)",
+            "uploader": "Queen Official",
+            "duration": 355
+        }));
+        let settings = settings();
+        let ytdlp = YtDlp::new(&tools.ytdlp_path(), &settings.ytdlp);
+        let ffmpeg = tools.ffmpeg_path();
+        let events = RecordedEvents::default();
+
+        let search = FallbackSearch::from_settings(&ytdlp, &settings.search, None);
+        let found = block_on(search.search("queen bohemian rhapsody", &SearchHints::default()))
+            .unwrap()
+            .unwrap();
+        let request = ItemRequest {
+            target: found.id.clone(),
+            output_path: tools.output_dir().to_string_lossy().into_owned(),
+            video_info: Some(found),
+            search_query: Some("queen bohemian rhapsody".to_string()),
+            ..ItemRequest::default()
+        };
+        let result =
+            block_on(Pipeline::new(&ytdlp, &ffmpeg, &settings).process_item(request, &events))
+                .unwrap();
+
+        let output = Path::new(&result.output_path);
+        assert_eq!(
+            output,
+            tools.output_dir().join("Queen - Bohemian Rhapsody.mp3")
+        );
+        assert_eq!(result.attempts, 1);
+        assert_eq!(result.conflict, ConflictResolution::NoConflict);
+        let tag = id3::Tag::read_from_path(output).unwrap();
+        assert_eq!(tag.title(), Some("Bohemian Rhapsody"));
+        assert_eq!(tag.artist(), Some("Queen"));
+
+        let progress = events.named("download-progress");
+        assert_eq!(progress.last().unwrap()["progress"], 100.0);
+        let calls = tools.ytdlp_calls();
+        assert_eq!(calls.len(), 2);
+        assert!(calls[1].contains(&format!("--ffmpeg-location {}", ffmpeg)));
+        let conversions = tools.ffmpeg_calls();
+        assert_eq!(conversions.len(), 1);
+        assert!(conversions[0].contains("[fJ9rUzIMcZQ].webm"));
+        assert!(!tools
+            .output_dir()
+            .join("Queen - Bohemian Rhapsody (Official Video) [fJ9rUzIMcZQ].webm")
+            .exists());
+    }
+
+    #[test]
+    fn test_unavailable_video_falls_back_to_next_candidate() {
+        let tools = FakeTools::new("pipeline_fallback");
+        tools.add_search_result(
+            json!({ "id": "removed0001", "title": "Daft Punk - One More Time" }),
+        );
+        tools.add_search_result(
+            json!({ "id": "working0002", "title": "Daft Punk - One More Time (Audio)" }),
+        );
+        tools.make_unavailable("removed0001");
+        let settings = settings();
+        let ytdlp = YtDlp::new(&tools.ytdlp_path(), &settings.ytdlp);
+        let ffmpeg = tools.ffmpeg_path();
+        let events = RecordedEvents::default();
+
+        let request = ItemRequest {
+            target: "removed0001".to_string(),
+            output_path: tools.output_dir().to_string_lossy().into_owned(),
+            search_query: Some("daft punk one more time".to_string()),
+            ..ItemRequest::default()
+        };
+        let result =
+            block_on(Pipeline::new(&ytdlp, &ffmpeg, &settings).process_item(request, &events))
+                .unwrap();
+
+        assert_eq!(result.attempts, 2);
+        assert_eq!(result.video_info.unwrap().id, "working0002");
+        assert!(Path::new(&result.output_path).exists());
+        let fallbacks = events.named("item-fallback");
+        assert_eq!(fallbacks.len(), 1);
+        assert_eq!(fallbacks[0]["from"], "removed0001");
+        assert_eq!(fallbacks[0]["kind"], "video_unavailable");
+    }
+
+    #[test]
+    fn test_unavailable_video_without_search_fails() {
+        let tools = FakeTools::new("pipeline_unavailable");
+        tools.make_unavailable("removed0001");
+        let settings = settings();
+        let ytdlp = YtDlp::new(&tools.ytdlp_path(), &settings.ytdlp);
+        let ffmpeg = tools.ffmpeg_path();
+
+        let request = ItemRequest {
+            target: "removed0001".to_string(),
+            output_path: tools.output_dir().to_string_lossy().into_owned(),
+            ..ItemRequest::default()
+        };
+        let error = block_on(
+            Pipeline::new(&ytdlp, &ffmpeg, &settings)
+                .process_item(request, &RecordedEvents::default()),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, crate::error::ErrorKind::VideoUnavailable);
+        assert_eq!(tools.ytdlp_calls().len(), 1);
+    }
+
+    #[test]
+    fn test_existing_file_is_kept_with_skip_policy() {
+        let tools = FakeTools::new("pipeline_conflict");
+        tools.add_search_result(json!({ "id": "abcdefghijk", "title": "Artist - Song" }));
+        fs::create_dir_all(tools.output_dir()).unwrap();
+        let existing = tools.output_dir().join("Artist - Song.mp3");
+        fs::write(&existing, b"already here").unwrap();
+        let settings = settings();
+        let ytdlp = YtDlp::new(&tools.ytdlp_path(), &settings.ytdlp);
+        let ffmpeg = tools.ffmpeg_path();
+
+        let request = ItemRequest {
+            target: "abcdefghijk".to_string(),
+            output_path: tools.output_dir().to_string_lossy().into_owned(),
+            conflict_policy: Some(ConflictPolicy::Skip),
+            ..ItemRequest::default()
+        };
+        let result = block_on(
+            Pipeline::new(&ytdlp, &ffmpeg, &settings)
+                .process_item(request, &RecordedEvents::default()),
+        )
+        .unwrap();
+
+        assert_eq!(result.conflict, ConflictResolution::Skipped);
+        assert_eq!(fs::read(&existing).unwrap(), b"already here");
+    }
+
+    #[test]
+    fn test_queued_items_follow_the_naming_and_conflict_settings() {
+        let tools = FakeTools::new("pipeline_queue_settings");
+        tools.add_search_result(json!({ "id": "abcdefghijk", "title": "Artist - Song" }));
+        tools.add_search_result(json!({ "id": "bcdefghijkl", "title": "Artist - Other Song" }));
+        let existing = tools.output_dir().join("Artist").join("Song.mp3");
+        fs::create_dir_all(existing.parent().unwrap()).unwrap();
+        fs::write(&existing, b"already here").unwrap();
+        let settings = AppSettings {
+            filename_template: Some("{artist}/{title}".to_string()),
+            conflict_policy: ConflictPolicy::Skip,
+            ..settings()
+        };
+        let ytdlp = YtDlp::new(&tools.ytdlp_path(), &settings.ytdlp);
+        let ffmpeg = tools.ffmpeg_path();
+        let pipeline = Pipeline::new(&ytdlp, &ffmpeg, &settings);
+        let output = tools.output_dir().to_string_lossy().into_owned();
+        let item = |row_number: usize, id: &str| QueueItem {
+            row_number,
+            input: id.to_string(),
+            target: Some(id.to_string()),
+            ..QueueItem::default()
+        };
+
+        let skipped = block_on(pipeline.run_queue_item(
+            item(1, "abcdefghijk"),
+            &output,
+            None,
+            &RecordedEvents::default(),
+        ));
+        let downloaded = block_on(pipeline.run_queue_item(
+            item(2, "bcdefghijkl"),
+            &output,
+            None,
+            &RecordedEvents::default(),
+        ));
+
+        assert_eq!(skipped.row.status, BatchItemStatus::Deduplicated);
+        assert_eq!(fs::read(&existing).unwrap(), b"already here");
+        assert_eq!(downloaded.row.status, BatchItemStatus::Downloaded);
+        assert_eq!(
+            downloaded.row.output_path.as_deref().map(Path::new),
+            Some(
+                tools
+                    .output_dir()
+                    .join("Artist")
+                    .join("Other Song.mp3")
+                    .as_path()
+            )
+        );
+    }
+}
@@ -0,0 +1,757 @@
+use reqwest::Url;
+use roxmltree::{Document, Node, ParsingOptions};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::batch_report::InputError;
+use crate::csv_parser::{
+    parse_csv_content, read_csv_headers, CsvImportResult, CsvTrackEntry, CsvTrackMetadata,
+};
+use crate::csv_profiles::{detect_profile, CsvProfile, DurationUnit};
+use crate::csv_sniffer::{decode_csv_bytes, has_delimited_rows};
+use crate::sources::SourceSite;
+use crate::title_parser::parse_title;
+
+/// One track as read from a playlist file, before a search query is built for it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistEntry {
+    pub metadata: CsvTrackMetadata,
+    pub location: Option<String>,
+}
+
+pub trait PlaylistImporter {
+    fn name(&self) -> &'static str;
+    fn detect(&self, text: &str) -> bool;
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String>;
+
+    /// Reads a whole file. Formats that need the raw bytes, such as CSV for its encoding and
+    /// dialect, override this.
+    fn import(&self, content: &[u8]) -> Result<CsvImportResult, String> {
+        let (text, _, _) = decode_csv_bytes(content);
+        import_with(self, &text)
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn seconds_from_millis(value: &str) -> Option<String> {
+    let millis = value.trim().parse::<u64>().ok()?;
+    Some(((millis + 500) / 1000).to_string())
+}
+
+pub struct M3uImporter;
+
+impl M3uImporter {
+    // "#EXTINF:215 tvg-id="x",Daft Punk - One More Time" -> (Some(215), "Daft Punk - One More Time")
+    fn parse_extinf(info: &str) -> (Option<String>, &str) {
+        let mut in_quotes = false;
+        let comma = info.char_indices().find(|(_, c)| {
+            if *c == '"' {
+                in_quotes = !in_quotes;
+            }
+            *c == ',' && !in_quotes
+        });
+
+        let (attributes, display) = match comma {
+            Some((index, _)) => (&info[..index], info[index + 1..].trim()),
+            None => (info, ""),
+        };
+        let duration = attributes
+            .split_whitespace()
+            .next()
+            .and_then(|d| d.parse::<f64>().ok())
+            .filter(|d| *d > 0.0)
+            .map(|d| (d.round() as u64).to_string());
+
+        (duration, display)
+    }
+}
+
+impl PlaylistImporter for M3uImporter {
+    fn name(&self) -> &'static str {
+        "M3U"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        let line = first_line(text);
+        line.starts_with("#EXTM3U") || line.starts_with("#EXTINF")
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String> {
+        let mut entries = Vec::new();
+        let mut pending = CsvTrackMetadata::default();
+
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(info) = line.strip_prefix("#EXTINF:") {
+                let (duration, display) = M3uImporter::parse_extinf(info);
+                let parsed = parse_title(display, None);
+                pending = CsvTrackMetadata {
+                    artist_names: parsed.artist,
+                    track_name: non_empty(&parsed.title),
+                    duration,
+                    ..CsvTrackMetadata::default()
+                };
+            } else if let Some(album) = line.strip_prefix("#EXTALB:") {
+                pending.album_name = non_empty(album);
+            } else if !line.starts_with('#') {
+                entries.push(PlaylistEntry {
+                    metadata: std::mem::take(&mut pending),
+                    location: Some(line.to_string()),
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+pub struct PlsImporter;
+
+impl PlaylistImporter for PlsImporter {
+    fn name(&self) -> &'static str {
+        "PLS"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        first_line(text).eq_ignore_ascii_case("[playlist]")
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String> {
+        let mut entries: HashMap<usize, PlaylistEntry> = HashMap::new();
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let split = key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len());
+            let Ok(index) = key[split..].parse::<usize>() else {
+                continue;
+            };
+
+            let entry = entries.entry(index).or_default();
+            match &key[..split] {
+                "file" => entry.location = non_empty(value),
+                "title" => {
+                    let parsed = parse_title(value.trim(), None);
+                    entry.metadata.artist_names = parsed.artist;
+                    entry.metadata.track_name = non_empty(&parsed.title);
+                }
+                "length" => {
+                    entry.metadata.duration = value
+                        .trim()
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|d| *d > 0)
+                        .map(|d| d.to_string())
+                }
+                _ => {}
+            }
+        }
+
+        let mut indexes: Vec<usize> = entries.keys().copied().collect();
+        indexes.sort_unstable();
+        Ok(indexes
+            .into_iter()
+            .filter_map(|index| entries.remove(&index))
+            .collect())
+    }
+}
+
+// Apple's plist exports always carry a DOCTYPE, which roxmltree rejects unless asked not to
+fn parse_xml(text: &str) -> Result<Document<'_>, roxmltree::Error> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    Document::parse_with_options(text, options)
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+        .and_then(non_empty)
+}
+
+pub struct XspfImporter;
+
+impl PlaylistImporter for XspfImporter {
+    fn name(&self) -> &'static str {
+        "XSPF"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.contains("<playlist") && text.contains("xspf.org/ns/0")
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String> {
+        let document = parse_xml(text).map_err(|e| format!("Invalid XSPF file: {}", e))?;
+
+        Ok(document
+            .descendants()
+            .filter(|node| node.has_tag_name("track"))
+            .map(|track| PlaylistEntry {
+                metadata: CsvTrackMetadata {
+                    artist_names: child_text(track, "creator"),
+                    track_name: child_text(track, "title"),
+                    album_name: child_text(track, "album"),
+                    track_number: child_text(track, "trackNum"),
+                    duration: child_text(track, "duration").and_then(|d| seconds_from_millis(&d)),
+                    ..CsvTrackMetadata::default()
+                },
+                location: child_text(track, "location"),
+            })
+            .collect())
+    }
+}
+
+pub struct ItunesXmlImporter;
+
+impl ItunesXmlImporter {
+    fn dict_entries<'a, 'input>(dict: Node<'a, 'input>) -> Vec<(String, Node<'a, 'input>)> {
+        let children: Vec<Node> = dict.children().filter(|n| n.is_element()).collect();
+        children
+            .chunks(2)
+            .filter_map(|pair| match pair {
+                [key, value] if key.has_tag_name("key") => {
+                    Some((key.text().unwrap_or("").to_string(), *value))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn dict_value<'a, 'input>(dict: Node<'a, 'input>, key: &str) -> Option<Node<'a, 'input>> {
+        ItunesXmlImporter::dict_entries(dict)
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    fn track_entry(track: Node) -> PlaylistEntry {
+        let fields: HashMap<String, String> = ItunesXmlImporter::dict_entries(track)
+            .into_iter()
+            .filter_map(|(key, value)| value.text().and_then(non_empty).map(|v| (key, v)))
+            .collect();
+        let field = |key: &str| fields.get(key).cloned();
+
+        PlaylistEntry {
+            metadata: CsvTrackMetadata {
+                artist_names: field("Artist"),
+                track_name: field("Name"),
+                album_name: field("Album"),
+                album_artist_names: field("Album Artist"),
+                artist_genres: field("Genre"),
+                album_release_date: field("Year"),
+                track_number: field("Track Number"),
+                disc_number: field("Disc Number"),
+                bpm_tempo: field("BPM"),
+                duration: field("Total Time").and_then(|d| seconds_from_millis(&d)),
+                ..CsvTrackMetadata::default()
+            },
+            location: field("Location"),
+        }
+    }
+}
+
+impl PlaylistImporter for ItunesXmlImporter {
+    fn name(&self) -> &'static str {
+        "iTunes XML"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.contains("<plist") && text.contains("<key>Tracks</key>")
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String> {
+        let document = parse_xml(text).map_err(|e| format!("Invalid iTunes library XML: {}", e))?;
+        let root = document
+            .root_element()
+            .children()
+            .find(|n| n.has_tag_name("dict"))
+            .ok_or("iTunes library XML has no top-level dictionary")?;
+        let tracks = ItunesXmlImporter::dict_value(root, "Tracks")
+            .ok_or("iTunes library XML has no Tracks dictionary")?;
+
+        let library: Vec<(String, Node)> = ItunesXmlImporter::dict_entries(tracks);
+
+        // A playlist export lists its tracks in order under the first playlist; fall back to library order
+        let playlist_order: Option<Vec<String>> = ItunesXmlImporter::dict_value(root, "Playlists")
+            .and_then(|playlists| playlists.children().find(|n| n.has_tag_name("dict")))
+            .and_then(|playlist| ItunesXmlImporter::dict_value(playlist, "Playlist Items"))
+            .map(|items| {
+                items
+                    .children()
+                    .filter(|n| n.has_tag_name("dict"))
+                    .filter_map(|item| ItunesXmlImporter::dict_value(item, "Track ID"))
+                    .filter_map(|id| id.text().map(|t| t.trim().to_string()))
+                    .collect()
+            });
+
+        let entries = match playlist_order {
+            Some(order) => {
+                let by_id: HashMap<&str, Node> = library
+                    .iter()
+                    .map(|(id, track)| (id.as_str(), *track))
+                    .collect();
+                order
+                    .iter()
+                    .filter_map(|id| by_id.get(id.as_str()))
+                    .map(|track| ItunesXmlImporter::track_entry(*track))
+                    .collect()
+            }
+            None => library
+                .iter()
+                .map(|(_, track)| ItunesXmlImporter::track_entry(*track))
+                .collect(),
+        };
+
+        Ok(entries)
+    }
+}
+
+/// A CSV export, read through the user's column mapping profiles.
+pub struct CsvImporter<'a> {
+    pub profiles: &'a [CsvProfile],
+    pub profile_name: Option<&'a str>,
+}
+
+impl PlaylistImporter for CsvImporter<'_> {
+    fn name(&self) -> &'static str {
+        "CSV"
+    }
+
+    // A file with consistent delimiters is a CSV even when no profile knows its columns, so the
+    // user hears about the columns instead of getting the header row as a search
+    fn detect(&self, text: &str) -> bool {
+        if has_delimited_rows(text) {
+            return true;
+        }
+        read_csv_headers(text.as_bytes())
+            .map(|headers| detect_profile(self.profiles, &headers).is_some())
+            .unwrap_or(false)
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String> {
+        let result = parse_csv_content(text.as_bytes(), self.profiles, self.profile_name)?;
+        Ok(result
+            .tracks
+            .into_iter()
+            .map(|track| PlaylistEntry {
+                metadata: track.metadata,
+                location: None,
+            })
+            .collect())
+    }
+
+    fn import(&self, content: &[u8]) -> Result<CsvImportResult, String> {
+        parse_csv_content(content, self.profiles, self.profile_name)
+    }
+}
+
+/// One track per line, as "Artist - Title", a URL or a file path.
+pub struct PlainTextImporter;
+
+impl PlaylistImporter for PlainTextImporter {
+    fn name(&self) -> &'static str {
+        "Text"
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        !first_line(text).is_empty()
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<PlaylistEntry>, String> {
+        Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                if SourceSite::from_url(line).is_some() || looks_like_path(line) {
+                    PlaylistEntry {
+                        metadata: CsvTrackMetadata::default(),
+                        location: Some(line.to_string()),
+                    }
+                } else {
+                    let parsed = parse_title(line, None);
+                    PlaylistEntry {
+                        metadata: CsvTrackMetadata {
+                            artist_names: parsed.artist,
+                            track_name: non_empty(&parsed.title),
+                            ..CsvTrackMetadata::default()
+                        },
+                        location: None,
+                    }
+                }
+            })
+            .collect())
+    }
+}
+
+fn looks_like_path(line: &str) -> bool {
+    line.starts_with("file:")
+        || ((line.contains('/') || line.contains('\\')) && Path::new(line).extension().is_some())
+}
+
+// "file:///Music/Daft%20Punk%20-%20One%20More%20Time.mp3" -> "Daft Punk - One More Time"
+fn location_stem(location: &str) -> Option<String> {
+    let decoded = Url::parse(location)
+        .ok()
+        .filter(|url| url.scheme() == "file")
+        .and_then(|url| url.to_file_path().ok())
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| location.to_string());
+
+    let file_name = decoded.rsplit(['/', '\\']).next().unwrap_or(&decoded);
+    let stem = Path::new(file_name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file_name.to_string());
+    non_empty(&stem)
+}
+
+fn track_entry(position: usize, entry: PlaylistEntry) -> Result<CsvTrackEntry, InputError> {
+    let mut metadata = entry.metadata;
+    let location = entry.location.as_deref();
+
+    // Online locations are downloaded directly; local files only tell us what to search for
+    if let Some(url) = location.filter(|l| SourceSite::from_url(l).is_some()) {
+        return Ok(CsvTrackEntry {
+            row_number: position,
+            duration_seconds: metadata.duration_seconds(Some(DurationUnit::Seconds)),
+            metadata,
+            search_query: url.to_string(),
+        });
+    }
+
+    if metadata.artist_names.is_none() && metadata.track_name.is_none() {
+        if let Some(stem) = location.and_then(location_stem) {
+            let parsed = parse_title(&stem, None);
+            metadata.artist_names = parsed.artist;
+            metadata.track_name = non_empty(&parsed.title);
+        }
+    }
+
+    let search_query = match (&metadata.artist_names, &metadata.track_name) {
+        (Some(artist), Some(track)) => format!("{} - {}", artist, track),
+        (Some(artist), None) => artist.clone(),
+        (None, Some(track)) => track.clone(),
+        (None, None) => {
+            return Err(InputError::new(
+                position,
+                location.unwrap_or_default(),
+                "Missing both artist and track name - cannot create search query",
+            ))
+        }
+    };
+
+    Ok(CsvTrackEntry {
+        row_number: position,
+        duration_seconds: metadata.duration_seconds(Some(DurationUnit::Seconds)),
+        metadata,
+        search_query,
+    })
+}
+
+fn import_with<I: PlaylistImporter + ?Sized>(
+    importer: &I,
+    text: &str,
+) -> Result<CsvImportResult, String> {
+    let mut tracks = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, entry) in importer.parse(text)?.into_iter().enumerate() {
+        match track_entry(index + 1, entry) {
+            Ok(track) => tracks.push(track),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    Ok(CsvImportResult {
+        total_count: tracks.len() + errors.len(),
+        success_count: tracks.len(),
+        error_count: errors.len(),
+        tracks,
+        errors,
+        profile: Some(importer.name().to_string()),
+        format: None,
+    })
+}
+
+// Checked in order; the plain text importer takes anything the others don't
+fn importers(profiles: &[CsvProfile]) -> Vec<Box<dyn PlaylistImporter + '_>> {
+    vec![
+        Box::new(XspfImporter),
+        Box::new(ItunesXmlImporter),
+        Box::new(PlsImporter),
+        Box::new(M3uImporter),
+        Box::new(CsvImporter {
+            profiles,
+            profile_name: None,
+        }),
+        Box::new(PlainTextImporter),
+    ]
+}
+
+/// Imports a track list of any supported format, recognised from its content. Naming a CSV
+/// profile skips detection.
+pub fn import_track_list(
+    content: &[u8],
+    profiles: &[CsvProfile],
+    profile_name: Option<&str>,
+) -> Result<CsvImportResult, String> {
+    if profile_name.is_some() {
+        return CsvImporter {
+            profiles,
+            profile_name,
+        }
+        .import(content);
+    }
+
+    let (text, _, _) = decode_csv_bytes(content);
+    match importers(profiles).iter().find(|i| i.detect(&text)) {
+        Some(importer) => importer.import(content),
+        None => import_with(&PlainTextImporter, &text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_profiles::builtin_csv_profiles;
+
+    fn import(content: &str) -> CsvImportResult {
+        import_track_list(content.as_bytes(), &builtin_csv_profiles(), None).unwrap()
+    }
+
+    #[test]
+    fn test_import_extended_m3u() {
+        let result = import(
+            "#EXTM3U\n\
+             #EXTINF:320,Daft Punk - One More Time\n\
+             /music/Daft Punk/Discovery/01 One More Time.mp3\n\
+             #EXTINF:-1 tvg-name=\"a, b\",Radio Stream\n\
+             https://soundcloud.com/artist/track\n",
+        );
+
+        assert_eq!(result.profile, Some("M3U".to_string()));
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.tracks[0].search_query, "Daft Punk - One More Time");
+        assert_eq!(result.tracks[0].duration_seconds, Some(320));
+        assert_eq!(
+            result.tracks[1].search_query,
+            "https://soundcloud.com/artist/track"
+        );
+        assert_eq!(
+            result.tracks[1].metadata.track_name,
+            Some("Radio Stream".to_string())
+        );
+    }
+
+    #[test]
+    fn test_import_m3u_uses_file_name_without_extinf() {
+        let result = import(
+            "#EXTM3U\nC:\\Music\\Daft Punk - Aerodynamic.mp3\nfile:///home/me/Music/Justice%20-%20D.A.N.C.E.flac\n",
+        );
+
+        assert_eq!(result.tracks[0].search_query, "Daft Punk - Aerodynamic");
+        assert_eq!(result.tracks[1].search_query, "Justice - D.A.N.C.E");
+    }
+
+    #[test]
+    fn test_import_pls() {
+        let result = import(
+            "[playlist]\n\
+             File2=/music/two.mp3\n\
+             Title2=Justice - Genesis\n\
+             File1=/music/one.mp3\n\
+             Title1=Daft Punk - Around the World\n\
+             Length1=429\n\
+             NumberOfEntries=2\n\
+             Version=2\n",
+        );
+
+        assert_eq!(result.profile, Some("PLS".to_string()));
+        assert_eq!(result.tracks.len(), 2);
+        assert_eq!(
+            result.tracks[0].search_query,
+            "Daft Punk - Around the World"
+        );
+        assert_eq!(result.tracks[0].duration_seconds, Some(429));
+        assert_eq!(result.tracks[1].search_query, "Justice - Genesis");
+    }
+
+    #[test]
+    fn test_import_xspf() {
+        let result = import(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <trackList>
+    <track>
+      <location>file:///music/one.mp3</location>
+      <creator>Daft Punk</creator>
+      <title>Digital Love</title>
+      <album>Discovery</album>
+      <trackNum>3</trackNum>
+      <duration>301000</duration>
+    </track>
+    <track>
+      <location>file:///music/unknown.mp3</location>
+    </track>
+  </trackList>
+</playlist>"#,
+        );
+
+        assert_eq!(result.profile, Some("XSPF".to_string()));
+        assert_eq!(result.tracks[0].search_query, "Daft Punk - Digital Love");
+        assert_eq!(
+            result.tracks[0].metadata.album_name,
+            Some("Discovery".to_string())
+        );
+        assert_eq!(
+            result.tracks[0].metadata.track_number,
+            Some("3".to_string())
+        );
+        assert_eq!(result.tracks[0].duration_seconds, Some(301));
+        assert_eq!(result.tracks[1].search_query, "unknown");
+    }
+
+    #[test]
+    fn test_import_itunes_xml_in_playlist_order() {
+        let result = import(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple Computer//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Major Version</key><integer>1</integer>
+  <key>Tracks</key>
+  <dict>
+    <key>101</key>
+    <dict>
+      <key>Track ID</key><integer>101</integer>
+      <key>Name</key><string>Harder, Better, Faster, Stronger</string>
+      <key>Artist</key><string>Daft Punk</string>
+      <key>Album Artist</key><string>Daft Punk</string>
+      <key>Album</key><string>Discovery</string>
+      <key>Disc Number</key><integer>1</integer>
+      <key>Track Number</key><integer>4</integer>
+      <key>Total Time</key><integer>224693</integer>
+      <key>Year</key><integer>2001</integer>
+    </dict>
+    <key>102</key>
+    <dict>
+      <key>Track ID</key><integer>102</integer>
+      <key>Name</key><string>Genesis</string>
+      <key>Artist</key><string>Justice</string>
+    </dict>
+  </dict>
+  <key>Playlists</key>
+  <array>
+    <dict>
+      <key>Name</key><string>Mix</string>
+      <key>Playlist Items</key>
+      <array>
+        <dict><key>Track ID</key><integer>102</integer></dict>
+        <dict><key>Track ID</key><integer>101</integer></dict>
+      </array>
+    </dict>
+  </array>
+</dict>
+</plist>"#,
+        );
+
+        assert_eq!(result.profile, Some("iTunes XML".to_string()));
+        assert_eq!(result.tracks[0].search_query, "Justice - Genesis");
+
+        let track = &result.tracks[1];
+        assert_eq!(
+            track.search_query,
+            "Daft Punk - Harder, Better, Faster, Stronger"
+        );
+        assert_eq!(
+            track.metadata.album_artist_names,
+            Some("Daft Punk".to_string())
+        );
+        assert_eq!(track.metadata.disc_number, Some("1".to_string()));
+        assert_eq!(track.metadata.album_release_date, Some("2001".to_string()));
+        assert_eq!(track.duration_seconds, Some(225));
+    }
+
+    #[test]
+    fn test_import_plain_text_list() {
+        let result = import(
+            "Daft Punk - Veridis Quo\n\nhttps://www.youtube.com/watch?v=dQw4w9WgXcQ\nJust A Title\n",
+        );
+
+        assert_eq!(result.profile, Some("Text".to_string()));
+        assert_eq!(result.tracks.len(), 3);
+        assert_eq!(
+            result.tracks[0].metadata.artist_names,
+            Some("Daft Punk".to_string())
+        );
+        assert_eq!(
+            result.tracks[1].search_query,
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        );
+        assert_eq!(result.tracks[2].search_query, "Just A Title");
+    }
+
+    #[test]
+    fn test_import_csv_is_still_detected() {
+        let result = import("Artist Name(s),Track Name\nThe Beatles,Hey Jude\n");
+
+        assert_eq!(result.profile, Some("Exportify".to_string()));
+        assert!(result.format.is_some());
+    }
+
+    #[test]
+    fn test_import_unrecognised_csv_reports_columns() {
+        let error =
+            import_track_list(b"Foo,Bar\n1,2\n", &builtin_csv_profiles(), None).unwrap_err();
+
+        assert!(error.contains("Could not recognise the CSV columns"));
+    }
+
+    #[test]
+    fn test_import_single_column_csv_is_detected() {
+        let result = import("Track Name\nHey Jude\n");
+
+        assert_eq!(result.profile, Some("Exportify".to_string()));
+        assert_eq!(result.tracks.len(), 1);
+        assert_eq!(result.tracks[0].search_query, "Hey Jude");
+    }
+
+    #[test]
+    fn test_import_text_with_commas_is_not_csv() {
+        let result = import("Daft Punk - One More Time\nEarth, Wind & Fire - September\n");
+
+        assert_eq!(result.profile, Some("Text".to_string()));
+        assert_eq!(
+            result.tracks[1].search_query,
+            "Earth, Wind & Fire - September"
+        );
+    }
+
+    #[test]
+    fn test_import_named_profile_skips_playlist_detection() {
+        let result = import_track_list(
+            b"Track Name\n#EXTM3U\n",
+            &builtin_csv_profiles(),
+            Some("Exportify"),
+        )
+        .unwrap();
+
+        assert_eq!(result.profile, Some("Exportify".to_string()));
+        assert_eq!(result.tracks[0].search_query, "#EXTM3U");
+    }
+}
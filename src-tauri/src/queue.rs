@@ -0,0 +1,471 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::{broadcast, Notify};
+
+use crate::batch_report::{BatchItemStatus, BatchReportRow};
+use crate::error::{AppError, AppResult};
+use crate::ffmpeg_setup::{check_ffmpeg, download_ffmpeg};
+use crate::pipeline::{EventSink, Pipeline, QueueItem, QueueOutcome};
+use crate::rate_limit;
+use crate::settings::{load_settings, settings_path};
+use crate::ytdlp::YtDlp;
+use crate::ytdlp_setup::get_ytdlp_command;
+use crate::AudioMode;
+
+// Finished items kept for the history endpoint; older ones are dropped
+const HISTORY_LIMIT: usize = 500;
+const EVENT_BUFFER: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    Queued,
+    Running,
+    Downloaded,
+    Deduplicated,
+    NotFound,
+    Failed,
+    Cancelled,
+}
+
+impl From<BatchItemStatus> for QueueStatus {
+    fn from(status: BatchItemStatus) -> Self {
+        match status {
+            BatchItemStatus::Downloaded => QueueStatus::Downloaded,
+            BatchItemStatus::Deduplicated => QueueStatus::Deduplicated,
+            BatchItemStatus::NotFound => QueueStatus::NotFound,
+            BatchItemStatus::Failed => QueueStatus::Failed,
+        }
+    }
+}
+
+/// Who added an item, so the app can tell its own batches from remote requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueSource {
+    App,
+    Http,
+    WatchFolder,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueEntry {
+    pub id: u64,
+    pub source: QueueSource,
+    pub item: QueueItem,
+    pub output_path: String,
+    pub audio_mode: AudioMode,
+    pub status: QueueStatus,
+    /// Filled in once the item has run.
+    pub report: Option<BatchReportRow>,
+}
+
+impl QueueEntry {
+    /// The entry's report, with items cancelled before they finished reported as failed.
+    pub fn report_row(&self) -> BatchReportRow {
+        self.report.clone().unwrap_or_else(|| {
+            let mut row = self.item.report_row();
+            row.status = BatchItemStatus::Failed;
+            row.error = Some("Cancelled".to_string());
+            row
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueSnapshot {
+    pub running: Option<QueueEntry>,
+    pub queued: Vec<QueueEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueEvent {
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_id: u64,
+    queued: VecDeque<QueueEntry>,
+    running: Option<(QueueEntry, Arc<Notify>)>,
+    history: VecDeque<QueueEntry>,
+}
+
+/// The download queue shared by the window and the local HTTP server. Items run one at a time
+/// in the order they were added, and every change is published as an event.
+pub struct DownloadQueue {
+    state: Mutex<QueueState>,
+    wake: Notify,
+    events: broadcast::Sender<QueueEvent>,
+}
+
+#[cfg(feature = "gui")]
+pub fn global() -> &'static DownloadQueue {
+    static QUEUE: std::sync::OnceLock<DownloadQueue> = std::sync::OnceLock::new();
+    QUEUE.get_or_init(DownloadQueue::new)
+}
+
+impl DownloadQueue {
+    pub fn new() -> DownloadQueue {
+        DownloadQueue {
+            state: Mutex::new(QueueState::default()),
+            wake: Notify::new(),
+            events: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn enqueue(
+        &self,
+        items: Vec<QueueItem>,
+        output_path: &str,
+        audio_mode: &AudioMode,
+        source: QueueSource,
+    ) -> Vec<u64> {
+        let ids = {
+            let mut state = self.state();
+            items
+                .into_iter()
+                .map(|item| {
+                    state.next_id += 1;
+                    let id = state.next_id;
+                    state.queued.push_back(QueueEntry {
+                        id,
+                        source,
+                        item,
+                        output_path: output_path.to_string(),
+                        audio_mode: audio_mode.clone(),
+                        status: QueueStatus::Queued,
+                        report: None,
+                    });
+                    id
+                })
+                .collect()
+        };
+        self.wake.notify_one();
+        self.publish_snapshot();
+        ids
+    }
+
+    /// Enqueues items and waits until each has finished or been cancelled, returning them in
+    /// the order they were given.
+    pub async fn run_batch(
+        &self,
+        items: Vec<QueueItem>,
+        output_path: &str,
+        audio_mode: &AudioMode,
+        source: QueueSource,
+    ) -> Vec<QueueEntry> {
+        let mut events = self.subscribe();
+        let ids = self.enqueue(items, output_path, audio_mode, source);
+        let mut finished: HashMap<u64, QueueEntry> = HashMap::new();
+
+        while finished.len() < ids.len() {
+            let check_history = match events.recv().await {
+                Ok(event) => event.event == "queue-item-finished",
+                // Missed events may have included ours, which are in the history by now
+                Err(broadcast::error::RecvError::Lagged(_)) => true,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if check_history {
+                let state = self.state();
+                for entry in state.history.iter().filter(|e| ids.contains(&e.id)) {
+                    finished.entry(entry.id).or_insert_with(|| entry.clone());
+                }
+            }
+        }
+        ids.iter().filter_map(|id| finished.remove(id)).collect()
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let state = self.state();
+        QueueSnapshot {
+            running: state.running.as_ref().map(|(entry, _)| entry.clone()),
+            queued: state.queued.iter().cloned().collect(),
+        }
+    }
+
+    /// Finished and cancelled items, newest first.
+    pub fn history(&self, limit: usize) -> Vec<QueueEntry> {
+        self.state().history.iter().take(limit).cloned().collect()
+    }
+
+    /// The finished entries among `ids`, in the order given.
+    pub fn finished(&self, ids: &[u64]) -> Vec<QueueEntry> {
+        let state = self.state();
+        ids.iter()
+            .filter_map(|id| state.history.iter().find(|entry| entry.id == *id))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes a waiting item, or stops the running one. Returns false for unknown or finished ids.
+    pub fn cancel(&self, id: u64) -> bool {
+        let cancelled = {
+            let mut state = self.state();
+            if let Some((_, stop)) = state.running.as_ref().filter(|(entry, _)| entry.id == id) {
+                stop.notify_one();
+                return true;
+            }
+            let position = state.queued.iter().position(|entry| entry.id == id);
+            position.and_then(|index| state.queued.remove(index))
+        };
+        match cancelled {
+            Some(mut entry) => {
+                entry.status = QueueStatus::Cancelled;
+                self.record(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<QueueEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn publish(&self, event: &str, payload: serde_json::Value) {
+        // Nobody listening is fine
+        let _ = self.events.send(QueueEvent {
+            event: event.to_string(),
+            payload,
+        });
+    }
+
+    fn publish_snapshot(&self) {
+        let snapshot = serde_json::to_value(self.snapshot()).unwrap_or_default();
+        self.publish("queue-updated", snapshot);
+    }
+
+    fn publish_entry(&self, event: &str, entry: &QueueEntry) {
+        self.publish(event, serde_json::to_value(entry).unwrap_or_default());
+    }
+
+    // The entry is in the history before anyone hears it finished, so listeners can look it up
+    fn record(&self, entry: QueueEntry) {
+        {
+            let mut state = self.state();
+            state.history.push_front(entry.clone());
+            state.history.truncate(HISTORY_LIMIT);
+        }
+        self.publish_entry("queue-item-finished", &entry);
+        self.publish_snapshot();
+    }
+
+    /// Waits for the next item and marks it running. The returned notifier fires if the item is
+    /// cancelled while it runs.
+    async fn next(&self) -> (QueueEntry, Arc<Notify>) {
+        loop {
+            let started = {
+                let mut state = self.state();
+                state.queued.pop_front().map(|mut entry| {
+                    entry.status = QueueStatus::Running;
+                    let stop = Arc::new(Notify::new());
+                    state.running = Some((entry.clone(), stop.clone()));
+                    (entry, stop)
+                })
+            };
+            if let Some((entry, stop)) = started {
+                self.publish_entry("queue-item-started", &entry);
+                self.publish_snapshot();
+                return (entry, stop);
+            }
+            self.wake.notified().await;
+        }
+    }
+
+    fn finish(&self, mut entry: QueueEntry, outcome: Option<QueueOutcome>) {
+        self.state().running = None;
+        match outcome {
+            Some(outcome) => {
+                entry.status = outcome.row.status.into();
+                entry.report = Some(outcome.row);
+            }
+            None => entry.status = QueueStatus::Cancelled,
+        }
+        self.record(entry);
+    }
+}
+
+impl Default for DownloadQueue {
+    fn default() -> Self {
+        DownloadQueue::new()
+    }
+}
+
+/// Publishes the pipeline's events for one item, tagged with its queue id.
+struct EntryEvents<'a> {
+    queue: &'a DownloadQueue,
+    id: u64,
+}
+
+impl EventSink for EntryEvents<'_> {
+    fn emit_event(&self, event: &str, mut payload: serde_json::Value) {
+        if let Some(fields) = payload.as_object_mut() {
+            fields.insert("id".to_string(), self.id.into());
+        }
+        self.queue.publish(event, payload);
+    }
+}
+
+async fn run_entry(queue: &DownloadQueue, data_dir: &Path, entry: &QueueEntry) -> QueueOutcome {
+    match try_run_entry(queue, data_dir, entry).await {
+        Ok(outcome) => outcome,
+        Err(e) => QueueOutcome::failed(entry.item.report_row(), e),
+    }
+}
+
+async fn try_run_entry(
+    queue: &DownloadQueue,
+    data_dir: &Path,
+    entry: &QueueEntry,
+) -> AppResult<QueueOutcome> {
+    // Settings and tools are looked up per item, so changes apply to the rest of the queue
+    let settings = load_settings(&settings_path(data_dir))?;
+    rate_limit::global().configure(&settings.rate_limits);
+    let ytdlp_path = get_ytdlp_command(data_dir)?;
+    let ffmpeg_path = match check_ffmpeg(data_dir) {
+        Ok(p) => p,
+        Err(_) => download_ffmpeg(data_dir).await.map_err(|e| AppError {
+            message: format!(
+                "FFmpeg is required but couldn't be downloaded: {}. Please install FFmpeg and try again.",
+                e
+            ),
+            ..e
+        })?,
+    };
+    let ytdlp = YtDlp::new(&ytdlp_path, &settings.ytdlp);
+    let events = EntryEvents {
+        queue,
+        id: entry.id,
+    };
+    Ok(Pipeline::new(&ytdlp, &ffmpeg_path, &settings)
+        .run_queue_item(
+            entry.item.clone(),
+            &entry.output_path,
+            Some(&entry.audio_mode),
+            &events,
+        )
+        .await)
+}
+
+/// Runs queued items one after another for as long as the app is open.
+pub async fn run_worker(queue: &DownloadQueue, data_dir: &Path) {
+    loop {
+        let (entry, stop) = queue.next().await;
+        // Dropping the download stops yt-dlp, which is killed with its future
+        let outcome = tokio::select! {
+            outcome = run_entry(queue, data_dir, &entry) => Some(outcome),
+            _ = stop.notified() => None,
+        };
+        queue.finish(entry, outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::block_on;
+
+    fn item(input: &str) -> QueueItem {
+        QueueItem {
+            input: input.to_string(),
+            search_query: Some(format!("{} official audio", input)),
+            ..QueueItem::default()
+        }
+    }
+
+    #[test]
+    fn test_enqueue_runs_items_in_order() {
+        let queue = DownloadQueue::new();
+        let mut events = queue.subscribe();
+        let ids = queue.enqueue(
+            vec![item("Song One"), item("Song Two")],
+            "/music",
+            &AudioMode::Official,
+            QueueSource::Http,
+        );
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(events.try_recv().unwrap().event, "queue-updated");
+
+        let (entry, _) = block_on(queue.next());
+        assert_eq!(entry.id, 1);
+        assert_eq!(entry.status, QueueStatus::Running);
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot.running.map(|e| e.id), Some(1));
+        assert_eq!(snapshot.queued.len(), 1);
+        assert_eq!(snapshot.queued[0].source, QueueSource::Http);
+
+        let mut row = entry.item.report_row();
+        row.status = BatchItemStatus::Downloaded;
+        queue.finish(entry, Some(QueueOutcome { row, error: None }));
+
+        let history = queue.history(10);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, QueueStatus::Downloaded);
+        assert!(queue.snapshot().running.is_none());
+    }
+
+    #[test]
+    fn test_cancel_queued_and_running_items() {
+        let queue = DownloadQueue::new();
+        queue.enqueue(
+            vec![item("Song One"), item("Song Two"), item("Song Three")],
+            "/music",
+            &AudioMode::Clean,
+            QueueSource::App,
+        );
+
+        assert!(queue.cancel(2));
+        assert!(!queue.cancel(2));
+        assert!(!queue.cancel(42));
+        assert_eq!(queue.history(10)[0].status, QueueStatus::Cancelled);
+
+        let (entry, stop) = block_on(queue.next());
+        assert!(queue.cancel(entry.id));
+        // The stop signal is kept until the worker gets to it
+        block_on(stop.notified());
+        queue.finish(entry, None);
+
+        let history = queue.history(10);
+        assert_eq!(history[0].id, 1);
+        assert_eq!(history[0].status, QueueStatus::Cancelled);
+        assert_eq!(queue.snapshot().queued[0].id, 3);
+
+        let finished = queue.finished(&[1, 2, 3]);
+        assert_eq!(
+            finished.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let row = finished[0].report_row();
+        assert_eq!(row.status, BatchItemStatus::Failed);
+        assert_eq!(row.error.as_deref(), Some("Cancelled"));
+    }
+
+    #[test]
+    fn test_failed_setup_is_reported_on_the_entry() {
+        let queue = DownloadQueue::new();
+        let data_dir = std::env::temp_dir().join("lyricut_queue_missing_tools");
+        queue.enqueue(
+            vec![item("Song One")],
+            "/music",
+            &AudioMode::Official,
+            QueueSource::App,
+        );
+
+        let (entry, _) = block_on(queue.next());
+        let outcome = block_on(run_entry(&queue, &data_dir, &entry));
+        assert_eq!(outcome.row.status, BatchItemStatus::Failed);
+        assert_eq!(
+            outcome.error.map(|e| e.kind),
+            Some(crate::error::ErrorKind::YtdlpMissing)
+        );
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use crate::retry::random_fraction;
+
+// Searches may come in small bursts, after which they are spread evenly over the minute
+const SEARCH_BURST: u32 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitSettings {
+    /// yt-dlp search requests allowed per minute across all jobs; 0 means unlimited.
+    pub searches_per_minute: u32,
+    /// Total download bandwidth in bytes per second, shared by every running download; 0 means unlimited.
+    pub download_bytes_per_second: u64,
+    /// Downloads allowed to run at once across all jobs; 0 means unlimited, or one at a time
+    /// when a bandwidth limit is set.
+    pub max_concurrent_downloads: u32,
+    /// Pause between the start of one download and the next, picked at random between the
+    /// minimum and maximum.
+    pub sleep_interval_seconds: u64,
+    pub max_sleep_interval_seconds: u64,
+}
+
+impl RateLimitSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_sleep_interval_seconds > 0
+            && self.max_sleep_interval_seconds < self.sleep_interval_seconds
+        {
+            return Err(
+                "Maximum sleep interval cannot be shorter than the minimum sleep interval"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    fn sleep_interval(&self, fraction: f64) -> Duration {
+        let min = self.sleep_interval_seconds as f64;
+        let max = (self.max_sleep_interval_seconds as f64).max(min);
+        Duration::from_secs_f64(min + (max - min) * fraction.clamp(0.0, 1.0))
+    }
+
+    // yt-dlp's --limit-rate is fixed when a download starts, so the budget is split into
+    // fixed per-slot shares rather than between whichever downloads happen to be running
+    fn download_slots(&self) -> Option<u64> {
+        if self.max_concurrent_downloads > 0 {
+            Some(self.max_concurrent_downloads as u64)
+        } else if self.download_bytes_per_second > 0 {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    fn share_of_bandwidth(&self) -> Option<u64> {
+        let total = self.download_bytes_per_second;
+        let slots = self.download_slots().unwrap_or(1);
+        (total > 0).then(|| (total / slots).max(1))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub fn per_minute(rate: u32, burst: u32, now: Instant) -> TokenBucket {
+        let capacity = burst.clamp(1, rate.max(1)) as f64;
+        TokenBucket {
+            capacity,
+            per_second: rate as f64 / 60.0,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    /// Takes a token and returns how long the caller has to wait before using it. The balance
+    /// may go negative, which queues concurrent callers behind each other.
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+}
+
+/// Process-wide limits shared by every command, so parallel jobs draw from the same budget.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+    active_downloads: AtomicU64,
+    slot_freed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    settings: RateLimitSettings,
+    search_bucket: Option<TokenBucket>,
+    next_download: Option<Instant>,
+}
+
+/// Held for the duration of one download.
+pub struct DownloadPermit<'a> {
+    limiter: &'a RateLimiter,
+    limit_rate: Option<u64>,
+}
+
+impl DownloadPermit<'_> {
+    /// This download's share of the bandwidth budget, for yt-dlp's `--limit-rate`.
+    pub fn limit_rate(&self) -> Option<u64> {
+        self.limit_rate
+    }
+}
+
+impl Drop for DownloadPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.active_downloads.fetch_sub(1, Ordering::SeqCst);
+        self.limiter.slot_freed.notify_waiters();
+    }
+}
+
+pub fn global() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(RateLimiter::default)
+}
+
+impl RateLimiter {
+    fn state(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn configure(&self, settings: &RateLimitSettings) {
+        let mut state = self.state();
+        if state.settings.searches_per_minute != settings.searches_per_minute {
+            state.search_bucket = None;
+        }
+        state.settings = settings.clone();
+    }
+
+    fn reserve_search(&self, now: Instant) -> Duration {
+        let mut state = self.state();
+        let rate = state.settings.searches_per_minute;
+        if rate == 0 {
+            return Duration::ZERO;
+        }
+        state
+            .search_bucket
+            .get_or_insert_with(|| TokenBucket::per_minute(rate, SEARCH_BURST, now))
+            .reserve(now)
+    }
+
+    pub async fn wait_for_search(&self) {
+        let wait = self.reserve_search(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // Books the next download start time; the sleep is kept here instead of being handed to
+    // yt-dlp's --sleep-interval so downloads in different jobs are spaced out as well
+    fn reserve_download(&self, now: Instant, fraction: f64) -> Duration {
+        let mut state = self.state();
+        let start = state.next_download.map_or(now, |next| next.max(now));
+        state.next_download = Some(start + state.settings.sleep_interval(fraction));
+        start - now
+    }
+
+    fn try_take_slot(&self) -> bool {
+        let slots = self.state().settings.download_slots();
+        self.active_downloads
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| match slots {
+                Some(slots) if active >= slots => None,
+                _ => Some(active + 1),
+            })
+            .is_ok()
+    }
+
+    async fn wait_for_slot(&self) {
+        loop {
+            // Created before the check so a permit dropped in between still wakes us
+            let freed = self.slot_freed.notified();
+            if self.try_take_slot() {
+                return;
+            }
+            freed.await;
+        }
+    }
+
+    pub async fn start_download(&self) -> DownloadPermit<'_> {
+        self.wait_for_slot().await;
+        let wait = self.reserve_download(Instant::now(), random_fraction());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        DownloadPermit {
+            limiter: self,
+            limit_rate: self.state().settings.share_of_bandwidth(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::block_on;
+
+    fn configured(settings: RateLimitSettings) -> RateLimiter {
+        let limiter = RateLimiter::default();
+        limiter.configure(&settings);
+        limiter
+    }
+
+    #[test]
+    fn test_token_bucket_allows_burst_then_spaces_requests() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::per_minute(30, 2, start);
+
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::from_secs(2));
+        assert_eq!(bucket.reserve(start), Duration::from_secs(4));
+        assert_eq!(
+            bucket.reserve(start + Duration::from_secs(10)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_unlimited_searches_never_wait() {
+        let limiter = configured(RateLimitSettings::default());
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert_eq!(limiter.reserve_search(now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_search_limit_is_shared_between_callers() {
+        let limiter = configured(RateLimitSettings {
+            searches_per_minute: 60,
+            ..RateLimitSettings::default()
+        });
+        let now = Instant::now();
+        let waits: Vec<Duration> = (0..5).map(|_| limiter.reserve_search(now)).collect();
+        assert_eq!(
+            waits,
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_secs(1),
+                Duration::from_secs(2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_downloads_are_spaced_by_sleep_interval() {
+        let limiter = configured(RateLimitSettings {
+            sleep_interval_seconds: 2,
+            max_sleep_interval_seconds: 6,
+            ..RateLimitSettings::default()
+        });
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_download(now, 0.5), Duration::ZERO);
+        assert_eq!(limiter.reserve_download(now, 0.0), Duration::from_secs(4));
+        assert_eq!(limiter.reserve_download(now, 1.0), Duration::from_secs(6));
+        assert_eq!(
+            limiter.reserve_download(now + Duration::from_secs(60), 0.0),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_bandwidth_is_split_between_active_downloads() {
+        let limiter = configured(RateLimitSettings {
+            download_bytes_per_second: 1_000_000,
+            max_concurrent_downloads: 3,
+            ..RateLimitSettings::default()
+        });
+
+        let permits: Vec<DownloadPermit> =
+            (0..3).map(|_| block_on(limiter.start_download())).collect();
+        let total: u64 = permits.iter().filter_map(|p| p.limit_rate()).sum();
+        assert!(total <= 1_000_000);
+        assert_eq!(permits[0].limit_rate(), Some(333_333));
+        assert!(!limiter.try_take_slot());
+
+        drop(permits);
+        assert_eq!(limiter.active_downloads.load(Ordering::SeqCst), 0);
+
+        let unlimited = configured(RateLimitSettings::default());
+        assert_eq!(block_on(unlimited.start_download()).limit_rate(), None);
+    }
+
+    #[test]
+    fn test_bandwidth_limit_without_slot_count_runs_one_download_at_a_time() {
+        let limiter = configured(RateLimitSettings {
+            download_bytes_per_second: 1_000_000,
+            ..RateLimitSettings::default()
+        });
+
+        let first = block_on(limiter.start_download());
+        assert_eq!(first.limit_rate(), Some(1_000_000));
+        assert!(!limiter.try_take_slot());
+        drop(first);
+        assert!(limiter.try_take_slot());
+    }
+
+    #[test]
+    fn test_download_waits_for_a_free_slot() {
+        let limiter = std::sync::Arc::new(configured(RateLimitSettings {
+            max_concurrent_downloads: 1,
+            ..RateLimitSettings::default()
+        }));
+
+        block_on(async {
+            let first = limiter.start_download().await;
+            let waiting = {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.start_download().await.limit_rate() })
+            };
+            tokio::task::yield_now().await;
+            assert!(!waiting.is_finished());
+
+            drop(first);
+            assert_eq!(waiting.await.unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn test_validate_sleep_interval() {
+        assert!(RateLimitSettings::default().validate().is_ok());
+        assert!(RateLimitSettings {
+            sleep_interval_seconds: 10,
+            max_sleep_interval_seconds: 5,
+            ..RateLimitSettings::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{AppError, AppResult, ErrorKind};
+
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total tries per operation, including the first one.
+    pub max_attempts: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub jitter: bool,
+    /// Download the next-best search result when the chosen video is unavailable.
+    pub fallback_to_next_candidate: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_delay_ms: 2000,
+            max_delay_ms: 30000,
+            jitter: true,
+            fallback_to_next_candidate: true,
+        }
+    }
+}
+
+/// Reported before each wait so the caller can surface attempt counts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetryAttempt {
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub kind: ErrorKind,
+    pub message: String,
+    pub delay_ms: u64,
+}
+
+impl RetryPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 || self.max_attempts > MAX_ATTEMPTS_LIMIT {
+            return Err(format!(
+                "Retry attempts must be between 1 and {}",
+                MAX_ATTEMPTS_LIMIT
+            ));
+        }
+        if self.initial_delay_ms > self.max_delay_ms {
+            return Err("Initial retry delay cannot exceed the maximum delay".to_string());
+        }
+        Ok(())
+    }
+
+    /// Delay before retry number `retry` (1 for the first retry). `jitter` is a fraction in
+    /// 0..1; with jitter enabled the delay lands between half and all of the exponential step.
+    pub fn backoff_delay(&self, retry: u32, jitter: f64) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let step = self
+            .initial_delay_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_delay_ms);
+
+        let delay = if self.jitter {
+            step / 2 + (step as f64 / 2.0 * jitter.clamp(0.0, 1.0)) as u64
+        } else {
+            step
+        };
+        Duration::from_millis(delay)
+    }
+
+    /// Runs `operation` until it succeeds, fails with a non-retryable error or runs out of
+    /// attempts. `operation` receives the 1-based attempt number.
+    pub async fn run<T, F>(
+        &self,
+        on_retry: impl FnMut(&RetryAttempt),
+        operation: impl FnMut(u32) -> F,
+    ) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+    {
+        self.run_with_sleep(tokio::time::sleep, random_fraction, on_retry, operation)
+            .await
+    }
+
+    async fn run_with_sleep<T, F, S>(
+        &self,
+        sleep: impl Fn(Duration) -> S,
+        jitter: impl Fn() -> f64,
+        mut on_retry: impl FnMut(&RetryAttempt),
+        mut operation: impl FnMut(u32) -> F,
+    ) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+        S: Future<Output = ()>,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            match operation(attempt).await {
+                Ok(value) => return Ok(value),
+                Err(error) if error.retryable && attempt < max_attempts => {
+                    let delay = self.backoff_delay(attempt, jitter());
+                    on_retry(&RetryAttempt {
+                        attempt,
+                        max_attempts,
+                        kind: error.kind,
+                        message: error.message,
+                        delay_ms: delay.as_millis() as u64,
+                    });
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(error) if attempt > 1 => {
+                    return Err(AppError {
+                        message: format!("{} (after {} attempts)", error.message, attempt),
+                        ..error
+                    })
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Errors where another upload of the same track may still work.
+pub fn is_unavailable(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::VideoUnavailable | ErrorKind::AgeRestricted | ErrorKind::GeoBlocked
+    )
+}
+
+// Std's RandomState is randomly seeded per instance, which is plenty for spreading out retries
+pub fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    hasher.write_u32(nanos);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::future::ready;
+    use crate::test_support::block_on;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_delay_ms: 1000,
+            max_delay_ms: 3000,
+            jitter: false,
+            fallback_to_next_candidate: true,
+        }
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles_and_caps() {
+        let policy = policy();
+        assert_eq!(policy.backoff_delay(1, 0.0), Duration::from_millis(1000));
+        assert_eq!(policy.backoff_delay(2, 0.0), Duration::from_millis(2000));
+        assert_eq!(policy.backoff_delay(3, 0.0), Duration::from_millis(3000));
+        assert_eq!(policy.backoff_delay(40, 0.0), Duration::from_millis(3000));
+    }
+
+    #[test]
+    fn test_backoff_delay_with_jitter_stays_in_upper_half() {
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy()
+        };
+        assert_eq!(policy.backoff_delay(2, 0.0), Duration::from_millis(1000));
+        assert_eq!(policy.backoff_delay(2, 1.0), Duration::from_millis(2000));
+        assert_eq!(policy.backoff_delay(2, 0.5), Duration::from_millis(1500));
+
+        let fraction = random_fraction();
+        assert!((0.0..1.0).contains(&fraction));
+    }
+
+    #[test]
+    fn test_run_retries_retryable_errors_until_success() {
+        let slept = RefCell::new(Vec::new());
+        let mut retries = Vec::new();
+
+        let result = block_on(policy().run_with_sleep(
+            |d| {
+                slept.borrow_mut().push(d);
+                ready(())
+            },
+            || 0.0,
+            |attempt| retries.push(attempt.attempt),
+            |attempt| {
+                ready(if attempt < 3 {
+                    Err(AppError::new(ErrorKind::RateLimited, "HTTP Error 429"))
+                } else {
+                    Ok(attempt)
+                })
+            },
+        ));
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(retries, vec![1, 2]);
+        assert_eq!(
+            *slept.borrow(),
+            vec![Duration::from_millis(1000), Duration::from_millis(2000)]
+        );
+    }
+
+    #[test]
+    fn test_run_stops_on_non_retryable_error() {
+        let mut calls = 0;
+        let result: AppResult<()> = block_on(policy().run_with_sleep(
+            |_| ready(()),
+            || 0.0,
+            |_| {},
+            |_| {
+                calls += 1;
+                ready(Err(AppError::new(ErrorKind::VideoUnavailable, "gone")))
+            },
+        ));
+
+        assert_eq!(calls, 1);
+        assert_eq!(result.unwrap_err().message, "gone");
+    }
+
+    #[test]
+    fn test_run_reports_attempts_when_exhausted() {
+        let result: AppResult<()> = block_on(policy().run_with_sleep(
+            |_| ready(()),
+            || 0.0,
+            |_| {},
+            |_| ready(Err(AppError::new(ErrorKind::Network, "connection reset"))),
+        ));
+
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Network);
+        assert_eq!(error.message, "connection reset (after 3 attempts)");
+    }
+
+    #[test]
+    fn test_run_waits_without_blocking_the_runtime() {
+        let policy = RetryPolicy {
+            initial_delay_ms: 1,
+            max_delay_ms: 1,
+            ..policy()
+        };
+
+        let other_task_ran = block_on(async {
+            let ran = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let flag = ran.clone();
+            tokio::spawn(async move { flag.store(true, std::sync::atomic::Ordering::SeqCst) });
+
+            policy
+                .run(
+                    |_| {},
+                    |attempt| {
+                        let ran = ran.load(std::sync::atomic::Ordering::SeqCst);
+                        ready(if attempt == 1 {
+                            Err(AppError::new(ErrorKind::Network, "connection reset"))
+                        } else {
+                            Ok(ran)
+                        })
+                    },
+                )
+                .await
+        });
+
+        // The test runtime has a single thread, so the spawned task only runs while the
+        // retry is parked in an async sleep
+        assert!(other_task_ran.unwrap());
+    }
+
+    #[test]
+    fn test_validate_retry_policy() {
+        assert!(RetryPolicy::default().validate().is_ok());
+        assert!(RetryPolicy {
+            max_attempts: 0,
+            ..RetryPolicy::default()
+        }
+        .validate()
+        .is_err());
+        assert!(RetryPolicy {
+            initial_delay_ms: 60000,
+            ..RetryPolicy::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,387 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::error::{AppError, AppResult};
+use crate::search_scoring::{best_candidate, rank_candidates, SearchHints};
+use crate::youtube_client::VideoInfo;
+use crate::ytdlp::{ProcessRunner, YtDlp, YtDlpRunner};
+use crate::{get_audio_mode_suffix, AudioMode};
+
+// Without hints the top music result is taken as-is, so only fetch extra candidates when they can be ranked
+const MUSIC_CANDIDATES_WITH_HINTS: usize = 5;
+
+#[allow(async_fn_in_trait)]
+pub trait SearchBackend {
+    fn name(&self) -> &'static str;
+
+    /// Every result worth trying, best first.
+    async fn candidates(&self, query: &str, hints: &SearchHints) -> AppResult<Vec<VideoInfo>>;
+
+    async fn search(&self, query: &str, hints: &SearchHints) -> AppResult<Option<VideoInfo>> {
+        Ok(self.candidates(query, hints).await?.into_iter().next())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchBackendKind {
+    #[default]
+    Youtube,
+    YoutubeMusic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub backend: SearchBackendKind,
+    pub mode_backends: HashMap<AudioMode, SearchBackendKind>,
+    pub fallback_to_youtube: bool,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        SearchSettings {
+            backend: SearchBackendKind::default(),
+            mode_backends: HashMap::new(),
+            fallback_to_youtube: true,
+        }
+    }
+}
+
+impl SearchSettings {
+    pub fn backends_for(&self, mode: Option<&AudioMode>) -> Vec<SearchBackendKind> {
+        let primary = mode
+            .and_then(|m| self.mode_backends.get(m))
+            .copied()
+            .unwrap_or(self.backend);
+
+        let mut kinds = vec![primary];
+        if self.fallback_to_youtube && primary != SearchBackendKind::Youtube {
+            kinds.push(SearchBackendKind::Youtube);
+        }
+        kinds
+    }
+}
+
+// Music results are already official releases, and the extra words only skew the match
+fn music_query(query: &str) -> &str {
+    let suffix = get_audio_mode_suffix(&AudioMode::Official);
+    query
+        .trim_end()
+        .strip_suffix(suffix)
+        .map(str::trim_end)
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(query)
+}
+
+/// Searches YouTube or YouTube Music through yt-dlp.
+pub struct YtDlpSearch<'a, R: YtDlpRunner = ProcessRunner> {
+    ytdlp: &'a YtDlp<R>,
+    kind: SearchBackendKind,
+}
+
+impl<R: YtDlpRunner> SearchBackend for YtDlpSearch<'_, R> {
+    fn name(&self) -> &'static str {
+        match self.kind {
+            SearchBackendKind::YoutubeMusic => "YouTube Music",
+            SearchBackendKind::Youtube => "YouTube",
+        }
+    }
+
+    async fn candidates(&self, query: &str, hints: &SearchHints) -> AppResult<Vec<VideoInfo>> {
+        let results = match self.kind {
+            SearchBackendKind::YoutubeMusic => {
+                self.ytdlp
+                    .search_music(music_query(query), MUSIC_CANDIDATES_WITH_HINTS)
+                    .await?
+            }
+            SearchBackendKind::Youtube => self.ytdlp.search_videos(query).await?,
+        };
+        Ok(rank_candidates(results, hints))
+    }
+
+    async fn search(&self, query: &str, hints: &SearchHints) -> AppResult<Option<VideoInfo>> {
+        if self.kind != SearchBackendKind::YoutubeMusic {
+            return Ok(self.candidates(query, hints).await?.into_iter().next());
+        }
+        let limit = if hints.is_empty() {
+            1
+        } else {
+            MUSIC_CANDIDATES_WITH_HINTS
+        };
+        Ok(best_candidate(
+            self.ytdlp.search_music(music_query(query), limit).await?,
+            hints,
+        ))
+    }
+}
+
+pub struct FallbackSearch<B: SearchBackend> {
+    backends: Vec<B>,
+}
+
+impl<B: SearchBackend> FallbackSearch<B> {
+    pub fn new(backends: Vec<B>) -> Self {
+        FallbackSearch { backends }
+    }
+}
+
+impl<'a, R: YtDlpRunner> FallbackSearch<YtDlpSearch<'a, R>> {
+    pub fn from_settings(
+        ytdlp: &'a YtDlp<R>,
+        settings: &SearchSettings,
+        mode: Option<&AudioMode>,
+    ) -> Self {
+        let backends = settings
+            .backends_for(mode)
+            .into_iter()
+            .map(|kind| YtDlpSearch { ytdlp, kind })
+            .collect();
+        FallbackSearch::new(backends)
+    }
+}
+
+impl<B: SearchBackend> SearchBackend for FallbackSearch<B> {
+    fn name(&self) -> &'static str {
+        "Fallback"
+    }
+
+    async fn candidates(&self, query: &str, hints: &SearchHints) -> AppResult<Vec<VideoInfo>> {
+        let found = self
+            .first_found(|backend| async move {
+                let candidates = backend.candidates(query, hints).await?;
+                Ok(Some(candidates).filter(|c| !c.is_empty()))
+            })
+            .await?;
+        Ok(found.unwrap_or_default())
+    }
+
+    async fn search(&self, query: &str, hints: &SearchHints) -> AppResult<Option<VideoInfo>> {
+        self.first_found(|backend| backend.search(query, hints))
+            .await
+    }
+}
+
+impl<B: SearchBackend> FallbackSearch<B> {
+    // Tries each backend in order; errors only surface if no backend finds anything
+    async fn first_found<'a, T, F>(&'a self, run: impl Fn(&'a B) -> F) -> AppResult<Option<T>>
+    where
+        F: Future<Output = AppResult<Option<T>>>,
+    {
+        let mut errors: Vec<(&'static str, AppError)> = Vec::new();
+
+        for backend in &self.backends {
+            match run(backend).await {
+                Ok(Some(found)) => return Ok(Some(found)),
+                Ok(None) => {}
+                Err(e) => errors.push((backend.name(), e)),
+            }
+        }
+
+        // The first backend's error decides the kind, since it is the one the user asked for
+        let message = errors
+            .iter()
+            .map(|(name, e)| format!("{}: {}", name, e))
+            .collect::<Vec<_>>()
+            .join("; ");
+        match errors.into_iter().next() {
+            Some((_, first)) => Err(AppError { message, ..first }),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::block_on;
+
+    struct StubBackend {
+        name: &'static str,
+        result: Result<Option<&'static str>, &'static str>,
+    }
+
+    impl SearchBackend for StubBackend {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn candidates(
+            &self,
+            _query: &str,
+            _hints: &SearchHints,
+        ) -> AppResult<Vec<VideoInfo>> {
+            self.result
+                .map(|id| {
+                    id.into_iter()
+                        .map(|id| VideoInfo {
+                            id: id.to_string(),
+                            title: String::new(),
+                            url: format!("https://www.youtube.com/watch?v={}", id),
+                            thumbnail_url: None,
+                            uploader: None,
+                            duration_seconds: None,
+                            upload_date: None,
+                            description: None,
+                            source: None,
+                            match_score: None,
+                        })
+                        .collect()
+                })
+                .map_err(AppError::internal)
+        }
+    }
+
+    fn stub(name: &'static str, result: Result<Option<&'static str>, &'static str>) -> StubBackend {
+        StubBackend { name, result }
+    }
+
+    #[test]
+    fn test_backends_for_defaults_to_youtube_only() {
+        let settings = SearchSettings::default();
+        assert_eq!(
+            settings.backends_for(None),
+            vec![SearchBackendKind::Youtube]
+        );
+    }
+
+    #[test]
+    fn test_backends_for_music_falls_back_to_youtube() {
+        let settings = SearchSettings {
+            backend: SearchBackendKind::YoutubeMusic,
+            ..SearchSettings::default()
+        };
+        assert_eq!(
+            settings.backends_for(None),
+            vec![SearchBackendKind::YoutubeMusic, SearchBackendKind::Youtube]
+        );
+    }
+
+    #[test]
+    fn test_music_query_drops_official_audio_suffix() {
+        assert_eq!(
+            music_query("Daft Punk - One More Time official audio"),
+            "Daft Punk - One More Time"
+        );
+        assert_eq!(
+            music_query("Daft Punk - One More Time clean audio"),
+            "Daft Punk - One More Time clean audio"
+        );
+        assert_eq!(music_query("official audio"), "official audio");
+    }
+
+    #[test]
+    fn test_backends_for_uses_mode_override() {
+        let mut settings = SearchSettings::default();
+        settings
+            .mode_backends
+            .insert(AudioMode::Official, SearchBackendKind::YoutubeMusic);
+
+        assert_eq!(
+            settings.backends_for(Some(&AudioMode::Official)),
+            vec![SearchBackendKind::YoutubeMusic, SearchBackendKind::Youtube]
+        );
+        assert_eq!(
+            settings.backends_for(Some(&AudioMode::Raw)),
+            vec![SearchBackendKind::Youtube]
+        );
+    }
+
+    #[test]
+    fn test_backends_for_without_fallback() {
+        let settings = SearchSettings {
+            backend: SearchBackendKind::YoutubeMusic,
+            fallback_to_youtube: false,
+            ..SearchSettings::default()
+        };
+        assert_eq!(
+            settings.backends_for(None),
+            vec![SearchBackendKind::YoutubeMusic]
+        );
+    }
+
+    #[test]
+    fn test_fallback_search_returns_first_hit() {
+        let chain = FallbackSearch::new(vec![
+            stub("music", Ok(Some("music_id"))),
+            stub("yt", Ok(Some("yt_id"))),
+        ]);
+        assert_eq!(
+            block_on(chain.search("song", &SearchHints::default()))
+                .unwrap()
+                .unwrap()
+                .id,
+            "music_id"
+        );
+    }
+
+    #[test]
+    fn test_fallback_search_falls_through_empty_results() {
+        let chain =
+            FallbackSearch::new(vec![stub("music", Ok(None)), stub("yt", Ok(Some("yt_id")))]);
+        assert_eq!(
+            block_on(chain.search("song", &SearchHints::default()))
+                .unwrap()
+                .unwrap()
+                .id,
+            "yt_id"
+        );
+    }
+
+    #[test]
+    fn test_fallback_search_falls_through_errors() {
+        let chain = FallbackSearch::new(vec![
+            stub("music", Err("blocked")),
+            stub("yt", Ok(Some("yt_id"))),
+        ]);
+        assert_eq!(
+            block_on(chain.search("song", &SearchHints::default()))
+                .unwrap()
+                .unwrap()
+                .id,
+            "yt_id"
+        );
+    }
+
+    #[test]
+    fn test_fallback_search_reports_errors_when_nothing_found() {
+        let chain = FallbackSearch::new(vec![stub("music", Err("blocked")), stub("yt", Ok(None))]);
+        assert_eq!(
+            block_on(chain.search("song", &SearchHints::default()))
+                .unwrap_err()
+                .message,
+            "music: blocked"
+        );
+    }
+
+    #[test]
+    fn test_fallback_candidates_skip_empty_backends() {
+        let chain =
+            FallbackSearch::new(vec![stub("music", Ok(None)), stub("yt", Ok(Some("yt_id")))]);
+        let candidates = block_on(chain.candidates("song", &SearchHints::default())).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].id, "yt_id");
+    }
+
+    #[test]
+    fn test_fallback_search_no_results() {
+        let chain = FallbackSearch::new(vec![stub("music", Ok(None)), stub("yt", Ok(None))]);
+        assert!(block_on(chain.search("song", &SearchHints::default()))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_search_settings_serde_round_trip() {
+        let mut settings = SearchSettings::default();
+        settings
+            .mode_backends
+            .insert(AudioMode::Clean, SearchBackendKind::Youtube);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains("\"clean\":\"youtube\""));
+        let parsed: SearchSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, settings);
+    }
+}
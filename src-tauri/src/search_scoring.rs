@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use crate::youtube_client::VideoInfo;
+
+const ISRC_BONUS: i32 = 100;
+const CLOSE_DURATION_BONUS: i32 = 30;
+const NEAR_DURATION_BONUS: i32 = 15;
+const WRONG_DURATION_PENALTY: i32 = -30;
+
+/// Extra facts about the wanted track, used to rank search candidates.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchHints {
+    pub isrc: Option<String>,
+    pub duration_seconds: Option<u64>,
+}
+
+impl SearchHints {
+    pub fn is_empty(&self) -> bool {
+        self.isrc.is_none() && self.duration_seconds.is_none()
+    }
+}
+
+// ISRCs are often printed with hyphens or spaces ("US-UM7-17-03861")
+fn compact_isrc(text: &str) -> String {
+    text.chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .collect::<String>()
+        .to_uppercase()
+}
+
+pub fn score_candidate(candidate: &VideoInfo, hints: &SearchHints) -> i32 {
+    let mut score = 0;
+
+    if let Some(isrc) = hints.isrc.as_deref().map(compact_isrc) {
+        let text = format!(
+            "{}\n{}",
+            candidate.title,
+            candidate.description.as_deref().unwrap_or("")
+        );
+        if !isrc.is_empty() && compact_isrc(&text).contains(&isrc) {
+            score += ISRC_BONUS;
+        }
+    }
+
+    if let (Some(wanted), Some(actual)) = (hints.duration_seconds, candidate.duration_seconds) {
+        score += match wanted.abs_diff(actual) {
+            0..=2 => CLOSE_DURATION_BONUS,
+            3..=5 => NEAR_DURATION_BONUS,
+            6..=15 => 0,
+            _ => WRONG_DURATION_PENALTY,
+        };
+    }
+
+    score
+}
+
+/// Orders candidates from best to worst; ties keep the search engine's order. Scores are only
+/// recorded on the results when there were hints to score against.
+pub fn rank_candidates(candidates: Vec<VideoInfo>, hints: &SearchHints) -> Vec<VideoInfo> {
+    let mut scored: Vec<(i32, VideoInfo)> = candidates
+        .into_iter()
+        .map(|candidate| (score_candidate(&candidate, hints), candidate))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    scored
+        .into_iter()
+        .map(|(score, mut candidate)| {
+            if !hints.is_empty() {
+                candidate.match_score = Some(score);
+            }
+            candidate
+        })
+        .collect()
+}
+
+pub fn best_candidate(candidates: Vec<VideoInfo>, hints: &SearchHints) -> Option<VideoInfo> {
+    rank_candidates(candidates, hints).into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, duration: Option<u64>, description: Option<&str>) -> VideoInfo {
+        VideoInfo {
+            id: id.to_string(),
+            title: format!("Video {}", id),
+            url: format!("https://www.youtube.com/watch?v={}", id),
+            thumbnail_url: None,
+            uploader: None,
+            duration_seconds: duration,
+            upload_date: None,
+            description: description.map(|d| d.to_string()),
+            source: None,
+            match_score: None,
+        }
+    }
+
+    #[test]
+    fn test_best_candidate_without_hints_keeps_first() {
+        let candidates = vec![
+            candidate("a", Some(200), None),
+            candidate("b", Some(180), None),
+        ];
+        let best = best_candidate(candidates, &SearchHints::default()).unwrap();
+        assert_eq!(best.id, "a");
+        assert_eq!(best.match_score, None);
+    }
+
+    #[test]
+    fn test_best_candidate_prefers_isrc_match() {
+        let hints = SearchHints {
+            isrc: Some("USUM71703861".to_string()),
+            duration_seconds: Some(180),
+        };
+        let candidates = vec![
+            candidate("close", Some(180), None),
+            candidate(
+                "isrc",
+                Some(240),
+                Some("Released on: 2017\nISRC: US-UM7-17-03861"),
+            ),
+        ];
+
+        let best = best_candidate(candidates, &hints).unwrap();
+        assert_eq!(best.id, "isrc");
+        assert_eq!(best.match_score, Some(70));
+    }
+
+    #[test]
+    fn test_best_candidate_prefers_matching_duration() {
+        let hints = SearchHints {
+            isrc: None,
+            duration_seconds: Some(212),
+        };
+        let candidates = vec![
+            candidate("music_video", Some(265), None),
+            candidate("audio", Some(213), None),
+        ];
+
+        assert_eq!(best_candidate(candidates, &hints).unwrap().id, "audio");
+    }
+
+    #[test]
+    fn test_score_candidate_duration_bands() {
+        let hints = SearchHints {
+            isrc: None,
+            duration_seconds: Some(200),
+        };
+        assert_eq!(
+            score_candidate(&candidate("a", Some(202), None), &hints),
+            30
+        );
+        assert_eq!(
+            score_candidate(&candidate("a", Some(196), None), &hints),
+            15
+        );
+        assert_eq!(score_candidate(&candidate("a", Some(210), None), &hints), 0);
+        assert_eq!(
+            score_candidate(&candidate("a", Some(260), None), &hints),
+            -30
+        );
+        assert_eq!(score_candidate(&candidate("a", None, None), &hints), 0);
+    }
+
+    #[test]
+    fn test_rank_candidates_orders_by_score() {
+        let hints = SearchHints {
+            isrc: None,
+            duration_seconds: Some(200),
+        };
+        let candidates = vec![
+            candidate("far", Some(300), None),
+            candidate("near", Some(204), None),
+            candidate("close", Some(201), None),
+        ];
+
+        let ids: Vec<String> = rank_candidates(candidates, &hints)
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec!["close", "near", "far"]);
+    }
+
+    #[test]
+    fn test_best_candidate_empty() {
+        assert!(best_candidate(Vec::new(), &SearchHints::default()).is_none());
+    }
+}
@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cleanup_rules::{default_cleanup_rules, validate_cleanup_rules, CleanupRule};
+use crate::conflict::ConflictPolicy;
+use crate::csv_profiles::{
+    builtin_csv_profiles, merge_csv_profiles, validate_csv_profiles, CsvProfile,
+};
+use crate::error::{AppError, AppResult};
+use crate::filename_template::FilenameTemplate;
+#[cfg(any(feature = "gui", test))]
+use crate::http_server::HttpServerSettings;
+use crate::link_resolver::ResolverSettings;
+use crate::organizer::OrganizeMode;
+use crate::rate_limit::RateLimitSettings;
+use crate::retry::RetryPolicy;
+use crate::search::SearchSettings;
+use crate::subtitles::SubtitleOptions;
+#[cfg(any(feature = "gui", test))]
+use crate::watch_folder::WatchSettings;
+use crate::ytdlp_command::YtDlpSettings;
+
+const SETTINGS_FILENAME: &str = "settings.json";
+// The identifier from tauri.conf.json, which names the app's data directory
+const APP_IDENTIFIER: &str = "com.lyricut.yt-downloader";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub cleanup_rules: Vec<CleanupRule>,
+    pub search: SearchSettings,
+    pub resolver: ResolverSettings,
+    /// The user's own profiles; the built-in ones are merged in by `all_csv_profiles`.
+    pub csv_profiles: Vec<CsvProfile>,
+    pub retry: RetryPolicy,
+    pub rate_limits: RateLimitSettings,
+    pub ytdlp: YtDlpSettings,
+    /// How downloads are named, such as "{artist} - {title}"; `None` keeps the cleaned video title.
+    pub filename_template: Option<String>,
+    /// What happens when a download would land on a file that is already there.
+    pub conflict_policy: ConflictPolicy,
+    /// Sorts new downloads into folders such as Artist/Album inside the output folder.
+    pub organize_mode: OrganizeMode,
+    /// Fetches subtitles with each download and turns them into lyrics; `None` skips them.
+    pub subtitles: Option<SubtitleOptions>,
+    // Only the app runs the server and the watch folders; the CLI never writes settings back, so
+    // leaving these out of its view of the file loses nothing
+    /// Read at startup; changes apply the next time the app opens.
+    #[cfg(any(feature = "gui", test))]
+    pub http_server: HttpServerSettings,
+    #[cfg(any(feature = "gui", test))]
+    pub watch: WatchSettings,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            cleanup_rules: default_cleanup_rules(),
+            search: SearchSettings::default(),
+            resolver: ResolverSettings::default(),
+            csv_profiles: Vec::new(),
+            retry: RetryPolicy::default(),
+            rate_limits: RateLimitSettings::default(),
+            ytdlp: YtDlpSettings::default(),
+            filename_template: None,
+            conflict_policy: ConflictPolicy::default(),
+            organize_mode: OrganizeMode::default(),
+            subtitles: None,
+            #[cfg(any(feature = "gui", test))]
+            http_server: HttpServerSettings::default(),
+            #[cfg(any(feature = "gui", test))]
+            watch: WatchSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_cleanup_rules(&self.cleanup_rules)?;
+        validate_csv_profiles(&self.csv_profiles)?;
+        self.retry.validate()?;
+        self.rate_limits.validate()?;
+        self.ytdlp.validate()?;
+        self.resolver.validate()?;
+        self.organize_mode.validate()?;
+        if let Some(subtitles) = &self.subtitles {
+            subtitles.validate()?;
+        }
+        if let Some(template) = self
+            .filename_template
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+        {
+            FilenameTemplate::parse(template)?;
+        }
+        #[cfg(any(feature = "gui", test))]
+        {
+            self.http_server.validate()?;
+            self.watch.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn all_csv_profiles(&self) -> Vec<CsvProfile> {
+        merge_csv_profiles(&self.csv_profiles)
+    }
+}
+
+/// Where the app keeps its data, for callers without a Tauri handle to ask.
+pub fn default_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+pub fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILENAME)
+}
+
+pub fn load_settings(path: &Path) -> AppResult<AppSettings> {
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let content =
+        fs::read_to_string(path).map_err(|e| AppError::io("Failed to read settings", e))?;
+    let mut settings: AppSettings = serde_json::from_str(&content)
+        .map_err(|e| AppError::invalid_input(format!("Invalid settings file: {}", e)))?;
+
+    // Earlier versions saved unchanged copies of the built-in profiles, which would otherwise
+    // shadow later updates to them
+    let builtins = builtin_csv_profiles();
+    settings.csv_profiles.retain(|p| !builtins.contains(p));
+    Ok(settings)
+}
+
+#[cfg(any(feature = "gui", test))]
+pub fn save_settings(path: &Path, settings: &AppSettings) -> AppResult<()> {
+    settings.validate().map_err(AppError::invalid_input)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create settings directory", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| AppError::internal(format!("Failed to serialize settings: {}", e)))?;
+
+    // Write to a temporary file first so a crash never leaves a half-written settings file
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, content).map_err(|e| AppError::io("Failed to write settings", e))?;
+    fs::rename(&temp_path, path).map_err(|e| AppError::io("Failed to write settings", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleanup_rules::{CleanupAction, MatchKind};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    #[test]
+    fn test_load_settings_missing_file_returns_defaults() {
+        let dir = test_dir("settings_missing_test");
+        let settings = load_settings(&settings_path(&dir)).unwrap();
+        assert_eq!(settings, AppSettings::default());
+    }
+
+    #[test]
+    fn test_save_and_load_settings_round_trip() {
+        let dir = test_dir("settings_round_trip_test");
+        let path = settings_path(&dir);
+        let settings = AppSettings {
+            cleanup_rules: vec![CleanupRule {
+                pattern: "[HQ]".to_string(),
+                kind: MatchKind::Literal,
+                action: CleanupAction::Remove,
+                replacement: None,
+            }],
+            ..AppSettings::default()
+        };
+
+        save_settings(&path, &settings).unwrap();
+        let loaded = load_settings(&path).unwrap();
+
+        assert_eq!(loaded, settings);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_settings_fills_missing_fields_with_defaults() {
+        let dir = test_dir("settings_partial_test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = settings_path(&dir);
+        fs::write(&path, "{}").unwrap();
+
+        let loaded = load_settings(&path).unwrap();
+
+        assert_eq!(loaded.cleanup_rules, default_cleanup_rules());
+        assert!(loaded.csv_profiles.is_empty());
+        assert_eq!(loaded.all_csv_profiles(), builtin_csv_profiles());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_settings_drops_saved_copies_of_builtin_profiles() {
+        let dir = test_dir("settings_builtin_profiles_test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = settings_path(&dir);
+        let mut profiles = builtin_csv_profiles();
+        profiles[0].name = "My Exportify".to_string();
+        let saved = serde_json::json!({ "csv_profiles": profiles });
+        fs::write(&path, saved.to_string()).unwrap();
+
+        let loaded = load_settings(&path).unwrap();
+
+        assert_eq!(loaded.csv_profiles, vec![profiles[0].clone()]);
+        assert_eq!(
+            loaded.all_csv_profiles().len(),
+            builtin_csv_profiles().len() + 1
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_settings_invalid_json() {
+        let dir = test_dir("settings_invalid_test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = settings_path(&dir);
+        fs::write(&path, "not json").unwrap();
+
+        let error = load_settings(&path).unwrap_err();
+        assert_eq!(error.kind, crate::error::ErrorKind::InvalidInput);
+        assert!(error.message.contains("Invalid settings file"));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_save_settings_rejects_invalid_rules() {
+        let dir = test_dir("settings_reject_test");
+        let settings = AppSettings {
+            cleanup_rules: vec![CleanupRule {
+                pattern: "(".to_string(),
+                kind: MatchKind::Regex,
+                action: CleanupAction::Remove,
+                replacement: None,
+            }],
+            ..AppSettings::default()
+        };
+
+        assert!(save_settings(&settings_path(&dir), &settings).is_err());
+        assert!(!settings_path(&dir).exists());
+    }
+
+    #[test]
+    fn test_validate_checks_filename_template() {
+        let valid = AppSettings {
+            filename_template: Some("{artist}/{title}".to_string()),
+            ..AppSettings::default()
+        };
+        assert!(valid.validate().is_ok());
+
+        let invalid = AppSettings {
+            filename_template: Some("{nope}".to_string()),
+            ..AppSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_checks_organize_mode() {
+        let invalid = AppSettings {
+            organize_mode: OrganizeMode::Custom("{nope}".to_string()),
+            ..AppSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+
+        let parsed: AppSettings =
+            serde_json::from_str(r#"{ "organize_mode": "artist_album" }"#).unwrap();
+        assert_eq!(parsed.organize_mode, OrganizeMode::ArtistAlbum);
+    }
+}
@@ -0,0 +1,168 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceSite {
+    Youtube,
+    Soundcloud,
+    Bandcamp,
+    Vimeo,
+    Mixcloud,
+    Other,
+}
+
+impl SourceSite {
+    /// Classifies an http(s) URL by host. Returns `None` for anything that is not a URL,
+    /// so plain text keeps being treated as a search query.
+    pub fn from_url(text: &str) -> Option<SourceSite> {
+        let url = Url::parse(text.trim()).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        let host = url.host_str()?.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+
+        let is_domain = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+        let site = if is_domain("youtube.com") || is_domain("youtu.be") {
+            SourceSite::Youtube
+        } else if is_domain("soundcloud.com") {
+            SourceSite::Soundcloud
+        } else if is_domain("bandcamp.com") {
+            SourceSite::Bandcamp
+        } else if is_domain("vimeo.com") {
+            SourceSite::Vimeo
+        } else if is_domain("mixcloud.com") {
+            SourceSite::Mixcloud
+        } else {
+            SourceSite::Other
+        };
+        Some(site)
+    }
+}
+
+/// YouTube playlist and channel pages list many videos, so they can't be queued as one download.
+pub fn is_youtube_collection(text: &str) -> bool {
+    if SourceSite::from_url(text) != Some(SourceSite::Youtube) {
+        return false;
+    }
+    let Ok(url) = Url::parse(text.trim()) else {
+        return false;
+    };
+    let path = url.path();
+    path == "/playlist"
+        || path.starts_with("/@")
+        || ["/channel/", "/c/", "/user/"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+}
+
+/// yt-dlp accepts either a bare YouTube video ID or a full URL for any supported site.
+pub fn media_url(target: &str) -> String {
+    let target = target.trim();
+    if SourceSite::from_url(target).is_some() {
+        target.to_string()
+    } else {
+        format!("https://www.youtube.com/watch?v={}", target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url_known_sites() {
+        let cases = [
+            (
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                SourceSite::Youtube,
+            ),
+            (
+                "https://music.youtube.com/watch?v=dQw4w9WgXcQ",
+                SourceSite::Youtube,
+            ),
+            ("https://youtu.be/dQw4w9WgXcQ", SourceSite::Youtube),
+            (
+                "https://soundcloud.com/artist/track",
+                SourceSite::Soundcloud,
+            ),
+            (
+                "https://m.soundcloud.com/artist/track",
+                SourceSite::Soundcloud,
+            ),
+            (
+                "https://artist.bandcamp.com/track/song",
+                SourceSite::Bandcamp,
+            ),
+            ("https://vimeo.com/123456", SourceSite::Vimeo),
+            ("https://www.mixcloud.com/dj/set/", SourceSite::Mixcloud),
+            ("https://example.com/video.mp4", SourceSite::Other),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(SourceSite::from_url(url), Some(expected), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_from_url_rejects_non_urls() {
+        assert_eq!(SourceSite::from_url("Daft Punk - One More Time"), None);
+        assert_eq!(SourceSite::from_url("dQw4w9WgXcQ"), None);
+        assert_eq!(SourceSite::from_url("ftp://example.com/file.mp3"), None);
+        assert_eq!(SourceSite::from_url(""), None);
+    }
+
+    #[test]
+    fn test_from_url_does_not_match_lookalike_hosts() {
+        assert_eq!(
+            SourceSite::from_url("https://notyoutube.com/watch?v=x"),
+            Some(SourceSite::Other)
+        );
+    }
+
+    #[test]
+    fn test_is_youtube_collection() {
+        assert!(is_youtube_collection(
+            "https://www.youtube.com/playlist?list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs"
+        ));
+        assert!(is_youtube_collection(
+            "https://music.youtube.com/playlist?list=OLAK5uy_abc"
+        ));
+        assert!(is_youtube_collection("https://www.youtube.com/@daftpunk"));
+        assert!(is_youtube_collection(
+            "https://www.youtube.com/channel/UC_kRDKYrUlrbtrSiyu5Tflg"
+        ));
+        assert!(is_youtube_collection(
+            "https://youtube.com/c/DaftPunk/videos"
+        ));
+        assert!(is_youtube_collection(
+            "https://www.youtube.com/user/daftpunkalive"
+        ));
+    }
+
+    #[test]
+    fn test_is_youtube_collection_allows_single_videos() {
+        assert!(!is_youtube_collection(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs"
+        ));
+        assert!(!is_youtube_collection("https://youtu.be/dQw4w9WgXcQ"));
+        assert!(!is_youtube_collection(
+            "https://soundcloud.com/artist/sets/playlist"
+        ));
+        assert!(!is_youtube_collection("Daft Punk - One More Time"));
+    }
+
+    #[test]
+    fn test_media_url() {
+        assert_eq!(
+            media_url("dQw4w9WgXcQ"),
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        );
+        assert_eq!(
+            media_url("https://soundcloud.com/artist/track"),
+            "https://soundcloud.com/artist/track"
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubtitleOptions {
+    pub language: String,
+    pub embed: bool,
+    pub write_sidecar: bool,
+}
+
+impl Default for SubtitleOptions {
+    fn default() -> Self {
+        SubtitleOptions {
+            language: "en".to_string(),
+            embed: true,
+            write_sidecar: false,
+        }
+    }
+}
+
+impl SubtitleOptions {
+    // The language goes to yt-dlp as an argument, so only language codes and lists of them pass
+    pub fn validate(&self) -> Result<(), String> {
+        let is_code_char = |c: char| c.is_ascii_alphanumeric() || "-_,.*".contains(c);
+        if self.language.is_empty() || !self.language.chars().all(is_code_char) {
+            return Err(format!("Invalid subtitle language: {}", self.language));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedLine {
+    pub start_ms: u64,
+    pub text: String,
+}
+
+fn parse_vtt_timestamp(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (clock, millis) = value.split_once('.').unwrap_or((value, "0"));
+    let parts: Vec<&str> = clock.split(':').collect();
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (h.parse::<u64>().ok()?, m.parse::<u64>().ok()?, s.parse::<u64>().ok()?),
+        [m, s] => (0, m.parse::<u64>().ok()?, s.parse::<u64>().ok()?),
+        _ => return None,
+    };
+    // The fraction is decimal, so ".5" means 500ms rather than 5ms
+    let millis = format!("{:0<3}", millis.get(..3.min(millis.len()))?)
+        .parse::<u64>()
+        .ok()?;
+
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
+
+pub fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn clean_caption_line(line: &str, tag_regex: &Regex) -> Option<String> {
+    let without_tags = tag_regex.replace_all(line, "");
+    let decoded = decode_entities(&without_tags).replace('♪', "");
+    let collapsed = decoded.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    // Sound annotations such as "[Music]" or "(Applause)" are not lyrics
+    let is_annotation = (collapsed.starts_with('[') && collapsed.ends_with(']'))
+        || (collapsed.starts_with('(') && collapsed.ends_with(')'));
+
+    if collapsed.is_empty() || is_annotation {
+        None
+    } else {
+        Some(collapsed)
+    }
+}
+
+pub fn parse_vtt(content: &str) -> Vec<TimedLine> {
+    let tag_regex = Regex::new(r"<[^>]*>").expect("valid tag regex");
+    let normalized = content.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines = Vec::new();
+
+    for block in normalized.split("\n\n") {
+        let mut block_lines = block.lines().skip_while(|l| l.trim().is_empty());
+        let mut timing_line = None;
+
+        // A cue may start with an optional identifier line before the timing line
+        for candidate in block_lines.by_ref().take(2) {
+            if candidate.contains("-->") {
+                timing_line = Some(candidate);
+                break;
+            }
+        }
+
+        let Some(timing_line) = timing_line else {
+            continue;
+        };
+        let Some(start) = timing_line
+            .split("-->")
+            .next()
+            .and_then(parse_vtt_timestamp)
+        else {
+            continue;
+        };
+
+        for text_line in block_lines {
+            if let Some(text) = clean_caption_line(text_line, &tag_regex) {
+                lines.push(TimedLine {
+                    start_ms: start,
+                    text,
+                });
+            }
+        }
+    }
+
+    lines
+}
+
+pub fn parse_srv3(content: &str) -> Vec<TimedLine> {
+    let paragraph_regex =
+        Regex::new(r#"(?s)<p\b([^>]*)>(.*?)</p>"#).expect("valid paragraph regex");
+    let start_regex = Regex::new(r#"\bt="(\d+)""#).expect("valid start regex");
+    let tag_regex = Regex::new(r"<[^>]*>").expect("valid tag regex");
+    let mut lines = Vec::new();
+
+    for captures in paragraph_regex.captures_iter(content) {
+        let Some(start) = start_regex
+            .captures(&captures[1])
+            .and_then(|c| c[1].parse::<u64>().ok())
+        else {
+            continue;
+        };
+
+        let body = captures[2].replace("<br/>", "\n").replace("<br />", "\n");
+        for text_line in body.lines() {
+            if let Some(text) = clean_caption_line(text_line, &tag_regex) {
+                lines.push(TimedLine {
+                    start_ms: start,
+                    text,
+                });
+            }
+        }
+    }
+
+    lines
+}
+
+// Lines from one cue share its start time
+fn group_cues(lines: Vec<TimedLine>) -> Vec<Vec<TimedLine>> {
+    let mut cues: Vec<Vec<TimedLine>> = Vec::new();
+    for line in lines {
+        match cues.last_mut() {
+            Some(cue) if cue[0].start_ms == line.start_ms => cue.push(line),
+            _ => cues.push(vec![line]),
+        }
+    }
+    cues
+}
+
+// How many leading lines of `cue` repeat the trailing lines of `previous`
+fn cue_overlap(previous: &[TimedLine], cue: &[TimedLine]) -> usize {
+    (1..=previous.len().min(cue.len()))
+        .rev()
+        .find(|&count| {
+            previous[previous.len() - count..]
+                .iter()
+                .zip(&cue[..count])
+                .all(|(a, b)| a.text.eq_ignore_ascii_case(&b.text))
+        })
+        .unwrap_or(0)
+}
+
+/// Auto-generated captions roll, so each cue repeats the end of the one before it.
+/// Only that overlap is dropped, so lyrics repeated further apart are kept.
+pub fn dedupe_rolling_lines(lines: Vec<TimedLine>) -> Vec<TimedLine> {
+    let cues = group_cues(lines);
+    let mut deduped = Vec::new();
+    let mut previous: &[TimedLine] = &[];
+    for cue in &cues {
+        deduped.extend_from_slice(&cue[cue_overlap(previous, cue)..]);
+        previous = cue;
+    }
+    deduped
+}
+
+// Only auto-generated VTT carries per-word timing tags, and only it rolls
+fn has_word_timings(content: &str) -> bool {
+    Regex::new(r"<\d{2}:\d{2}:\d{2}\.\d{3}>")
+        .expect("valid word timing regex")
+        .is_match(content)
+}
+
+pub fn to_lrc(lines: &[TimedLine]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            let minutes = line.start_ms / 60_000;
+            let seconds = (line.start_ms % 60_000) / 1000;
+            let hundredths = (line.start_ms % 1000) / 10;
+            format!("[{:02}:{:02}.{:02}]{}", minutes, seconds, hundredths, line.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn subtitle_to_lrc(content: &str, extension: &str) -> Result<String, String> {
+    let lines = match extension.to_lowercase().as_str() {
+        "vtt" if has_word_timings(content) => dedupe_rolling_lines(parse_vtt(content)),
+        "vtt" => parse_vtt(content),
+        "srv3" | "xml" => parse_srv3(content),
+        other => return Err(format!("Unsupported subtitle format: {}", other)),
+    };
+
+    Ok(to_lrc(&lines))
+}
+
+pub fn subtitle_file_to_lrc(path: &Path) -> Result<String, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .ok_or("Subtitle file has no extension")?;
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read subtitle file: {}", e))?;
+
+    subtitle_to_lrc(&content, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subtitle_options_validate() {
+        assert!(SubtitleOptions::default().validate().is_ok());
+        let options = |language: &str| SubtitleOptions {
+            language: language.to_string(),
+            ..SubtitleOptions::default()
+        };
+        assert!(options("en.*,de").validate().is_ok());
+        assert!(options("").validate().is_err());
+        assert!(options("en --exec rm").validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_vtt_timestamp_with_hours() {
+        assert_eq!(parse_vtt_timestamp("01:02:03.456"), Some(3_723_456));
+    }
+
+    #[test]
+    fn test_parse_vtt_timestamp_without_hours() {
+        assert_eq!(parse_vtt_timestamp("02:03.400"), Some(123_400));
+    }
+
+    #[test]
+    fn test_parse_vtt_timestamp_short_fraction() {
+        assert_eq!(parse_vtt_timestamp("00:01.5"), Some(1_500));
+        assert_eq!(parse_vtt_timestamp("00:01.05"), Some(1_050));
+        assert_eq!(parse_vtt_timestamp("00:01"), Some(1_000));
+    }
+
+    #[test]
+    fn test_parse_vtt_timestamp_invalid() {
+        assert_eq!(parse_vtt_timestamp("not a time"), None);
+    }
+
+    #[test]
+    fn test_parse_vtt_manual_subtitles() {
+        let vtt = "WEBVTT\nKind: captions\nLanguage: en\n\n1\n00:00:01.000 --> 00:00:03.000\nHello darkness\n\n2\n00:00:03.500 --> 00:00:05.000\nmy old friend\n";
+        let lines = parse_vtt(vtt);
+
+        assert_eq!(
+            lines,
+            vec![
+                TimedLine { start_ms: 1000, text: "Hello darkness".to_string() },
+                TimedLine { start_ms: 3500, text: "my old friend".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_vtt_strips_inline_timing_tags() {
+        let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000 align:start position:0%\nhello<00:00:01.500><c> world</c>\n";
+        let lines = parse_vtt(vtt);
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].text, "hello world");
+    }
+
+    #[test]
+    fn test_parse_vtt_skips_sound_annotations_and_notes() {
+        let vtt = "WEBVTT\n\nNOTE generated\n\n00:00:00.000 --> 00:00:02.000\n[Music]\n\n00:00:02.000 --> 00:00:04.000\n♪ Is this the real life ♪\n";
+        let lines = parse_vtt(vtt);
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].text, "Is this the real life");
+    }
+
+    #[test]
+    fn test_parse_vtt_decodes_entities() {
+        let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nRock &amp; roll&nbsp;ain&#39;t dead\n";
+        let lines = parse_vtt(vtt);
+
+        assert_eq!(lines[0].text, "Rock & roll ain't dead");
+    }
+
+    #[test]
+    fn test_parse_vtt_handles_crlf() {
+        let vtt = "WEBVTT\r\n\r\n00:00:01.000 --> 00:00:02.000\r\nLine one\r\n";
+        let lines = parse_vtt(vtt);
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].text, "Line one");
+    }
+
+    #[test]
+    fn test_dedupe_rolling_auto_captions() {
+        let vtt = "WEBVTT\n\n00:00:00.000 --> 00:00:02.000\nis this the<00:00:00.500><c> real life</c>\n\n00:00:02.000 --> 00:00:02.010\nis this the real life\n\n00:00:02.010 --> 00:00:04.000\nis this the real life\nis this just<00:00:02.500><c> fantasy</c>\n\n00:00:04.000 --> 00:00:04.010\nis this just fantasy\n";
+        let lrc = subtitle_to_lrc(vtt, "vtt").unwrap();
+
+        assert_eq!(
+            lrc,
+            "[00:00.00]is this the real life\n[00:02.01]is this just fantasy"
+        );
+    }
+
+    #[test]
+    fn test_dedupe_keeps_chorus_repeated_later() {
+        let lines = vec![
+            TimedLine { start_ms: 0, text: "la la".to_string() },
+            TimedLine { start_ms: 1000, text: "one".to_string() },
+            TimedLine { start_ms: 2000, text: "two".to_string() },
+            TimedLine { start_ms: 3000, text: "three".to_string() },
+            TimedLine { start_ms: 4000, text: "la la".to_string() },
+        ];
+        let deduped = dedupe_rolling_lines(lines);
+
+        assert_eq!(deduped.len(), 5);
+    }
+
+    #[test]
+    fn test_dedupe_keeps_lines_repeated_in_adjacent_cues() {
+        let vtt = "WEBVTT\n\n00:00.000 --> 00:02.000\nLet it be\n\n00:02.000 --> 00:04.000\nLet it be\n\n00:04.000 --> 00:06.000\nLet it be\nWhisper words of wisdom\n";
+        let lrc = subtitle_to_lrc(vtt, "vtt").unwrap();
+
+        assert_eq!(
+            lrc,
+            "[00:00.00]Let it be\n[00:02.00]Let it be\n[00:04.00]Let it be\n[00:04.00]Whisper words of wisdom"
+        );
+    }
+
+    #[test]
+    fn test_dedupe_rolling_keeps_repeats_that_are_not_overlap() {
+        let lines = vec![
+            TimedLine { start_ms: 0, text: "la la".to_string() },
+            TimedLine { start_ms: 1000, text: "la la".to_string() },
+            TimedLine { start_ms: 1000, text: "one".to_string() },
+            TimedLine { start_ms: 2000, text: "la la".to_string() },
+        ];
+        let texts: Vec<String> = dedupe_rolling_lines(lines)
+            .into_iter()
+            .map(|line| line.text)
+            .collect();
+
+        assert_eq!(texts, vec!["la la", "one", "la la"]);
+    }
+
+    #[test]
+    fn test_parse_srv3() {
+        let srv3 = r#"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3"><body>
+<p t="1200" d="2000">Hello <s t="300">there</s></p>
+<p t="3400" d="1500" a="1">Second&#39;s line</p>
+<p t="5000" d="100"></p>
+</body></timedtext>"#;
+        let lines = parse_srv3(srv3);
+
+        assert_eq!(
+            lines,
+            vec![
+                TimedLine { start_ms: 1200, text: "Hello there".to_string() },
+                TimedLine { start_ms: 3400, text: "Second's line".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_lrc_formats_timestamps() {
+        let lines = vec![
+            TimedLine { start_ms: 65_430, text: "Line".to_string() },
+            TimedLine { start_ms: 3_600_000, text: "Hour".to_string() },
+        ];
+
+        assert_eq!(to_lrc(&lines), "[01:05.43]Line\n[60:00.00]Hour");
+    }
+
+    #[test]
+    fn test_subtitle_to_lrc_unsupported_format() {
+        let result = subtitle_to_lrc("", "ass");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_subtitle_file_to_lrc_nonexistent_file() {
+        let result = subtitle_file_to_lrc(Path::new("/nonexistent/subs.en.vtt"));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use serde_json::Value;
+use std::cell::RefCell;
+#[cfg(unix)]
+use std::fs;
+use std::future::Future;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::EventSink;
+
+/// Runs a future to completion on a fresh single-threaded runtime, so tests don't need the
+/// app's runtime.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("test runtime")
+        .block_on(future)
+}
+
+// Answers searches with the recorded results in search.jsonl and "downloads" a single silent
+// MPEG frame, then extracts the audio with the configured ffmpeg the way the real yt-dlp does,
+// printing the same lines it prints
+#[cfg(unix)]
+const FAKE_YTDLP: &str = r#"#!/bin/sh
+dir="$(dirname "$0")"
+printf '%s\n' "$*" >> "$dir/yt-dlp.log"
+case " $* " in
+  *" --version "*) echo "2024.12.13"; exit 0 ;;
+  *" --dump-json "*) cat "$dir/search.jsonl" 2>/dev/null; exit 0 ;;
+esac
+output=""
+ffmpeg="$dir/ffmpeg"
+previous=""
+for arg in "$@"; do
+  [ "$previous" = "--output" ] && output="$arg"
+  [ "$previous" = "--ffmpeg-location" ] && ffmpeg="$arg"
+  previous="$arg"
+done
+[ -d "$ffmpeg" ] && ffmpeg="$ffmpeg/ffmpeg"
+url="$previous"
+id="${url##*=}"
+if [ -e "$dir/unavailable/$id" ]; then
+  echo "ERROR: [youtube] $id: Video unavailable. This video has been removed by the uploader" >&2
+  exit 1
+fi
+title="$(cat "$dir/titles/$id" 2>/dev/null || echo "$id")"
+mkdir -p "${output%/*}"
+source="${output%/*}/$title [$id].webm"
+path="${output%/*}/$title [$id].mp3"
+echo "[youtube] Extracting URL: $url"
+echo "[download]  50.0% of 417.00B at 1.00KiB/s ETA 00:00"
+echo "[download] 100% of 417.00B in 00:00:01"
+printf '\377\373\220\144' > "$source"
+head -c 413 /dev/zero >> "$source"
+echo "[ExtractAudio] Destination: $path"
+"$ffmpeg" -y -loglevel repeat+info -i "$source" -vn -acodec libmp3lame "$path" || {
+  echo "ERROR: Postprocessing: audio conversion failed" >&2
+  exit 1
+}
+rm -f "$source"
+"#;
+
+// Copies the input to the output, which is enough for anything that only checks the file exists
+#[cfg(unix)]
+const FAKE_FFMPEG: &str = r#"#!/bin/sh
+dir="$(dirname "$0")"
+printf '%s\n' "$*" >> "$dir/ffmpeg.log"
+[ "$1" = "-version" ] && { echo "ffmpeg version 6.1-fake"; exit 0; }
+input=""
+previous=""
+for arg in "$@"; do
+  [ "$previous" = "-i" ] && input="$arg"
+  previous="$arg"
+done
+if [ ! -f "$input" ]; then
+  echo "$input: No such file or directory" >&2
+  exit 1
+fi
+cp "$input" "$previous"
+"#;
+
+/// Stand-in yt-dlp and ffmpeg executables in a scratch directory, so the real process plumbing
+/// can be exercised without network access. The directory is removed on drop.
+#[cfg(unix)]
+pub struct FakeTools {
+    dir: PathBuf,
+}
+
+#[cfg(unix)]
+impl FakeTools {
+    pub fn new(name: &str) -> FakeTools {
+        let dir = std::env::temp_dir().join(format!("lyricut_fake_tools_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("titles")).unwrap();
+        fs::create_dir_all(dir.join("unavailable")).unwrap();
+
+        for (name, script) in [("yt-dlp", FAKE_YTDLP), ("ffmpeg", FAKE_FFMPEG)] {
+            let path = dir.join(name);
+            fs::write(&path, script).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        FakeTools { dir }
+    }
+
+    pub fn ytdlp_path(&self) -> String {
+        self.dir.join("yt-dlp").to_string_lossy().into_owned()
+    }
+
+    pub fn ffmpeg_path(&self) -> String {
+        self.dir.join("ffmpeg").to_string_lossy().into_owned()
+    }
+
+    /// Where tests should point downloads.
+    pub fn output_dir(&self) -> PathBuf {
+        self.dir.join("music")
+    }
+
+    /// Adds a line to the yt-dlp JSON every search returns. The title is also what a download of
+    /// this id is named after.
+    pub fn add_search_result(&self, result: Value) {
+        let id = result["id"].as_str().expect("search result needs an id");
+        let title = result["title"].as_str().unwrap_or(id);
+        fs::write(self.dir.join("titles").join(id), title).unwrap();
+
+        let mut lines = fs::read_to_string(self.dir.join("search.jsonl")).unwrap_or_default();
+        lines.push_str(&result.to_string());
+        lines.push('\n');
+        fs::write(self.dir.join("search.jsonl"), lines).unwrap();
+    }
+
+    /// Makes downloads of `id` fail the way yt-dlp reports a removed video.
+    pub fn make_unavailable(&self, id: &str) {
+        fs::write(self.dir.join("unavailable").join(id), "").unwrap();
+    }
+
+    /// Sets the fakes up as the tools of an app data directory, where the app looks for its own
+    /// copies. They are wrapped rather than copied so they keep finding their data.
+    pub fn install_into(&self, data_dir: &Path) {
+        let installed = [
+            ("yt-dlp", data_dir.join("yt-dlp")),
+            ("ffmpeg", data_dir.join("ffmpeg").join("6.1").join("ffmpeg")),
+        ];
+        for (name, path) in installed {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let script = format!(
+                "#!/bin/sh\nexec '{}' \"$@\"\n",
+                self.dir.join(name).display()
+            );
+            fs::write(&path, script).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    pub fn ytdlp_calls(&self) -> Vec<String> {
+        read_lines(&self.dir.join("yt-dlp.log"))
+    }
+
+    pub fn ffmpeg_calls(&self) -> Vec<String> {
+        read_lines(&self.dir.join("ffmpeg.log"))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for FakeTools {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(unix)]
+fn read_lines(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Keeps every event the pipeline emits.
+#[derive(Default)]
+pub struct RecordedEvents {
+    events: RefCell<Vec<(String, Value)>>,
+}
+
+impl RecordedEvents {
+    pub fn named(&self, event: &str) -> Vec<Value> {
+        self.events
+            .borrow()
+            .iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+}
+
+impl EventSink for RecordedEvents {
+    fn emit_event(&self, event: &str, payload: Value) {
+        self.events.borrow_mut().push((event.to_string(), payload));
+    }
+}
@@ -0,0 +1,406 @@
+use regex::Regex;
+
+const TOPIC_SUFFIX: &str = " - Topic";
+const VEVO_SUFFIX: &str = "vevo";
+const OFFICIAL_SUFFIX: &str = "official";
+const SECONDARY_SEPARATORS: &[&str] = &[" | ", " // ", " ~ "];
+const QUOTE_PAIRS: &[(char, char)] = &[
+    ('"', '"'),
+    ('“', '”'),
+    ('„', '“'),
+    ('«', '»'),
+    ('「', '」'),
+    ('『', '』'),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedTitle {
+    pub artist: Option<String>,
+    pub title: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChannelKind {
+    Topic,
+    Vevo,
+    Official,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Channel {
+    name: String,
+    kind: ChannelKind,
+}
+
+impl Channel {
+    fn parse(uploader: &str) -> Option<Self> {
+        let uploader = uploader.trim();
+        if uploader.is_empty() {
+            return None;
+        }
+
+        if let Some(name) = uploader.strip_suffix(TOPIC_SUFFIX) {
+            return Some(Channel {
+                name: name.trim().to_string(),
+                kind: ChannelKind::Topic,
+            });
+        }
+
+        for (suffix, kind) in [
+            (VEVO_SUFFIX, ChannelKind::Vevo),
+            (OFFICIAL_SUFFIX, ChannelKind::Official),
+        ] {
+            let Some(split) = uploader.len().checked_sub(suffix.len()) else {
+                continue;
+            };
+            if split > 0
+                && uploader.is_char_boundary(split)
+                && uploader[split..].eq_ignore_ascii_case(suffix)
+            {
+                let name = uploader[..split].trim();
+                return Some(Channel {
+                    name: split_camel_case(name),
+                    kind,
+                });
+            }
+        }
+
+        Some(Channel {
+            name: uploader.to_string(),
+            kind: ChannelKind::Other,
+        })
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let channel_key = match_key(&self.name);
+        let value_key = match_key(value);
+        !channel_key.is_empty() && value_key.starts_with(&channel_key)
+    }
+}
+
+struct Candidate {
+    artist: Option<String>,
+    title: String,
+    confidence: f32,
+}
+
+fn normalize_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn match_key(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+// Turns channel handles such as "TaylorSwift" into "Taylor Swift"
+fn split_camel_case(value: &str) -> String {
+    if value.contains(' ') {
+        return value.to_string();
+    }
+
+    let mut result = String::with_capacity(value.len() + 4);
+    let mut previous: Option<char> = None;
+    for c in value.chars() {
+        if c.is_uppercase() && previous.map(|p| p.is_lowercase()).unwrap_or(false) {
+            result.push(' ');
+        }
+        result.push(c);
+        previous = Some(c);
+    }
+    result
+}
+
+fn strip_quotes(value: &str) -> String {
+    let value = value.trim();
+    for (open, close) in QUOTE_PAIRS.iter().chain(&[('\'', '\'')]) {
+        if let Some(inner) = value
+            .strip_prefix(*open)
+            .and_then(|rest| rest.strip_suffix(*close))
+        {
+            if !inner.trim().is_empty() {
+                return inner.trim().to_string();
+            }
+        }
+    }
+    value.to_string()
+}
+
+fn is_quoted(value: &str) -> bool {
+    strip_quotes(value) != value.trim()
+}
+
+fn trim_separator_noise(value: &str) -> &str {
+    value.trim_matches(|c: char| c.is_whitespace() || "-–—:|~/".contains(c))
+}
+
+fn candidate(artist: &str, title: &str, confidence: f32) -> Option<Candidate> {
+    let artist = strip_quotes(artist);
+    let title = strip_quotes(title);
+    if artist.is_empty() || title.is_empty() {
+        return None;
+    }
+    Some(Candidate {
+        artist: Some(artist),
+        title,
+        confidence,
+    })
+}
+
+fn split_dash(title: &str) -> Option<Candidate> {
+    // Plain hyphens need surrounding spaces so names like "Jay-Z" stay intact
+    let dash_regex = Regex::new(r"\s+-\s+|\s*[–—]\s*").expect("valid dash regex");
+    let found = dash_regex.find(title)?;
+    let left = title[..found.start()].trim();
+    let mut right = title[found.end()..].trim();
+
+    // Anything after a secondary separator is usually album or channel noise
+    for separator in SECONDARY_SEPARATORS {
+        if let Some((head, _)) = right.split_once(separator) {
+            if !head.trim().is_empty() {
+                right = head.trim();
+            }
+        }
+    }
+
+    // "\"Song\" - Artist" puts the quoted song first
+    if is_quoted(left) && !is_quoted(right) {
+        return candidate(right, left, 0.7);
+    }
+    candidate(left, right, 0.75)
+}
+
+fn split_quoted(title: &str) -> Option<Candidate> {
+    for (open, close) in QUOTE_PAIRS {
+        let Some(start) = title.find(*open) else {
+            continue;
+        };
+        let after_open = start + open.len_utf8();
+        let Some(length) = title[after_open..].find(*close) else {
+            continue;
+        };
+        let song = title[after_open..after_open + length].trim();
+        let before = trim_separator_noise(&title[..start]);
+        let after = title[after_open + length + close.len_utf8()..].trim();
+
+        if song.is_empty() {
+            continue;
+        }
+        if let Some(artist) = after
+            .strip_prefix("by ")
+            .or_else(|| after.strip_prefix("By "))
+        {
+            return candidate(artist, song, 0.8);
+        }
+        if !before.is_empty() {
+            return candidate(before, song, 0.8);
+        }
+    }
+
+    // Single quotes double as apostrophes, so only trust them wrapping the end of the title
+    let single_quote_regex =
+        Regex::new(r"^(.+?)\s'([^']+)'\s*$").expect("valid single quote regex");
+    let captures = single_quote_regex.captures(title)?;
+    candidate(trim_separator_noise(&captures[1]), &captures[2], 0.7)
+}
+
+fn split_secondary(title: &str) -> Option<Candidate> {
+    for separator in SECONDARY_SEPARATORS {
+        let parts: Vec<&str> = title
+            .split(separator)
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect();
+        if parts.len() >= 2 {
+            return candidate(parts[0], parts[1], 0.6);
+        }
+    }
+    None
+}
+
+// "by" is common inside song names ("Stand by Me"), so it only splits when the uploader confirms it
+fn split_by(title: &str, channel: Option<&Channel>) -> Option<Candidate> {
+    let channel = channel?;
+    let by_regex = Regex::new(r"(?i)^(.+)\s+by\s+(.+)$").expect("valid by regex");
+    let captures = by_regex.captures(title)?;
+    if !channel.matches(&captures[2]) {
+        return None;
+    }
+    candidate(&captures[2], &captures[1], 0.85)
+}
+
+fn split_colon(title: &str) -> Option<Candidate> {
+    let (left, right) = title.split_once(':')?;
+    if left.trim().chars().count() > 50 {
+        return None;
+    }
+    candidate(left, right, 0.5)
+}
+
+pub fn parse_title(raw_title: &str, uploader: Option<&str>) -> ParsedTitle {
+    let title = normalize_whitespace(raw_title);
+    let channel = uploader.and_then(Channel::parse);
+
+    // Topic channels are generated from label metadata, so the video title is the song name itself
+    if let Some(channel) = channel.as_ref().filter(|c| c.kind == ChannelKind::Topic) {
+        let song = split_dash(&title)
+            .filter(|c| c.artist.as_deref().map(|a| channel.matches(a)).unwrap_or(false))
+            .map(|c| c.title)
+            .unwrap_or_else(|| strip_quotes(&title));
+        return ParsedTitle {
+            artist: Some(channel.name.clone()),
+            title: song,
+            confidence: 0.95,
+        };
+    }
+
+    let parsed = split_dash(&title)
+        .or_else(|| split_quoted(&title))
+        .or_else(|| split_by(&title, channel.as_ref()))
+        .or_else(|| split_secondary(&title))
+        .or_else(|| split_colon(&title));
+
+    match (parsed, channel) {
+        (Some(mut parsed), Some(channel)) => {
+            let artist = parsed.artist.clone().unwrap_or_default();
+            if channel.matches(&artist) {
+                parsed.confidence = (parsed.confidence + 0.15).min(0.9);
+            } else if channel.matches(&parsed.title) {
+                // The uploader owns the second half, so the title was written "Song - Artist"
+                parsed = Candidate {
+                    artist: Some(parsed.title),
+                    title: artist,
+                    confidence: 0.85,
+                };
+            }
+            ParsedTitle {
+                artist: parsed.artist,
+                title: parsed.title,
+                confidence: parsed.confidence,
+            }
+        }
+        (Some(parsed), None) => ParsedTitle {
+            artist: parsed.artist,
+            title: parsed.title,
+            confidence: parsed.confidence,
+        },
+        (None, Some(channel)) if channel.kind != ChannelKind::Other => ParsedTitle {
+            artist: Some(channel.name),
+            title: strip_quotes(&title),
+            confidence: 0.7,
+        },
+        (None, _) => ParsedTitle {
+            artist: None,
+            title: strip_quotes(&title),
+            confidence: 0.3,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (video title, uploader, expected artist, expected title)
+    const CASES: &[(&str, Option<&str>, Option<&str>, &str)] = &[
+        ("Queen - Bohemian Rhapsody", None, Some("Queen"), "Bohemian Rhapsody"),
+        ("  Daft   Punk  -  Around the World ", None, Some("Daft Punk"), "Around the World"),
+        ("Jay-Z - Empire State of Mind", None, Some("Jay-Z"), "Empire State of Mind"),
+        ("Artist Name – Song Title", None, Some("Artist Name"), "Song Title"),
+        ("Artist Name—Song Title", None, Some("Artist Name"), "Song Title"),
+        ("Daft Punk - Harder, Better, Faster, Stronger", None, Some("Daft Punk"), "Harder, Better, Faster, Stronger"),
+        ("Daft Punk - Get Lucky | Random Access Memories", None, Some("Daft Punk"), "Get Lucky"),
+        ("Kavinsky - Nightcall // Drive Soundtrack", None, Some("Kavinsky"), "Nightcall"),
+        ("Metallica - \"One\"", None, Some("Metallica"), "One"),
+        ("\"Chandelier\" - Sia", None, Some("Sia"), "Chandelier"),
+        ("Adele | Hello", None, Some("Adele"), "Hello"),
+        ("Daft Punk // Get Lucky", None, Some("Daft Punk"), "Get Lucky"),
+        ("Nujabes ~ Aruarian Dance", None, Some("Nujabes"), "Aruarian Dance"),
+        ("Eminem \"Lose Yourself\"", None, Some("Eminem"), "Lose Yourself"),
+        ("Radiohead “Creep”", None, Some("Radiohead"), "Creep"),
+        ("Stromae «Papaoutai»", None, Some("Stromae"), "Papaoutai"),
+        ("宇多田ヒカル「First Love」", None, Some("宇多田ヒカル"), "First Love"),
+        ("Guns N' Roses 'Patience'", None, Some("Guns N' Roses"), "Patience"),
+        ("\"Hurt\" by Johnny Cash", None, Some("Johnny Cash"), "Hurt"),
+        ("Coldplay: Yellow", None, Some("Coldplay"), "Yellow"),
+        ("Stand by Me", None, None, "Stand by Me"),
+        ("Don't Stop Me Now", None, None, "Don't Stop Me Now"),
+        ("Song Title Only", None, None, "Song Title Only"),
+        ("Bohemian Rhapsody", Some("Queen - Topic"), Some("Queen"), "Bohemian Rhapsody"),
+        ("Queen - Bohemian Rhapsody", Some("Queen - Topic"), Some("Queen"), "Bohemian Rhapsody"),
+        ("Blinding Lights", Some("The Weeknd - Topic"), Some("The Weeknd"), "Blinding Lights"),
+        ("Love Me - Remastered", Some("Elvis Presley - Topic"), Some("Elvis Presley"), "Love Me - Remastered"),
+        ("Shake It Off", Some("TaylorSwiftVEVO"), Some("Taylor Swift"), "Shake It Off"),
+        ("Taylor Swift - Shake It Off", Some("TaylorSwiftVEVO"), Some("Taylor Swift"), "Shake It Off"),
+        ("Shake It Off - Taylor Swift", Some("TaylorSwiftVEVO"), Some("Taylor Swift"), "Shake It Off"),
+        ("Hello | Adele", Some("AdeleVEVO"), Some("Adele"), "Hello"),
+        ("The Weeknd - Blinding Lights", Some("TheWeekndVEVO"), Some("The Weeknd"), "Blinding Lights"),
+        ("Beyoncé - Halo", Some("beyonceVEVO"), Some("Beyoncé"), "Halo"),
+        ("In the End", Some("Linkin Park Official"), Some("Linkin Park"), "In the End"),
+        ("Hurt by Johnny Cash", Some("Johnny Cash"), Some("Johnny Cash"), "Hurt"),
+        ("Stand by Me", Some("Ben E. King"), None, "Stand by Me"),
+        ("Some Random Vlog", Some("CoolVlogger"), None, "Some Random Vlog"),
+    ];
+
+    #[test]
+    fn test_parse_title_real_world_cases() {
+        for (raw, uploader, artist, title) in CASES {
+            let parsed = parse_title(raw, *uploader);
+            assert_eq!(
+                (parsed.artist.as_deref(), parsed.title.as_str()),
+                (*artist, *title),
+                "parsing {:?} uploaded by {:?}",
+                raw,
+                uploader
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_title_confidence_is_within_bounds() {
+        for (raw, uploader, _, _) in CASES {
+            let confidence = parse_title(raw, *uploader).confidence;
+            assert!((0.0..=1.0).contains(&confidence), "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn test_topic_channel_is_most_confident() {
+        let topic = parse_title("Bohemian Rhapsody", Some("Queen - Topic"));
+        let dash = parse_title("Queen - Bohemian Rhapsody", None);
+        assert!(topic.confidence > dash.confidence);
+    }
+
+    #[test]
+    fn test_matching_uploader_raises_confidence() {
+        let alone = parse_title("Adele - Hello", None);
+        let confirmed = parse_title("Adele - Hello", Some("AdeleVEVO"));
+        assert!(confirmed.confidence > alone.confidence);
+    }
+
+    #[test]
+    fn test_unparsed_title_has_low_confidence() {
+        let parsed = parse_title("Song Title Only", None);
+        let split = parse_title("Adele | Hello", None);
+        assert!(parsed.confidence < split.confidence);
+    }
+
+    #[test]
+    fn test_channel_parse() {
+        let vevo = Channel::parse("TaylorSwiftVEVO").unwrap();
+        assert_eq!(vevo.name, "Taylor Swift");
+        assert_eq!(vevo.kind, ChannelKind::Vevo);
+
+        let topic = Channel::parse("Queen - Topic").unwrap();
+        assert_eq!(topic.name, "Queen");
+        assert_eq!(topic.kind, ChannelKind::Topic);
+
+        assert_eq!(Channel::parse("VEVO").unwrap().kind, ChannelKind::Other);
+        assert!(Channel::parse("  ").is_none());
+    }
+}
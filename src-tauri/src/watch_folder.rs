@@ -0,0 +1,486 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::batch_report::{BatchItemStatus, BatchReportRow, InputError};
+use crate::conflict::suffixed_path;
+use crate::error::{AppError, AppResult};
+use crate::link_resolver::OpenGraphResolver;
+use crate::pipeline::QueueItem;
+use crate::queue::{DownloadQueue, QueueEntry, QueueSource};
+use crate::settings::AppSettings;
+use crate::{build_input_items, import_csv_tracks, AudioMode};
+
+const DONE_DIR: &str = "done";
+const FAILED_DIR: &str = "failed";
+const REPORT_SUFFIX: &str = ".report.json";
+
+/// A folder whose new track lists are downloaded, each folder with its own destination and
+/// audio mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchFolder {
+    pub path: String,
+    pub output_path: String,
+    pub audio_mode: AudioMode,
+    /// CSV profile for the folder's CSV files; detected from the headers when not set.
+    pub profile: Option<String>,
+}
+
+impl Default for WatchFolder {
+    fn default() -> Self {
+        WatchFolder {
+            path: String::new(),
+            output_path: String::new(),
+            audio_mode: AudioMode::Official,
+            profile: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchSettings {
+    pub folders: Vec<WatchFolder>,
+    pub poll_seconds: u64,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        WatchSettings {
+            folders: Vec::new(),
+            poll_seconds: 10,
+        }
+    }
+}
+
+impl WatchSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.poll_seconds == 0 {
+            return Err("Watch folders need a poll interval of at least a second".to_string());
+        }
+        let mut seen = HashSet::new();
+        for folder in &self.folders {
+            for path in [&folder.path, &folder.output_path] {
+                if path.trim().is_empty() || !Path::new(path).is_absolute() {
+                    return Err(format!("Watch folder paths must be absolute: '{}'", path));
+                }
+            }
+            if !seen.insert(Path::new(&folder.path)) {
+                return Err(format!("Folder is watched twice: {}", folder.path));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Written next to each processed file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchReport {
+    pub file: String,
+    pub succeeded: bool,
+    /// Lines that couldn't be read, or why nothing was queued.
+    pub errors: Vec<String>,
+    pub items: Vec<BatchReportRow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    /// CSV exports and playlists, read by the track list importers.
+    TrackList,
+    /// One link or search per line, like the app's input box.
+    Lines,
+}
+
+fn file_kind(path: &Path) -> Option<FileKind> {
+    let hidden = path
+        .file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(true);
+    if hidden {
+        return None;
+    }
+    match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "csv" | "m3u" | "m3u8" => Some(FileKind::TrackList),
+        "txt" => Some(FileKind::Lines),
+        _ => None,
+    }
+}
+
+/// Remembers the size and modification time each file had at the last scan, so files still
+/// being copied in are left alone until they stop changing.
+#[derive(Default)]
+struct Scanner {
+    seen: HashMap<PathBuf, (u64, Option<SystemTime>)>,
+}
+
+impl Scanner {
+    fn ready_files(&mut self, dir: &Path) -> Vec<PathBuf> {
+        let mut current = HashMap::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_file() && file_kind(&path).is_some() {
+                    current.insert(path, (metadata.len(), metadata.modified().ok()));
+                }
+            }
+        }
+
+        let mut ready: Vec<PathBuf> = current
+            .iter()
+            .filter(|(path, state)| self.seen.get(*path) == Some(state))
+            .map(|(path, _)| path.clone())
+            .collect();
+        ready.sort();
+        self.seen.retain(|path, _| path.parent() != Some(dir));
+        self.seen.extend(current);
+        ready
+    }
+}
+
+fn messages(errors: &[InputError]) -> Vec<String> {
+    errors.iter().map(InputError::to_string).collect()
+}
+
+async fn read_items(
+    settings: &AppSettings,
+    folder: &WatchFolder,
+    path: &Path,
+) -> AppResult<(Vec<QueueItem>, Vec<String>)> {
+    let content = fs::read(path)
+        .map_err(|e| AppError::io(&format!("Failed to read {}", path.display()), e))?;
+
+    if file_kind(path) == Some(FileKind::Lines) {
+        let resolver = OpenGraphResolver::new(&settings.resolver)?;
+        let text = String::from_utf8_lossy(&content);
+        let input = build_input_items(&text, &folder.audio_mode, &resolver).await;
+        let items = input
+            .items
+            .into_iter()
+            .map(QueueItem::from_processed)
+            .collect();
+        return Ok((items, messages(&input.errors)));
+    }
+
+    let import = import_csv_tracks(
+        &content,
+        settings,
+        folder.profile.as_deref(),
+        &folder.audio_mode,
+    )?;
+    let items = import.tracks.into_iter().map(QueueItem::from_csv).collect();
+    Ok((items, messages(&import.errors)))
+}
+
+/// Downloads everything in a dropped file through the queue, then moves it to `done/` or
+/// `failed/` with its report. Returns where the file went.
+async fn ingest_file(
+    queue: &DownloadQueue,
+    settings: &AppSettings,
+    folder: &WatchFolder,
+    path: &Path,
+) -> AppResult<PathBuf> {
+    let (items, mut errors) = match read_items(settings, folder, path).await {
+        Ok(read) => read,
+        Err(e) => (Vec::new(), vec![e.message]),
+    };
+
+    let mut rows = Vec::new();
+    if items.is_empty() {
+        if errors.is_empty() {
+            errors.push("Nothing to download in this file".to_string());
+        }
+    } else {
+        let entries = queue
+            .run_batch(
+                items,
+                &folder.output_path,
+                &folder.audio_mode,
+                QueueSource::WatchFolder,
+            )
+            .await;
+        rows.extend(entries.iter().map(QueueEntry::report_row));
+    }
+
+    let succeeded = errors.is_empty()
+        && rows.iter().all(|row| {
+            matches!(
+                row.status,
+                BatchItemStatus::Downloaded | BatchItemStatus::Deduplicated
+            )
+        });
+    let report = WatchReport {
+        file: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        succeeded,
+        errors,
+        items: rows,
+    };
+    file_away(path, &report)
+}
+
+fn file_away(path: &Path, report: &WatchReport) -> AppResult<PathBuf> {
+    let dir = path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(if report.succeeded {
+            DONE_DIR
+        } else {
+            FAILED_DIR
+        });
+    fs::create_dir_all(&dir)
+        .map_err(|e| AppError::io(&format!("Failed to create {}", dir.display()), e))?;
+
+    // An earlier file of the same name stays where it is
+    let desired = dir.join(&report.file);
+    let destination = std::iter::once(desired.clone())
+        .chain((2..).map(|index| suffixed_path(&desired, index)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(desired);
+    fs::rename(path, &destination)
+        .map_err(|e| AppError::io(&format!("Failed to move {}", path.display()), e))?;
+
+    let report_name = format!(
+        "{}{}",
+        destination
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        REPORT_SUFFIX
+    );
+    let content = serde_json::to_string_pretty(report)
+        .map_err(|e| AppError::internal(format!("Failed to write report: {}", e)))?;
+    fs::write(destination.with_file_name(report_name), content)
+        .map_err(|e| AppError::io("Failed to write report", e))?;
+    Ok(destination)
+}
+
+/// Polls the configured folders for as long as the app is open. Settings are read on every
+/// poll, so folders can be added or changed without a restart.
+#[cfg(feature = "gui")]
+pub async fn run_watcher(queue: &DownloadQueue, data_dir: &Path) {
+    let mut scanner = Scanner::default();
+    loop {
+        let settings = crate::settings::load_settings(&crate::settings::settings_path(data_dir))
+            .unwrap_or_else(|e| {
+                queue.publish(
+                    "watch-folder-error",
+                    serde_json::json!({ "path": null, "error": e }),
+                );
+                AppSettings::default()
+            });
+        for folder in &settings.watch.folders {
+            for path in scanner.ready_files(Path::new(&folder.path)) {
+                if let Err(e) = ingest_file(queue, &settings, folder, &path).await {
+                    queue.publish(
+                        "watch-folder-error",
+                        serde_json::json!({ "path": path.to_string_lossy(), "error": e }),
+                    );
+                }
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(
+            settings.watch.poll_seconds.max(1),
+        ))
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::block_on;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lyricut_watch_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read_report(path: &Path) -> WatchReport {
+        let name = format!(
+            "{}{}",
+            path.file_name().unwrap().to_string_lossy(),
+            REPORT_SUFFIX
+        );
+        serde_json::from_str(&fs::read_to_string(path.with_file_name(name)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(WatchSettings::default().validate().is_ok());
+        let folder = WatchFolder {
+            path: "/drop".to_string(),
+            output_path: "/music".to_string(),
+            ..WatchFolder::default()
+        };
+        let settings = WatchSettings {
+            folders: vec![folder.clone()],
+            ..WatchSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        let twice = WatchSettings {
+            folders: vec![folder.clone(), folder.clone()],
+            ..WatchSettings::default()
+        };
+        assert!(twice.validate().is_err());
+        let relative = WatchSettings {
+            folders: vec![WatchFolder {
+                output_path: "music".to_string(),
+                ..folder
+            }],
+            ..WatchSettings::default()
+        };
+        assert!(relative.validate().is_err());
+        let never = WatchSettings {
+            poll_seconds: 0,
+            ..settings
+        };
+        assert!(never.validate().is_err());
+    }
+
+    #[test]
+    fn test_files_are_ready_once_they_stop_changing() {
+        let dir = test_dir("scanner");
+        fs::write(dir.join("playlist.csv"), "Artist,Track\n").unwrap();
+        fs::write(dir.join("notes.pdf"), "").unwrap();
+        fs::write(dir.join(".hidden.txt"), "").unwrap();
+        fs::create_dir_all(dir.join(DONE_DIR)).unwrap();
+        let mut scanner = Scanner::default();
+
+        assert!(scanner.ready_files(&dir).is_empty());
+        assert_eq!(scanner.ready_files(&dir), vec![dir.join("playlist.csv")]);
+
+        fs::write(
+            dir.join("playlist.csv"),
+            "Artist,Track\nQueen,Bohemian Rhapsody\n",
+        )
+        .unwrap();
+        fs::write(dir.join("links.TXT"), "").unwrap();
+        assert!(scanner.ready_files(&dir).is_empty());
+        assert_eq!(
+            scanner.ready_files(&dir),
+            vec![dir.join("links.TXT"), dir.join("playlist.csv")]
+        );
+        assert!(scanner.ready_files(&dir.join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_file_moves_to_failed_with_a_report() {
+        let dir = test_dir("unreadable");
+        let folder = WatchFolder {
+            path: dir.to_string_lossy().into_owned(),
+            output_path: dir.join("music").to_string_lossy().into_owned(),
+            ..WatchFolder::default()
+        };
+        let queue = DownloadQueue::new();
+        let settings = AppSettings::default();
+
+        fs::create_dir_all(dir.join(FAILED_DIR)).unwrap();
+        fs::write(dir.join(FAILED_DIR).join("empty.txt"), "earlier").unwrap();
+        fs::write(dir.join("empty.txt"), "\n\n").unwrap();
+
+        let moved = block_on(ingest_file(
+            &queue,
+            &settings,
+            &folder,
+            &dir.join("empty.txt"),
+        ))
+        .unwrap();
+        assert_eq!(moved, dir.join(FAILED_DIR).join("empty (2).txt"));
+        assert!(!dir.join("empty.txt").exists());
+        let report = read_report(&moved);
+        assert!(!report.succeeded);
+        assert_eq!(report.file, "empty.txt");
+        assert_eq!(report.errors, vec!["Nothing to download in this file"]);
+        assert!(queue.history(10).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dropped_files_are_downloaded_and_filed() {
+        use crate::retry::RetryPolicy;
+        use crate::settings::{save_settings, settings_path};
+        use crate::test_support::FakeTools;
+        use serde_json::json;
+
+        let tools = FakeTools::new("watch_folder");
+        tools.add_search_result(json!({
+            "id": "fJ9rUzIMcZQ",
+            "title": "Queen - Bohemian Rhapsody (Official Audio)",
+            "uploader": "Queen Official",
+            "duration": 355
+        }));
+        tools.make_unavailable("removed0001");
+
+        let dir = test_dir("end_to_end");
+        let data_dir = dir.join("data");
+        tools.install_into(&data_dir);
+        let settings = AppSettings {
+            retry: RetryPolicy {
+                initial_delay_ms: 0,
+                max_delay_ms: 0,
+                ..RetryPolicy::default()
+            },
+            ..AppSettings::default()
+        };
+        save_settings(&settings_path(&data_dir), &settings).unwrap();
+
+        let queue: &'static DownloadQueue = Box::leak(Box::new(DownloadQueue::new()));
+        let worker_dir = data_dir.clone();
+        std::thread::spawn(move || block_on(crate::queue::run_worker(queue, &worker_dir)));
+
+        let drop_dir = dir.join("drop");
+        fs::create_dir_all(&drop_dir).unwrap();
+        let folder = WatchFolder {
+            path: drop_dir.to_string_lossy().into_owned(),
+            output_path: tools.output_dir().to_string_lossy().into_owned(),
+            ..WatchFolder::default()
+        };
+
+        fs::write(
+            drop_dir.join("curated.csv"),
+            "Artist Name(s),Track Name,Album Name\nQueen,Bohemian Rhapsody,A Night at the Opera\n",
+        )
+        .unwrap();
+        let moved = block_on(ingest_file(
+            queue,
+            &settings,
+            &folder,
+            &drop_dir.join("curated.csv"),
+        ))
+        .unwrap();
+        assert_eq!(moved, drop_dir.join(DONE_DIR).join("curated.csv"));
+        let report = read_report(&moved);
+        assert!(report.succeeded, "{:?}", report);
+        assert_eq!(report.items.len(), 1);
+        assert_eq!(report.items[0].video_id.as_deref(), Some("fJ9rUzIMcZQ"));
+        assert_eq!(queue.history(1)[0].source, QueueSource::WatchFolder);
+
+        fs::write(
+            drop_dir.join("links.txt"),
+            "https://www.youtube.com/watch?v=removed0001\n",
+        )
+        .unwrap();
+        let moved = block_on(ingest_file(
+            queue,
+            &settings,
+            &folder,
+            &drop_dir.join("links.txt"),
+        ))
+        .unwrap();
+        assert_eq!(moved, drop_dir.join(FAILED_DIR).join("links.txt"));
+        let report = read_report(&moved);
+        assert!(!report.succeeded);
+        assert_eq!(report.items[0].status, BatchItemStatus::Failed);
+    }
+}
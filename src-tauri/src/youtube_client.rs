@@ -1,8 +1,15 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::process::{Command, Stdio};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::subtitles::SubtitleOptions;
+
+const SUBTITLE_EXTENSIONS: &[&str] = &["vtt", "srv3"];
+const YOUTUBE_MUSIC_SEARCH_URL: &str = "https://music.youtube.com/search";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VideoInfo {
     pub id: String,
     pub title: String,
@@ -11,139 +18,122 @@ pub struct VideoInfo {
     pub uploader: Option<String>,
     pub duration_seconds: Option<u64>,
     pub upload_date: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub match_score: Option<i32>,
 }
 
-pub fn search_video(ytdlp_path: &str, query: &str) -> Result<Option<VideoInfo>, String> {
-    let search_query = format!("ytsearch10:{}", query);
-
-    let output = Command::new(ytdlp_path)
-        .args([
-            "--dump-json",
-            "--no-download",
-            "--quiet",
-            "--no-warnings",
-            &search_query,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("yt-dlp search failed: {}", stderr));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+pub fn music_search_url(query: &str) -> AppResult<String> {
+    let mut url = reqwest::Url::parse_with_params(YOUTUBE_MUSIC_SEARCH_URL, &[("q", query)])
+        .map_err(|e| AppError::invalid_input(format!("Invalid search query: {}", e)))?;
+    // Restrict results to the "Songs" shelf so artists, albums and videos are skipped
+    url.set_fragment(Some("songs"));
+    Ok(url.to_string())
+}
 
-    if lines.is_empty() {
-        return Ok(None);
-    }
+pub fn parse_search_output(stdout: &str) -> Vec<VideoInfo> {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|json| parse_video_info(&json).ok())
+        .collect()
+}
 
-    if let Some(first_line) = lines.first() {
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(first_line) {
-            let id = json["id"]
-                .as_str()
-                .ok_or("No video ID in response")?
-                .to_string();
-            let title = json["title"].as_str().unwrap_or("").to_string();
-            let url = format!("https://www.youtube.com/watch?v={}", id);
-
-            let thumbnail_url = json["thumbnail"]
-                .as_str()
-                .map(|s| s.to_string())
-                .or_else(|| {
-                    json["thumbnails"]
-                        .as_array()
-                        .and_then(|thumbnails| thumbnails.first())
-                        .and_then(|t| t["url"].as_str())
-                        .map(|s| s.to_string())
-                });
-
-            let uploader = json["uploader"]
-                .as_str()
+fn parse_video_info(json: &serde_json::Value) -> AppResult<VideoInfo> {
+    let id = json["id"]
+        .as_str()
+        .ok_or_else(|| AppError::new(ErrorKind::ToolFailed, "No video ID in response"))?
+        .to_string();
+    let title = json["title"].as_str().unwrap_or("").to_string();
+    let url = json["webpage_url"]
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", id));
+
+    let thumbnail_url = json["thumbnail"]
+        .as_str()
+        .map(|s| s.to_string())
+        .or_else(|| {
+            json["thumbnails"]
+                .as_array()
+                .and_then(|thumbnails| thumbnails.first())
+                .and_then(|t| t["url"].as_str())
                 .map(|s| s.to_string())
-                .or_else(|| json["uploader_name"].as_str().map(|s| s.to_string()));
-
-            let duration_seconds = json["duration"]
-                .as_u64()
-                .or_else(|| json["duration_seconds"].as_u64());
-
-            let upload_date = json["upload_date"].as_str().map(|s| s.to_string());
-
-            return Ok(Some(VideoInfo {
-                id,
-                title,
-                url,
-                thumbnail_url,
-                uploader,
-                duration_seconds,
-                upload_date,
-            }));
-        }
-    }
-
-    Ok(None)
+        });
+
+    let uploader = json["uploader"]
+        .as_str()
+        .map(|s| s.to_string())
+        .or_else(|| json["uploader_name"].as_str().map(|s| s.to_string()));
+
+    let duration_seconds = json["duration"]
+        .as_u64()
+        .or_else(|| json["duration_seconds"].as_u64());
+
+    let upload_date = json["upload_date"].as_str().map(|s| s.to_string());
+    let description = json["description"]
+        .as_str()
+        .map(|s| s.to_string())
+        .filter(|s| !s.trim().is_empty());
+    let source = json["extractor_key"]
+        .as_str()
+        .or_else(|| json["extractor"].as_str())
+        .map(|s| s.to_string());
+
+    Ok(VideoInfo {
+        id,
+        title,
+        url,
+        thumbnail_url,
+        uploader,
+        duration_seconds,
+        upload_date,
+        description,
+        source,
+        match_score: None,
+    })
 }
 
-pub fn download_stream(
-    ytdlp_path: &str,
-    video_id: &str,
-    output_path: &str,
-    ffmpeg_location: Option<&str>,
-    on_progress: impl Fn(f64, &str) + Send + 'static,
-) -> Result<String, String> {
-    let video_url = format!("https://www.youtube.com/watch?v={}", video_id);
-    let output_template = format!("{}/%(title)s [%(id)s].%(ext)s", output_path);
-
-    on_progress(0.0, "Starting download...");
-
-    let mut args: Vec<String> = vec![
-        "--format".to_string(),
-        "bestaudio[ext=m4a]/bestaudio[ext=webm]/bestaudio".to_string(),
-        "--output".to_string(),
-        output_template,
-        "--extract-audio".to_string(),
-        "--audio-format".to_string(),
-        "mp3".to_string(),
-        "--audio-quality".to_string(),
-        "0".to_string(),
-        "--no-playlist".to_string(),
-        "--no-warnings".to_string(),
-        "--progress".to_string(),
-    ];
-    if let Some(location) = ffmpeg_location {
-        args.push("--ffmpeg-location".to_string());
-        args.push(location.to_string());
-    }
-    args.push(video_url);
-
-    let output = Command::new(ytdlp_path)
-        .args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
-
-    let output = output
-        .wait_with_output()
-        .map_err(|e| format!("Failed to wait for yt-dlp: {}", e))?;
-
-    on_progress(90.0, "Processing complete...");
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("yt-dlp download failed: {}", stderr));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let extracted_filename = extract_downloaded_filename(&stdout);
-
-    on_progress(100.0, "Download complete");
+pub fn subtitle_args(options: &SubtitleOptions) -> Vec<String> {
+    // yt-dlp prefers manual subtitles over auto-captions when both are requested
+    vec![
+        "--write-subs".to_string(),
+        "--write-auto-subs".to_string(),
+        "--sub-langs".to_string(),
+        options.language.clone(),
+        "--sub-format".to_string(),
+        "vtt/srv3/best".to_string(),
+    ]
+}
 
-    extracted_filename.ok_or("Failed to determine downloaded filename".to_string())
+pub fn find_subtitle_file(downloaded_path: &str) -> Option<PathBuf> {
+    let path = Path::new(downloaded_path);
+    let stem = path.file_stem()?.to_str()?;
+    let prefix = format!("{}.", stem);
+    let parent = path.parent()?;
+
+    fs::read_dir(parent)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .find(|candidate| {
+            let name_matches = candidate
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with(&prefix))
+                .unwrap_or(false);
+            let extension_matches = candidate
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| SUBTITLE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                .unwrap_or(false);
+            name_matches && extension_matches
+        })
 }
 
-fn extract_downloaded_filename(output: &str) -> Option<String> {
+pub fn extract_downloaded_filename(output: &str) -> Option<String> {
     let patterns = [
         r"\[ExtractAudio\] Destination: (.+\.mp3)",
         r"\[Merger\] Merging formats into (.+\.mp3)",
@@ -177,6 +167,9 @@ mod tests {
             uploader: Some("TestUploader".to_string()),
             duration_seconds: Some(180),
             upload_date: Some("2024-01-01".to_string()),
+            description: None,
+            source: None,
+            match_score: None,
         };
 
         let serialized = serde_json::to_string(&video_info).unwrap();
@@ -201,6 +194,9 @@ mod tests {
             uploader: None,
             duration_seconds: None,
             upload_date: None,
+            description: None,
+            source: None,
+            match_score: None,
         };
 
         assert_eq!(video_info.id, "test123");
@@ -263,68 +259,110 @@ mod tests {
     }
 
     #[test]
-    fn test_search_video_empty_query_returns_error() {
-        let result = search_video("yt-dlp", "");
-        assert!(result.is_err() || result.unwrap().is_none());
+    fn test_subtitle_args_requests_manual_and_auto_subs() {
+        let options = SubtitleOptions {
+            language: "es".to_string(),
+            ..SubtitleOptions::default()
+        };
+        let args = subtitle_args(&options);
+
+        assert!(args.contains(&"--write-subs".to_string()));
+        assert!(args.contains(&"--write-auto-subs".to_string()));
+        let lang_index = args.iter().position(|a| a == "--sub-langs").unwrap();
+        assert_eq!(args[lang_index + 1], "es");
     }
 
     #[test]
-    fn test_search_video_whitespace_only_query_returns_error() {
-        let result = search_video("yt-dlp", "   ");
-        assert!(result.is_err() || result.unwrap().is_none());
+    fn test_find_subtitle_file_next_to_download() {
+        let dir = std::env::temp_dir().join("find_subtitle_file_test");
+        fs::create_dir_all(&dir).unwrap();
+        let audio = dir.join("Song [abc123def45].mp3");
+        let subtitle = dir.join("Song [abc123def45].en.vtt");
+        fs::write(&audio, b"audio").unwrap();
+        fs::write(&subtitle, b"WEBVTT").unwrap();
+
+        let found = find_subtitle_file(audio.to_str().unwrap());
+        assert_eq!(found, Some(subtitle));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_find_subtitle_file_missing() {
+        let dir = std::env::temp_dir().join("find_subtitle_file_missing_test");
+        fs::create_dir_all(&dir).unwrap();
+        let audio = dir.join("Other Song.mp3");
+        fs::write(&audio, b"audio").unwrap();
+
+        assert_eq!(find_subtitle_file(audio.to_str().unwrap()), None);
+
+        fs::remove_dir_all(&dir).ok();
     }
 
     #[test]
-    fn test_download_stream_invalid_video_id_returns_error() {
-        let temp_dir = std::env::temp_dir();
-        let result = download_stream(
-            "yt-dlp",
-            "invalid_id_that_does_not_exist_12345",
-            temp_dir.to_str().unwrap(),
-            None,
-            |_, _| {},
+    fn test_parse_search_output_keeps_every_candidate() {
+        let stdout = concat!(
+            r#"{"id":"first","title":"One"}"#,
+            "\n",
+            "not json\n",
+            r#"{"title":"missing id"}"#,
+            "\n",
+            r#"{"id":"second","title":"Two","duration":200}"#,
+            "\n",
         );
-        assert!(result.is_err());
+
+        let candidates = parse_search_output(stdout);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].id, "first");
+        assert_eq!(candidates[1].duration_seconds, Some(200));
     }
 
     #[test]
-    fn test_download_stream_invalid_path_returns_error() {
-        let result = download_stream(
-            "yt-dlp",
-            "dQw4w9WgXcQ",
-            "/nonexistent/path/that/does/not/exist",
-            None,
-            |_, _| {},
+    fn test_music_search_url_encodes_query() {
+        let url = music_search_url("AC/DC Back in Black & more").unwrap();
+        assert_eq!(
+            url,
+            "https://music.youtube.com/search?q=AC%2FDC+Back+in+Black+%26+more#songs"
         );
-        assert!(result.is_err());
     }
 
     #[test]
-    fn test_download_stream_progress_callback() {
-        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-        use std::sync::Arc;
-
-        let called = Arc::new(AtomicBool::new(false));
-        let progress_count = Arc::new(AtomicUsize::new(0));
-        let called_clone = called.clone();
-        let progress_count_clone = progress_count.clone();
-
-        let temp_dir = std::env::temp_dir();
-        let _result = download_stream(
-            "yt-dlp",
-            "dQw4w9WgXcQ",
-            temp_dir.to_str().unwrap(),
-            None,
-            move |progress, message| {
-                called_clone.store(true, Ordering::SeqCst);
-                progress_count_clone.fetch_add(1, Ordering::SeqCst);
-                assert!(progress >= 0.0 && progress <= 100.0);
-                assert!(!message.is_empty());
-            },
+    fn test_parse_video_info_reads_description() {
+        let json = serde_json::json!({
+            "id": "abc123",
+            "title": "Bohemian Rhapsody",
+            "uploader": "Queen - Topic",
+            "duration": 355,
+            "description": "Provided to YouTube by EMI"
+        });
+        let info = parse_video_info(&json).unwrap();
+
+        assert_eq!(info.url, "https://www.youtube.com/watch?v=abc123");
+        assert_eq!(info.uploader.as_deref(), Some("Queen - Topic"));
+        assert_eq!(
+            info.description.as_deref(),
+            Some("Provided to YouTube by EMI")
         );
+    }
 
-        assert!(called.load(Ordering::SeqCst));
-        assert!(progress_count.load(Ordering::SeqCst) > 0);
+    #[test]
+    fn test_parse_video_info_other_site() {
+        let json = serde_json::json!({
+            "id": "1234567890",
+            "title": "Track",
+            "webpage_url": "https://soundcloud.com/artist/track",
+            "extractor_key": "Soundcloud"
+        });
+        let info = parse_video_info(&json).unwrap();
+
+        assert_eq!(info.url, "https://soundcloud.com/artist/track");
+        assert_eq!(info.source.as_deref(), Some("Soundcloud"));
+    }
+
+    #[test]
+    fn test_parse_video_info_requires_id() {
+        assert!(parse_video_info(&serde_json::json!({ "title": "No id" })).is_err());
     }
 
     #[test]
@@ -337,6 +375,9 @@ mod tests {
             uploader: Some("TestUploader".to_string()),
             duration_seconds: Some(200),
             upload_date: Some("2024-02-01".to_string()),
+            description: None,
+            source: None,
+            match_score: None,
         };
         let cloned = video_info.clone();
         assert_eq!(video_info.id, cloned.id);
@@ -358,6 +399,9 @@ mod tests {
             uploader: None,
             duration_seconds: None,
             upload_date: None,
+            description: None,
+            source: None,
+            match_score: None,
         };
         let debug = format!("{:?}", video_info);
         assert!(debug.contains("abc"));
@@ -0,0 +1,385 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::rate_limit;
+use crate::sources::media_url;
+use crate::subtitles::SubtitleOptions;
+use crate::youtube_client::{
+    extract_downloaded_filename, music_search_url, parse_search_output, subtitle_args, VideoInfo,
+};
+use crate::ytdlp_command::{YtDlpCommand, YtDlpSettings};
+
+const VIDEO_SEARCH_RESULTS: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs one yt-dlp invocation to completion. Tests substitute a scripted runner so nothing
+/// touches the network.
+#[allow(async_fn_in_trait)]
+pub trait YtDlpRunner {
+    async fn run(&self, command: &YtDlpCommand, timeout: Duration) -> AppResult<ProcessOutput>;
+}
+
+/// Spawns the real binary without blocking the async runtime.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessRunner;
+
+impl YtDlpRunner for ProcessRunner {
+    async fn run(&self, command: &YtDlpCommand, timeout: Duration) -> AppResult<ProcessOutput> {
+        let mut process = tokio::process::Command::from(command.command());
+        process
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // Dropping the wait on timeout then kills yt-dlp instead of leaving it running
+            .kill_on_drop(true);
+
+        let child = process
+            .spawn()
+            .map_err(|e| AppError::spawn("yt-dlp", ErrorKind::YtdlpMissing, e))?;
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                AppError::new(
+                    ErrorKind::TimedOut,
+                    format!("yt-dlp timed out after {} seconds", timeout.as_secs()),
+                )
+            })?
+            .map_err(|e| AppError::io("Failed to wait for yt-dlp", e))?;
+
+        Ok(ProcessOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// yt-dlp with the user's global options and timeouts applied to every call.
+pub struct YtDlp<R: YtDlpRunner = ProcessRunner> {
+    command: YtDlpCommand,
+    runner: R,
+    search_timeout: Duration,
+    download_timeout: Duration,
+}
+
+impl YtDlp {
+    pub fn new(program: &str, settings: &YtDlpSettings) -> YtDlp {
+        YtDlp::with_runner(program, settings, ProcessRunner)
+    }
+}
+
+impl<R: YtDlpRunner> YtDlp<R> {
+    pub fn with_runner(program: &str, settings: &YtDlpSettings, runner: R) -> YtDlp<R> {
+        YtDlp {
+            command: YtDlpCommand::new(program, settings),
+            runner,
+            search_timeout: Duration::from_secs(settings.search_timeout_seconds),
+            download_timeout: Duration::from_secs(settings.download_timeout_seconds),
+        }
+    }
+
+    pub async fn search_videos(&self, query: &str) -> AppResult<Vec<VideoInfo>> {
+        if query.trim().is_empty() {
+            return Err(AppError::invalid_input("Search query is empty"));
+        }
+        let search_query = format!("ytsearch{}:{}", VIDEO_SEARCH_RESULTS, query.trim());
+        self.search(vec![search_query]).await
+    }
+
+    pub async fn search_music(&self, query: &str, limit: usize) -> AppResult<Vec<VideoInfo>> {
+        if query.trim().is_empty() {
+            return Err(AppError::invalid_input("Search query is empty"));
+        }
+        let search_url = music_search_url(query.trim())?;
+        let items = format!("1-{}", limit.max(1));
+        self.search(vec!["--playlist-items".to_string(), items, search_url])
+            .await
+    }
+
+    async fn search(&self, search_args: Vec<String>) -> AppResult<Vec<VideoInfo>> {
+        rate_limit::global().wait_for_search().await;
+        let command = self
+            .command
+            .clone()
+            .args(["--dump-json", "--no-download", "--quiet", "--no-warnings"])
+            .args(search_args);
+        let output = self.runner.run(&command, self.search_timeout).await?;
+
+        if !output.success {
+            return Err(AppError::from_tool_stderr("yt-dlp search", &output.stderr));
+        }
+        Ok(parse_search_output(&output.stdout))
+    }
+
+    /// Downloads `target` as an MP3 into `output_path` and returns the downloaded file's path.
+    pub async fn download(
+        &self,
+        target: &str,
+        output_path: &str,
+        ffmpeg_location: Option<&str>,
+        subtitles: Option<&SubtitleOptions>,
+        limit_rate: Option<u64>,
+        on_progress: impl Fn(f64, &str),
+    ) -> AppResult<String> {
+        let output_template = format!("{}/%(title)s [%(id)s].%(ext)s", output_path);
+
+        on_progress(0.0, "Starting download...");
+
+        let mut command = self.command.clone().args([
+            "--format",
+            "bestaudio[ext=m4a]/bestaudio[ext=webm]/bestaudio",
+            "--output",
+            output_template.as_str(),
+            "--extract-audio",
+            "--audio-format",
+            "mp3",
+            "--audio-quality",
+            "0",
+            "--no-playlist",
+            "--no-warnings",
+            "--progress",
+        ]);
+        if let Some(location) = ffmpeg_location {
+            command = command.args(["--ffmpeg-location", location]);
+        }
+        if let Some(options) = subtitles {
+            command = command.args(subtitle_args(options));
+        }
+        if let Some(bytes_per_second) = limit_rate {
+            command = command.args(["--limit-rate".to_string(), bytes_per_second.to_string()]);
+        }
+        let command = command.arg(media_url(target));
+
+        let output = self.runner.run(&command, self.download_timeout).await?;
+
+        on_progress(90.0, "Processing complete...");
+
+        if !output.success {
+            return Err(AppError::from_tool_stderr(
+                "yt-dlp download",
+                &output.stderr,
+            ));
+        }
+
+        let extracted_filename = extract_downloaded_filename(&output.stdout);
+
+        on_progress(100.0, "Download complete");
+
+        extracted_filename
+            .ok_or_else(|| AppError::internal("Failed to determine downloaded filename"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use crate::test_support::block_on;
+
+    /// Replays canned outputs in order and records the arguments of every call.
+    #[derive(Default)]
+    struct ScriptedRunner {
+        outputs: RefCell<VecDeque<AppResult<ProcessOutput>>>,
+        calls: RefCell<Vec<(Vec<String>, Duration)>>,
+    }
+
+    impl ScriptedRunner {
+        fn then(self, output: AppResult<ProcessOutput>) -> ScriptedRunner {
+            self.outputs.borrow_mut().push_back(output);
+            self
+        }
+
+        fn succeeds(self, stdout: &str) -> ScriptedRunner {
+            self.then(Ok(ProcessOutput {
+                success: true,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            }))
+        }
+
+        fn fails(self, stderr: &str) -> ScriptedRunner {
+            self.then(Ok(ProcessOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: stderr.to_string(),
+            }))
+        }
+    }
+
+    impl YtDlpRunner for ScriptedRunner {
+        async fn run(&self, command: &YtDlpCommand, timeout: Duration) -> AppResult<ProcessOutput> {
+            self.calls.borrow_mut().push((command.full_args(), timeout));
+            self.outputs
+                .borrow_mut()
+                .pop_front()
+                .unwrap_or_else(|| Err(AppError::internal("unexpected yt-dlp call")))
+        }
+    }
+
+    fn ytdlp(runner: ScriptedRunner) -> YtDlp<ScriptedRunner> {
+        YtDlp::with_runner("yt-dlp", &YtDlpSettings::default(), runner)
+    }
+
+    #[test]
+    fn test_search_videos_parses_every_result() {
+        let ytdlp = ytdlp(ScriptedRunner::default().succeeds(concat!(
+            r#"{"id":"first","title":"One"}"#,
+            "\n",
+            r#"{"id":"second","title":"Two","duration":200}"#,
+            "\n",
+        )));
+
+        let results = block_on(ytdlp.search_videos(" queen bohemian rhapsody ")).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].duration_seconds, Some(200));
+        let calls = ytdlp.runner.calls.borrow();
+        assert_eq!(calls[0].1, Duration::from_secs(60));
+        assert_eq!(
+            calls[0].0.last().map(String::as_str),
+            Some("ytsearch10:queen bohemian rhapsody")
+        );
+        assert!(calls[0].0.contains(&"--dump-json".to_string()));
+    }
+
+    #[test]
+    fn test_search_music_limits_playlist_items() {
+        let ytdlp = ytdlp(ScriptedRunner::default().succeeds(""));
+
+        let results = block_on(ytdlp.search_music("Queen", 5)).unwrap();
+
+        assert!(results.is_empty());
+        let calls = ytdlp.runner.calls.borrow();
+        let args = &calls[0].0;
+        let items = args.iter().position(|a| a == "--playlist-items").unwrap();
+        assert_eq!(args[items + 1], "1-5");
+        assert_eq!(
+            args[items + 2],
+            "https://music.youtube.com/search?q=Queen#songs"
+        );
+    }
+
+    #[test]
+    fn test_empty_queries_never_run_ytdlp() {
+        let ytdlp = ytdlp(ScriptedRunner::default());
+
+        let video = block_on(ytdlp.search_videos("   ")).unwrap_err();
+        let music = block_on(ytdlp.search_music("", 1)).unwrap_err();
+
+        assert_eq!(video.kind, ErrorKind::InvalidInput);
+        assert_eq!(music.kind, ErrorKind::InvalidInput);
+        assert!(ytdlp.runner.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_search_failure_is_classified() {
+        let ytdlp = ytdlp(
+            ScriptedRunner::default()
+                .fails("ERROR: Unable to download webpage: HTTP Error 429: Too Many Requests"),
+        );
+
+        let error = block_on(ytdlp.search_videos("song")).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::RateLimited);
+        assert!(error.retryable);
+    }
+
+    #[test]
+    fn test_download_builds_arguments_and_reports_progress() {
+        let ytdlp = ytdlp(ScriptedRunner::default().succeeds(
+            "[download] 100% of 3.2MiB\n[ExtractAudio] Destination: /music/Song [abc123def45].mp3\n",
+        ));
+        let progress = RefCell::new(Vec::new());
+        let subtitles = SubtitleOptions {
+            language: "en".to_string(),
+            ..SubtitleOptions::default()
+        };
+
+        let path = block_on(ytdlp.download(
+            "abc123def45",
+            "/music",
+            Some("/opt/ffmpeg"),
+            Some(&subtitles),
+            Some(500_000),
+            |percent, _| progress.borrow_mut().push(percent),
+        ))
+        .unwrap();
+
+        assert_eq!(path, "/music/Song [abc123def45].mp3");
+        assert_eq!(*progress.borrow(), vec![0.0, 90.0, 100.0]);
+        let calls = ytdlp.runner.calls.borrow();
+        let (args, timeout) = &calls[0];
+        assert_eq!(*timeout, Duration::from_secs(1800));
+        assert_eq!(
+            args.last().map(String::as_str),
+            Some("https://www.youtube.com/watch?v=abc123def45")
+        );
+        for expected in [
+            "/music/%(title)s [%(id)s].%(ext)s",
+            "/opt/ffmpeg",
+            "--write-subs",
+            "500000",
+        ] {
+            assert!(args.contains(&expected.to_string()), "{}", expected);
+        }
+    }
+
+    #[test]
+    fn test_download_failures() {
+        let ytdlp = ytdlp(
+            ScriptedRunner::default()
+                .fails("ERROR: [youtube] abc: Video unavailable")
+                .succeeds("[download] nothing useful\n"),
+        );
+
+        let unavailable =
+            block_on(ytdlp.download("abc", "/music", None, None, None, |_, _| {})).unwrap_err();
+        assert_eq!(unavailable.kind, ErrorKind::VideoUnavailable);
+
+        let no_filename =
+            block_on(ytdlp.download("abc", "/music", None, None, None, |_, _| {})).unwrap_err();
+        assert_eq!(no_filename.kind, ErrorKind::Internal);
+    }
+
+    #[test]
+    fn test_process_runner_missing_binary() {
+        let command = YtDlpCommand::new("/nonexistent/yt-dlp", &YtDlpSettings::default());
+
+        let error = block_on(ProcessRunner.run(&command, Duration::from_secs(5))).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::YtdlpMissing);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_runner_times_out() {
+        let command = YtDlpCommand::new("sh", &YtDlpSettings::default()).args(["-c", "sleep 5"]);
+
+        let error = block_on(ProcessRunner.run(&command, Duration::from_millis(100))).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::TimedOut);
+        assert!(!error.retryable);
+        assert!(error.message.contains("timed out"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_runner_captures_output() {
+        let command = YtDlpCommand::new("sh", &YtDlpSettings::default())
+            .args(["-c", "echo out; echo err >&2; exit 3"]);
+
+        let output = block_on(ProcessRunner.run(&command, Duration::from_secs(5))).unwrap();
+
+        assert!(!output.success);
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+    }
+}
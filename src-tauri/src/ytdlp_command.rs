@@ -0,0 +1,398 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::Path;
+use std::process::Command;
+
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+
+// The options users may add, and whether each takes a value. Anything else is refused, since
+// yt-dlp has many ways to run other programs (--exec, --use-postprocessor, --downloader), read
+// other configuration or write files and output the app doesn't expect.
+const ALLOWED_ARGS: &[(&str, bool)] = &[
+    ("-f", true),
+    ("--format", true),
+    ("-S", true),
+    ("--format-sort", true),
+    ("--audio-quality", true),
+    ("-R", true),
+    ("--retries", true),
+    ("--fragment-retries", true),
+    ("--extractor-retries", true),
+    ("--retry-sleep", true),
+    ("--socket-timeout", true),
+    ("--sleep-requests", true),
+    ("--sleep-interval", true),
+    ("--max-sleep-interval", true),
+    ("-r", true),
+    ("--limit-rate", true),
+    ("--throttled-rate", true),
+    ("-N", true),
+    ("--concurrent-fragments", true),
+    ("--http-chunk-size", true),
+    ("--geo-bypass", false),
+    ("--geo-bypass-country", true),
+    ("--geo-verification-proxy", true),
+    ("--xff", true),
+    ("--extractor-args", true),
+    ("--add-header", true),
+    ("--referer", true),
+    ("--impersonate", true),
+    ("--cookies-from-browser", true),
+    ("--no-check-certificates", false),
+    ("--prefer-insecure", false),
+    ("--legacy-server-connect", false),
+    ("-6", false),
+    ("--force-ipv6", false),
+    ("--age-limit", true),
+    ("--no-playlist", false),
+    ("--no-cache-dir", false),
+    ("--abort-on-unavailable-fragments", false),
+    ("--skip-unavailable-fragments", false),
+];
+
+/// Network and identity options applied to every yt-dlp invocation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YtDlpSettings {
+    pub proxy: Option<String>,
+    /// Netscape-format cookies file, needed for age-restricted videos.
+    pub cookies_file: Option<String>,
+    pub source_address: Option<String>,
+    pub force_ipv4: bool,
+    pub user_agent: Option<String>,
+    pub extra_args: Vec<String>,
+    /// yt-dlp is stopped if a search or download runs longer than this.
+    pub search_timeout_seconds: u64,
+    pub download_timeout_seconds: u64,
+}
+
+impl Default for YtDlpSettings {
+    fn default() -> Self {
+        YtDlpSettings {
+            proxy: None,
+            cookies_file: None,
+            source_address: None,
+            force_ipv4: false,
+            user_agent: None,
+            extra_args: Vec::new(),
+            search_timeout_seconds: 60,
+            download_timeout_seconds: 1800,
+        }
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn allowed_option(name: &str) -> Result<bool, String> {
+    ALLOWED_ARGS
+        .iter()
+        .find(|(option, _)| *option == name)
+        .map(|(_, takes_value)| *takes_value)
+        .ok_or_else(|| format!("yt-dlp option {} is not allowed", name))
+}
+
+fn validate_extra_args(args: &[String]) -> Result<(), String> {
+    let mut awaiting_value: Option<String> = None;
+    for arg in args {
+        if arg.trim().is_empty() {
+            return Err("Extra yt-dlp arguments cannot be empty".to_string());
+        }
+        if arg.chars().any(|c| c.is_control()) {
+            return Err(format!(
+                "Extra yt-dlp argument contains a control character: {:?}",
+                arg
+            ));
+        }
+        if awaiting_value.take().is_some() {
+            continue;
+        }
+
+        if arg.starts_with("--") {
+            // Long options may carry their value after "="; abbreviations are refused with the rest
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (arg.as_str(), false),
+            };
+            let takes_value = allowed_option(name)?;
+            if inline_value && !takes_value {
+                return Err(format!("yt-dlp option {} does not take a value", name));
+            }
+            if takes_value && !inline_value {
+                awaiting_value = Some(name.to_string());
+            }
+        } else if let Some(letters) = arg.strip_prefix('-').filter(|l| !l.is_empty()) {
+            // Short options can be grouped ("-6N4"), and the first one taking a value swallows
+            // the rest of the argument or the next one
+            for (index, letter) in letters.char_indices() {
+                let name = format!("-{}", letter);
+                if allowed_option(&name)? {
+                    if index + letter.len_utf8() == letters.len() {
+                        awaiting_value = Some(name);
+                    }
+                    break;
+                }
+            }
+        } else {
+            return Err(format!(
+                "Extra yt-dlp argument {:?} is not an option or an option's value",
+                arg
+            ));
+        }
+    }
+    match awaiting_value {
+        Some(name) => Err(format!("yt-dlp option {} needs a value", name)),
+        None => Ok(()),
+    }
+}
+
+impl YtDlpSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(proxy) = non_empty(&self.proxy) {
+            let url =
+                reqwest::Url::parse(proxy).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            if !PROXY_SCHEMES.contains(&url.scheme()) {
+                return Err(format!("Unsupported proxy scheme: {}", url.scheme()));
+            }
+        }
+
+        if let Some(cookies) = non_empty(&self.cookies_file) {
+            if !Path::new(cookies).is_file() {
+                return Err(format!("Cookies file not found: {}", cookies));
+            }
+        }
+
+        if let Some(address) = non_empty(&self.source_address) {
+            let ip: IpAddr = address
+                .parse()
+                .map_err(|_| format!("Invalid source address: {}", address))?;
+            if self.force_ipv4 && ip.is_ipv6() {
+                return Err("An IPv6 source address cannot be used with IPv4 forcing".to_string());
+            }
+        }
+
+        if let Some(agent) = non_empty(&self.user_agent) {
+            if agent.chars().any(|c| c.is_control()) {
+                return Err("User agent cannot contain control characters".to_string());
+            }
+        }
+
+        if self.search_timeout_seconds == 0 || self.download_timeout_seconds == 0 {
+            return Err("yt-dlp timeouts must be at least one second".to_string());
+        }
+
+        validate_extra_args(&self.extra_args)
+    }
+
+    fn global_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(proxy) = non_empty(&self.proxy) {
+            args.extend(["--proxy".to_string(), proxy.to_string()]);
+        }
+        if let Some(cookies) = non_empty(&self.cookies_file) {
+            args.extend(["--cookies".to_string(), cookies.to_string()]);
+        }
+        if let Some(address) = non_empty(&self.source_address) {
+            args.extend(["--source-address".to_string(), address.to_string()]);
+        }
+        if self.force_ipv4 {
+            args.push("--force-ipv4".to_string());
+        }
+        if let Some(agent) = non_empty(&self.user_agent) {
+            args.extend(["--user-agent".to_string(), agent.to_string()]);
+        }
+        args
+    }
+}
+
+/// Builds a yt-dlp invocation. The settings' global options come first and the user's extra
+/// arguments last, so they can override anything the app sets for a particular call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YtDlpCommand {
+    program: String,
+    global_args: Vec<String>,
+    extra_args: Vec<String>,
+    args: Vec<String>,
+}
+
+impl YtDlpCommand {
+    pub fn new(program: &str, settings: &YtDlpSettings) -> YtDlpCommand {
+        YtDlpCommand {
+            program: program.to_string(),
+            global_args: settings.global_args(),
+            extra_args: settings.extra_args.clone(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> YtDlpCommand {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> YtDlpCommand
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn full_args(&self) -> Vec<String> {
+        let mut args = self.global_args.clone();
+        args.extend(self.args.iter().cloned());
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(self.full_args());
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_options_come_first_and_extra_args_last() {
+        let settings = YtDlpSettings {
+            proxy: Some("socks5://127.0.0.1:1080".to_string()),
+            cookies_file: Some("/home/me/cookies.txt".to_string()),
+            source_address: Some("0.0.0.0".to_string()),
+            force_ipv4: true,
+            user_agent: Some("Mozilla/5.0".to_string()),
+            extra_args: vec!["--format".to_string(), "bestaudio[abr>=160]".to_string()],
+            ..YtDlpSettings::default()
+        };
+
+        let command = YtDlpCommand::new("yt-dlp", &settings)
+            .args(["--format", "bestaudio"])
+            .arg("https://www.youtube.com/watch?v=abc");
+
+        assert_eq!(command.command().get_program(), "yt-dlp");
+        assert_eq!(
+            command.full_args(),
+            vec![
+                "--proxy",
+                "socks5://127.0.0.1:1080",
+                "--cookies",
+                "/home/me/cookies.txt",
+                "--source-address",
+                "0.0.0.0",
+                "--force-ipv4",
+                "--user-agent",
+                "Mozilla/5.0",
+                "--format",
+                "bestaudio",
+                "https://www.youtube.com/watch?v=abc",
+                "--format",
+                "bestaudio[abr>=160]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_settings_add_nothing() {
+        let command = YtDlpCommand::new("yt-dlp", &YtDlpSettings::default()).arg("--version");
+        assert_eq!(command.full_args(), vec!["--version"]);
+    }
+
+    #[test]
+    fn test_validate_proxy_and_source_address() {
+        assert!(YtDlpSettings::default().validate().is_ok());
+        assert!(YtDlpSettings {
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            ..YtDlpSettings::default()
+        }
+        .validate()
+        .is_ok());
+        assert!(YtDlpSettings {
+            proxy: Some("ftp://proxy.corp".to_string()),
+            ..YtDlpSettings::default()
+        }
+        .validate()
+        .is_err());
+        assert!(YtDlpSettings {
+            source_address: Some("not an ip".to_string()),
+            ..YtDlpSettings::default()
+        }
+        .validate()
+        .is_err());
+        assert!(YtDlpSettings {
+            source_address: Some("::1".to_string()),
+            force_ipv4: true,
+            ..YtDlpSettings::default()
+        }
+        .validate()
+        .is_err());
+        assert!(YtDlpSettings {
+            cookies_file: Some("/nonexistent/cookies.txt".to_string()),
+            ..YtDlpSettings::default()
+        }
+        .validate()
+        .is_err());
+        assert!(YtDlpSettings {
+            download_timeout_seconds: 0,
+            ..YtDlpSettings::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_extra_args() {
+        let extra = |args: &[&str]| {
+            YtDlpSettings {
+                extra_args: args.iter().map(|a| a.to_string()).collect(),
+                ..YtDlpSettings::default()
+            }
+            .validate()
+        };
+
+        assert!(extra(&["--geo-bypass", "--retries", "5"]).is_ok());
+        assert!(extra(&["--format=bestaudio", "-f140", "-6N", "4"]).is_ok());
+        assert!(extra(&["--exec", "rm -rf ~"]).is_err());
+        assert!(extra(&["--output=%(id)s.%(ext)s"]).is_err());
+        assert!(extra(&["-j"]).is_err());
+        assert!(extra(&["-o%(id)s.%(ext)s"]).is_err());
+        assert!(extra(&["--retries\n5"]).is_err());
+    }
+
+    #[test]
+    fn test_validate_extra_args_refuses_bypasses() {
+        let extra = |args: &[&str]| {
+            YtDlpSettings {
+                extra_args: args.iter().map(|a| a.to_string()).collect(),
+                ..YtDlpSettings::default()
+            }
+            .validate()
+        };
+
+        assert!(extra(&["--use-postprocessor", "Exec:exec_cmd=touch /tmp/x"]).is_err());
+        assert!(extra(&["--use-postprocessor=Exec:exec_cmd=touch /tmp/x"]).is_err());
+        assert!(extra(&["--ppa", "ffmpeg:-i /etc/passwd"]).is_err());
+        assert!(extra(&["--postprocessor-args", "ffmpeg:-i /etc/passwd"]).is_err());
+        assert!(extra(&["--exec-before-download", "sh"]).is_err());
+        assert!(extra(&["--exe", "sh"]).is_err());
+        assert!(extra(&["--config-location", "/tmp/evil.conf"]).is_err());
+        assert!(extra(&["--config-locations", "/tmp/evil.conf"]).is_err());
+        assert!(extra(&["--downloader", "sh"]).is_err());
+        // Grouped short flags are checked one by one
+        assert!(extra(&["-xj"]).is_err());
+        assert!(extra(&["-sj"]).is_err());
+        assert!(extra(&["-6j"]).is_err());
+        // A value can't be passed to an option that takes none, nor left out
+        assert!(extra(&["--geo-bypass=--exec"]).is_err());
+        assert!(extra(&["--retries"]).is_err());
+        // Anything else would be taken as another URL to download
+        assert!(extra(&["https://example.com/video"]).is_err());
+        assert!(extra(&["-"]).is_err());
+        assert!(extra(&["--"]).is_err());
+        assert!(extra(&[" "]).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use std::fs;
-use std::path::PathBuf;
-use tauri::{Emitter, Manager};
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult, ErrorKind};
 
 #[cfg(target_os = "windows")]
 const YTDLP_FILENAME: &str = "yt-dlp.exe";
@@ -12,94 +13,68 @@ const YTDLP_DOWNLOAD_URL: &str = "https://github.com/yt-dlp/yt-dlp/releases/late
 #[cfg(not(target_os = "windows"))]
 const YTDLP_DOWNLOAD_URL: &str = "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp";
 
-pub fn get_ytdlp_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    fs::create_dir_all(&data_dir)
-        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+pub fn get_ytdlp_path(data_dir: &Path) -> AppResult<PathBuf> {
+    fs::create_dir_all(data_dir)
+        .map_err(|e| AppError::io("Failed to create app data dir", e))?;
     
     Ok(data_dir.join(YTDLP_FILENAME))
 }
 
-#[tauri::command]
-pub fn check_ytdlp(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let path = get_ytdlp_path(&app_handle)?;
+#[cfg(feature = "gui")]
+pub fn check_ytdlp(data_dir: &Path) -> AppResult<String> {
+    let path = get_ytdlp_path(data_dir)?;
     
     if path.exists() {
         Ok(path.to_string_lossy().to_string())
     } else {
-        Err("yt-dlp not installed".to_string())
+        Err(AppError::new(ErrorKind::YtdlpMissing, "yt-dlp not installed"))
     }
 }
 
-#[tauri::command]
-pub async fn download_ytdlp(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let path = get_ytdlp_path(&app_handle)?;
+/// Reports each step through `on_progress(status, message)`.
+pub async fn download_ytdlp(data_dir: &Path, on_progress: impl Fn(&str, &str)) -> AppResult<String> {
+    let path = get_ytdlp_path(data_dir)?;
     
     if path.exists() {
-        let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-            "status": "already_installed",
-            "message": "yt-dlp already installed"
-        }));
+        on_progress("already_installed", "yt-dlp already installed");
         return Ok(path.to_string_lossy().to_string());
     }
     
-    let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-        "status": "downloading",
-        "message": "Starting download..."
-    }));
+    on_progress("downloading", "Starting download...");
     
     let response = reqwest::get(YTDLP_DOWNLOAD_URL)
         .await
         .map_err(|e| {
-            let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-                "status": "error",
-                "message": format!("Failed to connect: {}", e)
-            }));
-            format!("Failed to download yt-dlp: {}", e)
+            on_progress("error", &format!("Failed to connect: {}", e));
+            AppError::new(ErrorKind::Network, format!("Failed to download yt-dlp: {}", e))
         })?;
     
     if !response.status().is_success() {
-        let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-            "status": "error",
-            "message": format!("HTTP error: {}", response.status())
-        }));
-        return Err(format!("Failed to download yt-dlp: HTTP {}", response.status()));
+        on_progress("error", &format!("HTTP error: {}", response.status()));
+        return Err(AppError::http_status(
+            "Failed to download yt-dlp",
+            response.status().as_u16(),
+        ));
     }
     
     let total_size = response.content_length().unwrap_or(0);
     
-    let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-        "status": "downloading",
-        "message": format!("Downloading {} bytes...", total_size)
-    }));
+    on_progress("downloading", &format!("Downloading {} bytes...", total_size));
     
     let bytes = response
         .bytes()
         .await
         .map_err(|e| {
-            let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-                "status": "error",
-                "message": format!("Failed to read response: {}", e)
-            }));
-            format!("Failed to read response body: {}", e)
+            on_progress("error", &format!("Failed to read response: {}", e));
+            AppError::new(ErrorKind::Network, format!("Failed to read response body: {}", e))
         })?;
     
-    let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-        "status": "installing",
-        "message": "Writing binary to disk..."
-    }));
+    on_progress("installing", "Writing binary to disk...");
     
     fs::write(&path, &bytes)
         .map_err(|e| {
-            let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-                "status": "error",
-                "message": format!("Failed to write file: {}", e)
-            }));
-            format!("Failed to write yt-dlp binary: {}", e)
+            on_progress("error", &format!("Failed to write file: {}", e));
+            AppError::io("Failed to write yt-dlp binary", e)
         })?;
     
     #[cfg(unix)]
@@ -107,28 +82,25 @@ pub async fn download_ytdlp(app_handle: tauri::AppHandle) -> Result<String, Stri
         use std::os::unix::fs::PermissionsExt;
         fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
             .map_err(|e| {
-                let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-                    "status": "error",
-                    "message": format!("Failed to set permissions: {}", e)
-                }));
-                format!("Failed to set executable permissions: {}", e)
+                on_progress("error", &format!("Failed to set permissions: {}", e));
+                AppError::io("Failed to set executable permissions", e)
             })?;
     }
     
-    let _ = app_handle.emit("ytdlp-progress", serde_json::json!({
-        "status": "complete",
-        "message": "yt-dlp installed successfully"
-    }));
+    on_progress("complete", "yt-dlp installed successfully");
     
     Ok(path.to_string_lossy().to_string())
 }
 
-pub fn get_ytdlp_command(app_handle: &tauri::AppHandle) -> Result<String, String> {
-    let path = get_ytdlp_path(app_handle)?;
+pub fn get_ytdlp_command(data_dir: &Path) -> AppResult<String> {
+    let path = get_ytdlp_path(data_dir)?;
     
     if path.exists() {
         Ok(path.to_string_lossy().to_string())
     } else {
-        Err("yt-dlp not installed. Please wait for it to download.".to_string())
+        Err(AppError::new(
+            ErrorKind::YtdlpMissing,
+            "yt-dlp not installed. Please wait for it to download.",
+        ))
     }
 }
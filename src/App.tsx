@@ -2,13 +2,15 @@ import { useState, useEffect, useRef } from "react";
 import { Button } from "./components/ui/Button";
 import { Card, CardHeader, CardTitle, CardContent, CardDescription } from "./components/ui/Card";
 import { MainLayout, MainLayoutHeader, MainLayoutFooter, AudioModeSelector, DownloadInput, ActionButtons, ProgressIndicator } from "./components/layout/MainLayout";
-import { DownloadProvider, useDownloadStore, AudioMode, CsvImportResult } from "./store/DownloadStore";
+import { DownloadProvider, useDownloadStore, AudioMode, BatchReportRow, CsvImportResult, InputError, QueueEntry, QueueItem } from "./store/DownloadStore";
 import { AboutModal } from "./components/AboutModal";
 import { SetupOverlay } from "./components/SetupOverlay";
 import { FfmpegWarning } from "./components/FfmpegWarning";
 import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
+import { listen } from "@tauri-apps/api/event";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { downloadDir } from "@tauri-apps/api/path";
+import { formatError } from "@/lib/utils";
 import { FolderOpen, RotateCcw, Settings, Heart, Info, CheckCircle2, XCircle, Download } from "lucide-react";
 
 type SetupStatus = "checking" | "downloading" | "ready" | "error";
@@ -308,6 +310,7 @@ function DownloadForm() {
   } = useDownloadStore();
 
   const [settingsOpen, setSettingsOpen] = useState(false);
+  const [lastReport, setLastReport] = useState<BatchReportRow[]>([]);
   const [ytdlpStatus, setYtdlpStatus] = useState<'detected' | 'missing' | 'checking' | 'downloading'>('checking');
   const [ytdlpPath, setYtdlpPath] = useState('');
   const [ffmpegStatus, setFfmpegStatus] = useState<'detected' | 'missing' | 'checking' | 'downloading'>('checking');
@@ -331,6 +334,29 @@ function DownloadForm() {
   useEffect(() => {
     checkYtdlpStatus();
     checkFfmpegStatus();
+    invoke("check_local_server").catch((error) => {
+      setStatus(`Local server not started: ${formatError(error)}`);
+    });
+  }, []);
+
+  useEffect(() => {
+    // Other tools and the watch folders add downloads too; they run in the same queue
+    const unlisten = listen<QueueEntry>("queue-item-finished", (event) => {
+      if (event.payload.source !== "app") {
+        const origin = event.payload.source === "http" ? "Remote request" : "Watch folder";
+        setStatus(`${origin} ${event.payload.status.replace(/_/g, " ")}: ${event.payload.item.input}`);
+      }
+    });
+    const unlistenWatchErrors = listen<{ path: string | null; error: unknown }>("watch-folder-error", (event) => {
+      const { path, error } = event.payload;
+      setStatus(path
+        ? `Watch folder file ${path} not processed: ${formatError(error)}`
+        : `Watch folders paused: ${formatError(error)}`);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenWatchErrors.then((fn) => fn());
+    };
   }, []);
 
   const checkYtdlpStatus = async () => {
@@ -370,7 +396,7 @@ function DownloadForm() {
       console.error("Failed to download FFmpeg:", error);
       setFfmpegSource('');
       setFfmpegStatus('missing');
-      setStatus(`Failed to download FFmpeg: ${error}`);
+      setStatus(`Failed to download FFmpeg: ${formatError(error)}`);
     }
   };
 
@@ -384,7 +410,7 @@ function DownloadForm() {
     } catch (error) {
       console.error("Failed to download yt-dlp:", error);
       setYtdlpStatus('missing');
-      setStatus(`Failed to download yt-dlp: ${error}`);
+      setStatus(`Failed to download yt-dlp: ${formatError(error)}`);
     }
   };
 
@@ -405,7 +431,7 @@ function DownloadForm() {
         await invoke("open_folder", { path: downloadPath });
       } catch (error) {
         console.error("Failed to open folder:", error);
-        setStatus(`Failed to open folder: ${error}`);
+        setStatus(`Failed to open folder: ${formatError(error)}`);
       }
     } else {
       setStatus("No download path selected");
@@ -416,7 +442,7 @@ function DownloadForm() {
     try {
       const selected = await open({
         multiple: false,
-        filters: [{ name: 'CSV', extensions: ['csv'] }]
+        filters: [{ name: 'Track lists', extensions: ['csv', 'tsv', 'txt', 'm3u', 'm3u8', 'pls', 'xspf', 'xml'] }]
       });
 
       if (!selected) {
@@ -424,9 +450,8 @@ function DownloadForm() {
       }
 
       const filePath = selected as string;
-      const content = await invoke<string>("read_file_command", { path: filePath });
       const result = await invoke<CsvImportResult>("parse_csv_command", { 
-        csvContent: content, 
+        csvPath: filePath, 
         audioMode: audioMode as string 
       });
 
@@ -434,13 +459,38 @@ function DownloadForm() {
         setCsvData(result);
         const queries = result.tracks.map(t => t.search_query).join('\n');
         setInputText(queries);
-        setStatus(`Imported ${result.success_count} tracks from CSV`);
+        const details = [result.profile && `${result.profile} format`, result.format?.encoding].filter(Boolean);
+        const profileNote = details.length > 0 ? ` (${details.join(", ")})` : "";
+        setStatus(`Imported ${result.success_count} tracks${profileNote}`);
       } else if (result) {
-        setStatus(`CSV import had ${result.error_count} errors`);
+        setStatus(`Import had ${result.error_count} errors`);
+      }
+    } catch (error) {
+      console.error("Failed to import track list:", error);
+      setStatus(`Import failed: ${formatError(error)}`);
+    }
+  };
+
+  const handleExportReport = async () => {
+    try {
+      const reportPath = await save({
+        defaultPath: downloadPath ? `${downloadPath}/download-report.csv` : "download-report.csv",
+        filters: [
+          { name: 'CSV', extensions: ['csv'] },
+          { name: 'JSON', extensions: ['json'] },
+        ]
+      });
+
+      if (!reportPath) {
+        return;
       }
+
+      const playlistPath = reportPath.replace(/\.(csv|json)$/i, "") + ".m3u8";
+      await invoke("export_batch_report", { rows: lastReport, reportPath, playlistPath });
+      setStatus(`Saved report to ${reportPath} and playlist to ${playlistPath}`);
     } catch (error) {
-      console.error("Failed to import CSV:", error);
-      setStatus(`CSV import failed: ${error}`);
+      console.error("Failed to export report:", error);
+      setStatus(`Report export failed: ${formatError(error)}`);
     }
   };
 
@@ -454,14 +504,19 @@ function DownloadForm() {
       console.log("Starting download process...");
       const result = await invoke<{
         items: Array<{
+          line_number: number;
           input_type: string;
           original_input: string;
           processed_query: string;
           video_id: string | null;
+          source: string | null;
+          metadata_override: Record<string, string | null> | null;
+          duration_seconds: number | null;
         }>;
         total_count: number;
         url_count: number;
         search_count: number;
+        errors: InputError[];
       }>("process_input", { inputText, audioMode: audioMode as string });
 
       console.log("Processed input result:", result);
@@ -479,15 +534,15 @@ function DownloadForm() {
 
       const canUseCsvMetadata = csvData && csvData.tracks.length === result.items.length;
       const errors: string[] = [];
+      const deduplicated: string[] = [];
 
-      for (let i = 0; i < result.items.length; i++) {
-        const item = result.items[i];
-        setCurrentItem(i + 1);
-        setProgress((i / result.total_count) * 100);
-        
-        let metadataOverride = null;
+      const items: QueueItem[] = result.items.map((item, i) => {
+        let rowNumber = item.line_number;
+        let metadataOverride: Record<string, string | null | undefined> | null = item.metadata_override;
+        let searchHints = { isrc: null as string | null | undefined, duration_seconds: item.duration_seconds };
         if (canUseCsvMetadata) {
             const track = csvData.tracks[i];
+            rowNumber = track.row_number;
             if (item.original_input.trim() === track.search_query.trim() || 
                 item.processed_query.includes(track.search_query.trim())) {
                 metadataOverride = {
@@ -496,50 +551,103 @@ function DownloadForm() {
                     album: track.metadata.album_name,
                     genre: track.metadata.artist_genres,
                     year: track.metadata.album_release_date,
+                    track_number: track.metadata.track_number,
+                    disc_number: track.metadata.disc_number,
+                    album_artist: track.metadata.album_artist_names,
+                    label: track.metadata.label,
+                    isrc: track.metadata.isrc,
+                };
+                searchHints = {
+                    isrc: track.metadata.isrc,
+                    duration_seconds: track.duration_seconds ?? item.duration_seconds,
                 };
             }
         }
+        return {
+          row_number: rowNumber,
+          input: item.original_input,
+          // YouTube links download by ID; links from other sites go to yt-dlp as-is
+          target: item.video_id ?? (item.input_type === "Url" ? item.processed_query : null),
+          search_query: item.input_type === "SearchQuery" ? item.processed_query : null,
+          search_hints: searchHints,
+          metadata_override: metadataOverride,
+        };
+      });
 
-        try {
-          let videoId = item.video_id;
-
-          if (item.input_type === "SearchQuery" && !videoId) {
-            setStatus(`Searching: ${item.processed_query}`);
-            const videoInfo = await invoke<{ id: string } | null>("search_video_command", {
-              query: item.processed_query,
-            });
-            if (videoInfo) {
-                videoId = videoInfo.id;
-            }
-          }
+      // The batch runs in the app's shared queue, next to anything added over the local server
+      const finished = new Map<number, QueueEntry>();
+      let ids: number[] | null = null;
+      let resolveBatch = () => {};
+      const batchDone = new Promise<void>((resolve) => { resolveBatch = resolve; });
+      const checkDone = () => {
+        if (ids && ids.every((id) => finished.has(id))) {
+          resolveBatch();
+        }
+      };
+      const unlistenStarted = await listen<QueueEntry>("queue-item-started", (event) => {
+        const index = ids ? ids.indexOf(event.payload.id) : -1;
+        if (index >= 0) {
+          setCurrentItem(index + 1);
+          setStatus(`Processing: ${event.payload.item.input}`);
+        }
+      });
+      const unlistenFinished = await listen<QueueEntry>("queue-item-finished", (event) => {
+        finished.set(event.payload.id, event.payload);
+        if (ids && ids.includes(event.payload.id)) {
+          setProgress((ids.filter((id) => finished.has(id)).length / ids.length) * 100);
+        }
+        checkDone();
+      });
+      try {
+        ids = await invoke<number[]>("enqueue_items", { items, outputPath, audioMode: audioMode as string });
+        checkDone();
+        await batchDone;
+      } finally {
+        unlistenStarted();
+        unlistenFinished();
+      }
 
-          if (videoId) {
-            setStatus(`Processing: ${item.original_input}`);
-            await invoke<string>("process_item", {
-              videoId,
-              outputPath,
-              metadataOverride
-            });
-          } else {
-             setStatus(`Not found: ${item.processed_query}`);
-          }
-        } catch (itemError) {
-          const errMsg = `Item ${i + 1} (${item.original_input}): ${itemError}`;
+      // Inputs that never became an item get a failed row of their own, numbered like the
+      // imported track list when the input is still that list
+      const imported = csvData && csvData.tracks.map((t) => t.search_query).join('\n') === inputText
+        ? csvData
+        : null;
+      const inputErrors: InputError[] = imported
+        ? [
+            ...result.errors.map((error) => ({
+              ...error,
+              row_number: imported.tracks[error.row_number - 1]?.row_number ?? error.row_number,
+            })),
+            ...imported.errors,
+          ]
+        : result.errors;
+      const reportRows = await invoke<BatchReportRow[]>("build_batch_report", {
+        ids: ids ?? [],
+        inputErrors,
+      });
+      for (const reportRow of reportRows) {
+        if (reportRow.status === "failed") {
+          const errMsg = reportRow.query ? `${reportRow.query}: ${reportRow.error}` : `${reportRow.error}`;
           console.error(errMsg);
           errors.push(errMsg);
-          setStatus(`Error: ${errMsg}`);
+        } else if (reportRow.status === "deduplicated") {
+          deduplicated.push(`${reportRow.query} (already downloaded)`);
         }
       }
+      setLastReport(reportRows);
       
       setProgress(100);
+      const dedupeSummary = deduplicated.length > 0
+        ? ` Deduplicated ${deduplicated.length}: ${deduplicated.join(' | ')}`
+        : "";
       if (errors.length > 0) {
-        setStatus(`Failed ${errors.length}/${result.total_count}: ${errors.join(' | ')}`);
+        setStatus(`Failed ${errors.length}/${reportRows.length}: ${errors.join(' | ')}${dedupeSummary}`);
       } else {
-        setStatus("Download complete!");
+        setStatus(`Download complete!${dedupeSummary}`);
       }
     } catch (error) {
       console.error("Download failed:", error);
-      setStatus(`Error: ${error}`);
+      setStatus(`Error: ${formatError(error)}`);
     }
   };
 
@@ -580,6 +688,7 @@ function DownloadForm() {
             <ActionButtons
               onImportCsv={handleImportCsv}
               onDownload={handleDownload}
+              onExportReport={lastReport.length > 0 ? handleExportReport : undefined}
               isImportDisabled={inputText.length > 0}
             />
           </CardContent>
@@ -2,6 +2,7 @@ import { useState } from "react";
 import { Modal, ModalHeader, ModalTitle, ModalDescription, ModalContent, ModalFooter } from "./ui/Modal";
 import { Button } from "./ui/Button";
 import { invoke } from "@tauri-apps/api/core";
+import { formatError } from "@/lib/utils";
 
 export function FfmpegWarning({
   isOpen,
@@ -22,7 +23,7 @@ export function FfmpegWarning({
       onClose();
     } catch (e) {
       setDownloadStatus("error");
-      setDownloadError(String(e));
+      setDownloadError(formatError(e));
     }
   };
 
@@ -3,7 +3,7 @@ import { cn } from "@/lib/utils";
 import { Button } from "@/components/ui/Button";
 import { Input } from "@/components/ui/Input";
 import { Textarea } from "@/components/ui/Textarea";
-import { FolderOpen, Download, Upload, Play, Radio, Music, Mic2 } from "lucide-react";
+import { FolderOpen, Download, Upload, Play, Radio, Music, Mic2, FileText } from "lucide-react";
 
 interface MainLayoutProps extends React.HTMLAttributes<HTMLDivElement> {
   children?: React.ReactNode;
@@ -203,11 +203,12 @@ function DownloadInput({ value, onChange, placeholder, disabled, className }: Do
 interface ActionButtonsProps {
   onImportCsv: () => void;
   onDownload: () => void;
+  onExportReport?: () => void;
   isImportDisabled?: boolean;
   isDownloadDisabled?: boolean;
 }
 
-function ActionButtons({ onImportCsv, onDownload, isImportDisabled, isDownloadDisabled }: ActionButtonsProps) {
+function ActionButtons({ onImportCsv, onDownload, onExportReport, isImportDisabled, isDownloadDisabled }: ActionButtonsProps) {
   return (
     <div className="flex gap-3 pt-2">
       <Button
@@ -219,6 +220,16 @@ function ActionButtons({ onImportCsv, onDownload, isImportDisabled, isDownloadDi
         <Upload className="w-4 h-4 mr-2" />
         Import CSV
       </Button>
+      {onExportReport && (
+        <Button
+          variant="outline"
+          onClick={onExportReport}
+          className="flex-1"
+        >
+          <FileText className="w-4 h-4 mr-2" />
+          Export Report
+        </Button>
+      )}
       <Button
         variant="default"
         size="lg"
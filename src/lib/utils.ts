@@ -4,3 +4,22 @@ import { twMerge } from "tailwind-merge";
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+export interface AppError {
+  kind: string;
+  message: string;
+  retryable: boolean;
+  hint: string | null;
+}
+
+function isAppError(error: unknown): error is AppError {
+  return typeof error === "object" && error !== null && "message" in error && "kind" in error;
+}
+
+// Commands reject with a structured AppError; anything else (plugin errors, thrown JS) is stringified
+export function formatError(error: unknown): string {
+  if (isAppError(error)) {
+    return error.hint ? `${error.message} (${error.hint})` : error.message;
+  }
+  return String(error);
+}
@@ -10,12 +10,28 @@ export interface CsvTrackMetadata {
   artist_genres?: string;
   album_release_date?: string;
   bpm_tempo?: string;
+  isrc?: string;
+  duration?: string;
+  track_number?: string;
+  disc_number?: string;
+  album_artist_names?: string;
+  label?: string;
+  explicit?: string;
+  popularity?: string;
+  spotify_uri?: string;
 }
 
 export interface CsvTrackEntry {
   row_number: number;
   metadata: CsvTrackMetadata;
   search_query: string;
+  duration_seconds?: number | null;
+}
+
+export interface InputError {
+  row_number: number;
+  input: string;
+  message: string;
 }
 
 export interface CsvImportResult {
@@ -23,7 +39,60 @@ export interface CsvImportResult {
   total_count: number;
   success_count: number;
   error_count: number;
-  errors: string[];
+  errors: InputError[];
+  profile?: string | null;
+  format?: CsvFormat | null;
+}
+
+export interface CsvFormat {
+  encoding: string;
+  bom: boolean;
+  delimiter: string;
+  quote: string;
+}
+
+export interface VideoInfo {
+  id: string;
+  title: string;
+  url: string;
+  thumbnail_url?: string | null;
+  uploader?: string | null;
+  duration_seconds?: number | null;
+  upload_date?: string | null;
+  description?: string | null;
+  match_score?: number | null;
+}
+
+export interface BatchReportRow {
+  row_number: number;
+  query: string;
+  video_id: string | null;
+  video_title: string | null;
+  match_score: number | null;
+  status: "downloaded" | "deduplicated" | "not_found" | "failed";
+  error: string | null;
+  output_path: string | null;
+}
+
+export interface QueueItem {
+  row_number: number;
+  input: string;
+  target: string | null;
+  search_query: string | null;
+  search_hints: { isrc?: string | null; duration_seconds?: number | null };
+  metadata_override: Record<string, string | null | undefined> | null;
+}
+
+export type QueueStatus = "queued" | "running" | "downloaded" | "deduplicated" | "not_found" | "failed" | "cancelled";
+
+export interface QueueEntry {
+  id: number;
+  source: "app" | "http" | "watch_folder";
+  item: QueueItem;
+  output_path: string;
+  audio_mode: AudioMode;
+  status: QueueStatus;
+  report: BatchReportRow | null;
 }
 
 interface DownloadState {
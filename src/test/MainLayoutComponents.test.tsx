@@ -96,6 +96,16 @@ describe("ActionButtons Component", () => {
     expect(handleDownload).toHaveBeenCalledTimes(1);
   });
 
+  it("only renders export report button when a handler is given", () => {
+    const handleExportReport = vi.fn();
+    const { rerender } = render(<ActionButtons onImportCsv={() => {}} onDownload={() => {}} />);
+    expect(screen.queryByRole("button", { name: "Export Report" })).not.toBeInTheDocument();
+
+    rerender(<ActionButtons onImportCsv={() => {}} onDownload={() => {}} onExportReport={handleExportReport} />);
+    fireEvent.click(screen.getByRole("button", { name: "Export Report" }));
+    expect(handleExportReport).toHaveBeenCalledTimes(1);
+  });
+
   it("disables import button when isImportDisabled is true", () => {
     render(<ActionButtons onImportCsv={() => {}} onDownload={() => {}} isImportDisabled />);
     expect(screen.getByRole("button", { name: "Import CSV" })).toBeDisabled();